@@ -45,19 +45,534 @@ Each stage groups closely related _actions_ together,
 and they are applied in a particular order. If any of the stages
 failed, ali-rs exits.
 
+With `ali-rs apply -n` (dry-run), ali-rs still validates the manifest,
+but only prints commands, file writes, and downloads it would have performed,
+without touching any disk or the install location. This includes files
+written by manifest key `files` and by ali-rs hooks.
+
+### Command logs
+
+Output of every command is shown on the console as usual,
+and is also captured per action:
+
+- The JSON report lists `actions` in order, each with commands it ran,
+  their exit codes, durations, and the last 20 lines of stdout and stderr
+
+- Full output of all commands is written to the log file
+  (`/tmp/ali-rs/ali-rs.log`, or `--log-file`)
+
+- If an action failed, the JSON error includes its commands,
+  plus `exitCode`, `duration`, and `stderrTail` of the failed command
+
+### Progress events
+
+With `ali-rs apply --events jsonl`, ali-rs streams progress events
+as JSON lines, e.g. to drive a dashboard or a TUI, in addition to the final report.
+
+Each event has `event`, `stage`, and a Unix `timestamp` in milliseconds:
+
+- `stageStart`, `stageEnd` (with `duration`), and `stageFailed` (with `error`)
+
+- `actionStart`, `actionEnd` (with `action` and `duration`),
+  and `actionFailed` (with `action` and `error`).
+  Actions are numbered by `index` within their stage
+
+- `command`, with command line `cmd`, for each command run by the action
+
+- `report`, the last event, with the same keys as the final JSON report
+
+Events are written to stdout by default, in which case command output
+and dry-run records are shown on stderr instead, and the report
+is only emitted as the `report` event. Use `--events-to` to write
+events elsewhere, e.g. `--events-to /dev/fd/3` for file descriptor 3.
+
+### Timing
+
+The JSON report includes `elapsedTime`, and every action in `actions`
+has its Unix `start` time in milliseconds and `duration`.
+
+A machine-readable summary is under `timing`:
+
+- `stages`, each with `stage`, `start`, `duration`, and whether it `failed`
+
+- `slowestActions`, the 5 slowest actions
+
+- `pacstrap` and `chroot`, total time spent in `pacstrap`
+  and `arch-chroot` commands
+
+- `host`, with the live system `kernel` release and archiso `isoVersion`
+
+### Install records
+
+After a successful `apply`, ali-rs keeps records of how the system was built
+in `/var/log/ali-rs` under the install location:
+
+- `manifest.yaml`, the effective manifest, including packages added by ali-rs,
+  with secrets redacted
+
+- `plan.json`, with stages applied and skipped, block devices,
+  and the kernel command line
+
+- `report.json`, the JSON report
+
+Records are only readable by root. Redacted secrets in the recorded
+`manifest.yaml` fail secret resolution, so to reuse it, replace them
+with secret references such as `prompt`.
+
+Failing to write records only prints a warning, since the installation is done.
+
+### Error codes
+
+Errors are printed to stderr as JSON, with a stable error `code`
+such as `E-BLK-005` (LVM VG validation failed). Codes never change meaning,
+so automation can match codes instead of error messages.
+
+Block device errors have codes per component (e.g. `E-BLK-002` for
+partitions), and common sub-causes have their own codes across components:
+`E-BLK-012` (device already in use), `E-BLK-013` (partitions overlap),
+and `E-BLK-014` (LVs exceed their VG).
+
+Each class of errors exits with its own exit code:
+
+| Class           | Codes                | Exit code |
+| --------------- | -------------------- | --------- |
+| CLI arguments   | `E-ARG-*`            | 2         |
+| Manifest        | `E-MAN-*`            | 3         |
+| Validation      | `E-VAL-*`, `E-BLK-*` | 4         |
+| Commands        | `E-CMD-*`            | 5         |
+| Files           | `E-FIL-*`            | 6         |
+| Hooks           | `E-HOK-*`            | 7         |
+| Not implemented | `E-IMP-*`            | 8         |
+| ali-rs bugs     | `E-BUG-*`            | 70        |
+
+Errors during installation have the code of the error that caused them,
+e.g. `E-CMD-002` if `pacstrap` exited with error.
+
+To list all codes, or to explain a code:
+
+```shell
+ali-rs errors list
+ali-rs errors explain E-BLK-005
+```
+
+## Pacman in ali-rs
+
+pacman options, mirrors, and extra repositories can be configured via
+manifest key `pacman`. ali-rs writes the configuration to the live system
+for `pacstrap -C`, and to the target's `/etc/pacman.conf` and
+`/etc/pacman.d/mirrorlist` after bootstrap.
+
+```yaml
+pacman:
+  mirrors:
+    - http://mirror.local/archlinux/$repo/os/$arch
+  parallel_downloads: 5
+  color: true
+  ignore_pkg: [linux]
+  repos:
+    - name: multilib # uses mirrorlist
+    - name: custom
+      servers: [http://repo.local/$arch]
+      sig_level: Optional TrustAll
+```
+
+### Keyring
+
+A stale keyring on an old live ISO often fails `pacstrap`. Before
+`pacstrap`, ali-rs can prepare the live system's keyring with
+`pacman-key --init` and `--populate`, upgrade `archlinux-keyring`,
+and import and locally sign extra keys (e.g. for custom repos),
+from a keyserver or an armored key file.
+
+Because `pacstrap -K` gives the target a fresh keyring, the extra keys
+are imported and locally signed again in the target after `pacstrap`.
+`keyring` is a top-level key, so it does not write `pacman.conf`.
+
+```yaml
+keyring:
+  init: true
+  upgrade: true
+  keys:
+    - id: F3B607488DB35A47
+      keyserver: hkps://keyserver.ubuntu.com
+    - id: 3056513887B78AEB8BF4E0A3E5F0D3B4C6F0A3B1
+      file: ./keys/custom-repo.asc
+```
+
+### Package lists, groups, and local packages
+
+Besides `pacstraps`, packages can come from package-list files
+(one package per line, `#` starts a comment), and from package groups,
+where either `include` or `exclude` selects group members.
+Package-list paths are relative to the manifest file, and lists are
+merged into `pacstraps` before validation.
+
+Package groups are resolved when `apply` syncs package databases
+to check packages. With `--no-validate`, `--no-sync-check`, or `--dry-run`,
+groups are instead passed to `pacstrap` by name (or as their `include`d
+members), so groups with `exclude` need package database sync.
+
+Local package files (`.pkg.tar.zst`) on the live system are copied into
+the target's package cache and installed with `pacman -U` in `arch-chroot`
+after `pacstrap`.
+
+```yaml
+pacstraps: [git]
+package_lists:
+  - ./roles/base.txt
+  - ./roles/desktop.txt
+package_groups:
+  - name: base-devel
+    exclude: [gcc]
+  - name: plasma
+    include: [plasma-desktop, kwin]
+local_packages:
+  - ./pkgs/yay-bin-12.3.5-1-x86_64.pkg.tar.zst
+```
+
+### pacstrap options and package phases
+
+`pacstrap_opts` controls `pacstrap(8)` flags: `init_keyring` (`-K`,
+on by default), `host_cache` (`-c`), `no_copy_keyring` (`-G`),
+`no_copy_mirrorlist` (`-M`), and `interactive` (`-i`).
+
+Packages can also be split into ordered `phases`, installed with
+`pacman -S` in `arch-chroot` after `pacstrap` and after the target's
+`pacman.conf` is written, so later phases can use repos configured there.
+
+A phase with `after: files` runs later in `arch-chroot`, after users
+are created and manifest `files` are written, e.g. for packages whose
+install hooks read those files. Such phases must come last.
+
+```yaml
+pacstrap_opts:
+  host_cache: true
+  phases:
+    - name: drivers
+      packages: [mesa, vulkan-radeon]
+    - name: desktop
+      packages: [plasma-meta, sddm]
+      after: files # default: pacstrap
+```
+
+### Offline installation
+
+For air-gapped machines, `offline: true` omits `core` and `extra`,
+so that only manifest repos (e.g. a local `file://` repo) are used.
+The live system's package cache (or `cache_dir`) can be bind-mounted
+into the target during bootstrap, so that cached packages are reused.
+
+```yaml
+pacman:
+  offline: true
+  bind_cache: true # or cache_dir: /mnt/usb/pkg
+  repos:
+    - name: local
+      servers: [file:///mnt/usb/repo]
+      sig_level: Optional TrustAll
+```
+
+Before touching any disk, `ali-rs apply` validates that `base` and all packages
+in `pacstraps` can be resolved in the configured sync databases. This syncs
+databases into `/tmp/ali-rs/db`, which needs root and network access,
+so it is skipped by `ali-rs validate`, dry-run (`apply -n`), and `apply --no-sync-check`.
+
+## Kernel command line in ali-rs
+
+ali-rs computes kernel parameters for the new system from the manifest
+storage stack, e.g. `root=UUID=`, `rootflags=subvol=`, `rd.lvm.vg=`,
+and `cryptdevice=` (or `rd.luks.name=` for systemd-based initramfs).
+
+The parameters can be configured via manifest key `cmdline`:
+
+```yaml
+cmdline:
+  initramfs: systemd # or udev (default)
+  hibernate: true    # adds resume= for the first swap device
+  extra:
+    - quiet
+```
+
+After `stage-mountpoints`, the computed command line is exported
+as environment variable `ALI_KERNEL_CMDLINE`, which is available
+to commands in `chroot` and `postinstall`, and is also expanded
+in [ali-rs hooks](./HOOKS.md).
+
+The command line is only computed if manifest has keys `cmdline`,
+`bootloader`, or `uki`, and only if a stage after `stage-mountpoints` is applied.
+
+## Bootloader in ali-rs
+
+Bootloader can be installed via manifest key `bootloader`,
+instead of raw `bootctl` or `grub-install` commands in `chroot`:
+
+```yaml
+bootloader:
+  type: systemd-boot # or grub
+  timeout: 5
+  # esp: /boot       # detected from vfat mountpoints if omitted
+  # target: uefi     # grub only, uefi or bios
+  # device: /dev/sda # grub bios only
+```
+
+The ESP must be mounted via `mountpoints`, and must be vfat.
+For systemd-boot, the ESP must be mounted on `/boot`.
+
+### Unified kernel images (UKI)
+
+With manifest key `uki`, ali-rs writes `/etc/kernel/cmdline` and
+a mkinitcpio preset that builds UKIs into `<ESP>/EFI/Linux`,
+which systemd-boot picks up without loader entries. The ESP
+can then be mounted anywhere, e.g. `/efi`.
+
+```yaml
+uki:
+  kernel: linux # mkinitcpio preset name
+  sign:
+    tool: sbsign # signs on live system with local key files
+    key: /root/keys/db.key
+    cert: /root/keys/db.crt
+    # tool: sbctl # signs in chroot with target's sbctl keys
+```
+
+With `sbctl`, package `sbctl` is added to `pacstraps`, and ali-rs runs
+`sbctl create-keys` in chroot if the new system has no sbctl keys yet.
+With `sbsign`, the key and cert files must exist on the live system.
+
+## Hostname and hosts in ali-rs
+
+ali-rs writes `/etc/hostname` and `/etc/hosts` with localhost entries
+and the hostname mapping on `127.0.1.1`. The hostname must be a single
+RFC 1123 label, and can be paired with a domain or an explicit FQDN.
+
+```yaml
+hostname: foo
+domain: example.com # or fqdn: foo.example.com
+hosts:
+  - ip: 10.0.0.2
+    names: [bar.example.com, bar]
+```
+
+## Network in ali-rs
+
+Network interfaces can be configured via manifest key `network`,
+which supports DHCP, static IPv4/IPv6 addresses, gateways, routes,
+bonds, and VLANs. Unlike hook `@quicknet`, any number of interfaces
+can be configured.
+
+```yaml
+network:
+  backend: networkd # or networkmanager, netctl
+  interfaces:
+    - name: bond0
+      addresses: [10.0.0.10/24, fd00::10/64]
+      gateways: [10.0.0.1]
+      dns: [1.1.1.1]
+      routes:
+        - to: 10.1.0.0/16
+          via: 10.0.0.254
+      bond:
+        mode: 802.3ad
+        slaves: [eno1, eno2]
+    - name: vlan100
+      dhcp: true
+      vlan:
+        parent: bond0
+        id: 100
+```
+
+Configuration files are written in `stage-routines`, and the backend
+services (or netctl profiles) are enabled in `stage-chroot_ali`.
+Backend packages (`networkmanager`, `netctl`) are added to `pacstraps`.
+
+### Wi-Fi with iwd
+
+Wireless networks are configured with `iwd` via `network.wifi`.
+ali-rs writes `/var/lib/iwd/<ssid>.psk` (mode `0600`) for each network,
+and `/etc/iwd/main.conf`. IP addresses are configured by iwd itself
+(`EnableNetworkConfiguration=true`), or by systemd-networkd if
+`networking` is `networkd`.
+
+```yaml
+network:
+  wifi:
+    networking: iwd # or networkd
+    interface: wlan0 # networkd only, defaults to wl*
+    networks:
+      - ssid: Home
+        passphrase: foobarbaz # or psk: <64 hex digits>
+        hidden: false
+```
+
+Package `iwd` is added to `pacstraps`, and `iwd.service` is enabled.
+Wi-Fi cannot be used with backend `networkmanager`.
+
+## Locales in ali-rs
+
+Locales, console keymap and font, and X11 keyboard layout can be
+configured via manifest key `locale`:
+
+```yaml
+locale:
+  gen:
+    - en_US.UTF-8 UTF-8
+    - th_TH.UTF-8       # charset can be omitted
+  lang: en_US.UTF-8     # defaults to the first locale in gen
+  lc:
+    LC_TIME: th_TH.UTF-8
+  keymap: us            # /etc/vconsole.conf
+  font: ter-v16n        # /etc/vconsole.conf
+  x11_layout: us        # /etc/X11/xorg.conf.d/00-keyboard.conf
+```
+
+Locales are validated against `/usr/share/i18n/SUPPORTED`, and keymaps
+against `/usr/share/kbd/keymaps` on the live system. ali-rs uncomments
+the locales in `/etc/locale.gen` before running `locale-gen`.
+
+If omitted, ali-rs generates and uses `en_US.UTF-8`.
+
+## Users in ali-rs
+
+Regular users and their groups can be created via manifest key `users`.
+Users are created in `stage-chroot_ali`, after locales and timezone.
+
+```yaml
+users:
+  - username: foo
+    uid: 1000           # optional
+    groups: [video]     # created with groupadd if missing
+    shell: /bin/zsh     # optional
+    password: foo       # plaintext, hashed with SHA-512 by ali-rs
+    # password_hash: $6$... (mutually exclusive with password)
+    create_home: true   # default
+    home_dir: /home/foo # default
+    ssh_keys:
+      - ssh-ed25519 AAAA... foo@host
+    sudo: true          # adds user to wheel and enables sudo for wheel
+```
+
+`ssh_keys` are written to `.ssh/authorized_keys` under the user's home,
+so they cannot be used with `create_home: false`.
+
+If any user has `sudo`, ali-rs adds `sudo` to `pacstraps`,
+and writes `/etc/sudoers.d/00-ali-rs-wheel` for group `wheel`.
+
+## Systemd units in ali-rs
+
+Systemd units can be enabled, disabled, or masked via manifest key
+`services`. Units under key `user` are applied to all users with
+`systemctl --global`.
+
+```yaml
+services:
+  enable:
+    - sshd.service
+    - fstrim.timer
+  mask:
+    - systemd-resolved
+  user:
+    enable:
+      - pipewire.socket
+```
+
+Before running `systemctl` in `stage-chroot_ali`, ali-rs checks that
+unit files to be enabled or disabled exist under `/usr/lib/systemd/system`
+(or `/usr/lib/systemd/user` for user units) in the target system.
+
+## Files in ali-rs
+
+Arbitrary files can be written into the target via manifest key `files`,
+with content either inline or from a source (local path or URL).
+Files are written in `stage-chroot_ali` after users are created,
+so that `owner` and `group` can refer to manifest users.
+
+```yaml
+files:
+  - dest: /etc/ssh/sshd_config.d/10-port.conf
+    content: |
+      Port {{ port }}
+    template: true
+    vars:
+      port: "2222"
+  - dest: /home/foo/.config/foo.toml
+    source: https://example.com/foo.toml
+    mode: "0600"
+    owner: foo
+    group: foo
+```
+
+Templates replace `{{ key }}` tokens with `vars`, and built-in vars
+`hostname`, `fqdn`, and `kernel_cmdline`.
+
 ## Root password in ali-rs
 
 User `root` password (hashed) is defined in manifest key
 [`rootpasswd`](https://github.com/soyart/ali/blob/master/ALI.md#key-rootpasswd).
 
-If not given, ali-rs will use the default password as defined in
-[`constants.rs`](./src/constants.rs), currently `archalirs`.
+`rootpasswd` must be in crypt(3) format, e.g. `$6$salt$hash` (SHA-512)
+or `$y$j9T$salt$hash` (yescrypt), and is validated for that format.
+
+Alternatively, the root account can be configured with key `root_account`
+(`root` is already an alias for `rootfs`), which is mutually exclusive
+with `rootpasswd`:
+
+```yaml
+root_account:
+  # Plaintext password or secret reference, hashed locally
+  password: env:ROOT_PASS
+
+  # Hash scheme for password: sha512 (default) or bcrypt.
+  # Scheme yescrypt is rejected for plaintext passwords.
+  scheme: sha512
+
+  # Or a pre-hashed password in crypt(3) format.
+  # Yescrypt hashes can only be given this way.
+  # password_hash: $y$j9T$salt$hash
+
+  # Or lock root (passwd --lock root) instead of setting any password
+  # lock: true
+```
+
+If neither is given, ali-rs will use the default password as defined in
+[`constants.rs`](./src/constants.rs), currently `archalirs`,
+and validation prints a warning. To install without a usable root
+password, lock root with `root_account: {lock: true}` instead.
 
 Note that users can always do a manual `chroot` to change root password
 any time after the installer exits.
 
 > ALI spec does not specify what an installer should do in case it is not given.
 
+### Secrets in ali-rs
+
+LUKS passphrases, `rootpasswd`, user passwords, and Wi-Fi passphrases and PSKs
+are secrets: ali-rs never prints them in logs, errors, or the JSON report,
+where they show up as `<redacted>`.
+
+Instead of a literal value, a secret can reference where it is kept,
+so that manifests with secrets can be committed:
+
+```yaml
+dm:
+  - type: luks
+    device: /dev/vda2
+    name: crypty
+    key: env:LUKS_PASS # Read from environment variable LUKS_PASS
+
+rootpasswd: file:/run/secrets/rootpasswd # Read from file, without trailing newline
+
+users:
+  - username: foo
+    password: prompt # Prompted for without echo, and confirmed
+```
+
+References are resolved before validation, so resolved secrets
+are validated like literal ones.
+
+Secrets are also never passed as command arguments, which are visible
+in the process list. LUKS passphrases are written to `cryptsetup --key-file=-`
+stdin, and password hashes to `chpasswd -e` stdin, without any shell.
+
 ### ALI manifest application stages in ali-rs
 
 ali-rs follows ALI steps in this strict order:
@@ -75,23 +590,33 @@ ali-rs follows ALI steps in this strict order:
 3. `stage-routines`
 
    This stage contains actions that ali-rs will apply on the behalf
-   of the users **outside of a `chroot(1)`**, e.g. writing `/etc/locale.gen`,
-   `/etc/locale.conf`, `/etc/hostname`, and populating `/etc/fstab`
+   of the users **outside of a `chroot(1)`**, e.g. writing `/etc/locale.conf`,
+   `/etc/vconsole.conf`, `/etc/hostname`, and populating `/etc/fstab`
    with `genfstab(8)`.
 
 4. `stage-chroot_ali`
 
    This stage contains actions that ali-rs will apply on the behalf
    of the users **inside of `chroot(1)`**, e.g. linking timezones, and
-   generating locale with `locale-gen`.
+   uncommenting locales in `/etc/locale.gen` before running `locale-gen`.
 
-5. `stage-chroot_user`
+5. `stage-bootloader`
+
+   This stage installs and configures the bootloader defined in manifest
+   key `bootloader` (systemd-boot or GRUB), using the ESP detected from
+   `mountpoints` and the kernel command line computed by ali-rs.
+
+6. `stage-chroot_user`
 
    This stage executes user-defined shell commands in manifest key `chroot`
    **inside of `chroot(1)`**. Users could use this stage to configure their
    bootloader or set root password.
 
-6. `stage-postinstall_user`
+   Each command is run with `sh -c` inside `arch-chroot`, so pipes and
+   redirections also happen inside the new system. Commands generated by
+   ali-rs itself are never run through a shell.
+
+7. `stage-postinstall_user`
 
    This stage executes user-defined shell commands in manifest key `postinstall`
    **outside of `chroot(1)`**. This is currently the last stage of ALI.
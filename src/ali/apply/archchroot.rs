@@ -6,8 +6,16 @@ use crate::types::action::{
     ActionChrootAli,
     ActionChrootUser,
 };
-use crate::utils::shell;
+use crate::utils::fs::{
+    read_file,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
 
+use super::locale;
 use super::map_err::*;
 
 pub fn chroot_ali(
@@ -16,20 +24,20 @@ pub fn chroot_ali(
 ) -> Result<Vec<ActionChrootAli>, AliError> {
     let mut actions = Vec::new();
 
-    let (action_tz, cmd_tz) = cmd_link_timezone(&manifest.timezone);
+    let (action_tz, zoneinfo) = link_timezone(&manifest.timezone);
+    let cmd_tz = ["ln", "-s", &zoneinfo, "/etc/localtime"];
     if let Err(err) = shell::arch_chroot(location, &cmd_tz) {
         return Err(map_err_chroot_ali(err, action_tz, actions));
     }
 
-    actions.push(action_tz);
+    actions.push(journal::done(action_tz));
 
-    let cmd_locale_gen = cmd_locale_gen();
     let action_locale_gen = ActionChrootAli::LocaleGen;
-    if let Err(err) = shell::arch_chroot(location, &cmd_locale_gen) {
+    if let Err(err) = locale_gen(manifest, location) {
         return Err(map_err_chroot_ali(err, action_locale_gen, actions));
     }
 
-    actions.push(action_locale_gen);
+    actions.push(journal::done(action_locale_gen));
 
     Ok(actions)
 }
@@ -51,7 +59,7 @@ where
                 location,
             )?;
 
-            actions.push(ActionChrootUser::Hook(action_hook));
+            actions.push(journal::done(ActionChrootUser::Hook(action_hook)));
 
             continue;
         }
@@ -59,27 +67,31 @@ where
         let action_user_cmd =
             ActionChrootUser::UserArchChrootCmd(cmd.to_string());
 
-        if let Err(err) = shell::arch_chroot(location, cmd) {
+        if let Err(err) = shell::arch_chroot_sh(location, cmd) {
             return Err(map_err_chroot_user(err, action_user_cmd, actions));
         }
 
-        actions.push(action_user_cmd);
+        actions.push(journal::done(action_user_cmd));
     }
 
     Ok(actions)
 }
 
-fn cmd_link_timezone(tz: &Option<String>) -> (ActionChrootAli, String) {
+fn link_timezone(tz: &Option<String>) -> (ActionChrootAli, String) {
     let tz = tz.clone().unwrap_or(defaults::TIMEZONE.to_string());
-    let tz_cmd = format!("ln -s /usr/share/zoneinfo/{} /etc/localtime", tz);
+    let zoneinfo = format!("/usr/share/zoneinfo/{tz}");
 
-    (ActionChrootAli::LinkTimezone(tz), tz_cmd)
+    (ActionChrootAli::LinkTimezone(tz), zoneinfo)
 }
 
-// Appends defaults::DEFAULT_LOCALE_GEN to /etc/locale.gen
-fn cmd_locale_gen() -> String {
-    format!(
-        "echo {} >> /etc/locale.gen && locale-gen",
-        defaults::LOCALE_GEN
-    )
+// Uncomments manifest locales in /etc/locale.gen before running locale-gen
+fn locale_gen(manifest: &Manifest, location: &str) -> Result<(), AliError> {
+    let locale_gen = format!("{location}/etc/locale.gen");
+    let entries = manifest.locale.clone().unwrap_or_default().gen_entries();
+
+    // locale.gen is provided by glibc, but may be missing
+    let orig = read_file(&locale_gen).unwrap_or_default();
+    write_file(&locale_gen, locale::fmt_locale_gen(&orig, &entries))?;
+
+    shell::arch_chroot(location, &["locale-gen"])
 }
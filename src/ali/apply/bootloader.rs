@@ -0,0 +1,282 @@
+use crate::ali::cmdline::KernelCmdline;
+use crate::ali::{
+    GrubTarget,
+    Manifest,
+    ManifestBootloader,
+    ManifestGrub,
+    ManifestSystemdBoot,
+    ManifestUki,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::types::action::ActionBootloader;
+use crate::utils::fs::{
+    read_file,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::map_err::map_err_bootloader;
+
+const DEFAULT_TIMEOUT: u32 = 3;
+pub(super) const DEFAULT_KERNEL: &str = "linux";
+const DEFAULT_GRUB_ID: &str = "GRUB";
+
+pub fn install_bootloader(
+    manifest: &Manifest,
+    location: &str,
+    cmdline: &KernelCmdline,
+) -> Result<Vec<ActionBootloader>, AliError> {
+    let esp = manifest.esp().map(|mnt| mnt.dest.clone());
+
+    match &manifest.bootloader {
+        None => Ok(Vec::new()),
+
+        Some(ManifestBootloader::SystemdBoot(conf)) => {
+            let esp = esp.ok_or(AliError::BadManifest(
+                &codes::E_VAL_BOOTLOADER,
+                "systemd-boot: no ESP found in mountpoints".to_string(),
+            ))?;
+
+            systemd_boot(conf, &manifest.uki, &esp, location, cmdline)
+        }
+
+        Some(ManifestBootloader::Grub(conf)) => {
+            grub(conf, esp.as_deref(), location, cmdline)
+        }
+    }
+}
+
+// If UKI is used, no loader entry is written because systemd-boot
+// automatically picks up UKIs in {esp}/EFI/Linux.
+fn systemd_boot(
+    conf: &ManifestSystemdBoot,
+    uki: &Option<ManifestUki>,
+    esp: &str,
+    location: &str,
+    cmdline: &KernelCmdline,
+) -> Result<Vec<ActionBootloader>, AliError> {
+    let mut actions = Vec::new();
+    let kernel = conf.kernel.as_deref().unwrap_or(DEFAULT_KERNEL);
+    let entry = match uki {
+        Some(uki) => {
+            let kernel = uki.kernel.as_deref().unwrap_or(DEFAULT_KERNEL);
+            format!("arch-{kernel}.efi")
+        }
+        None => format!("arch-{kernel}.conf"),
+    };
+
+    let action_install = ActionBootloader::BootctlInstall {
+        esp: esp.to_string(),
+    };
+    let esp_path = format!("--esp-path={esp}");
+    let cmd_install = ["bootctl", &esp_path, "install"];
+    if let Err(err) = shell::arch_chroot(location, &cmd_install) {
+        return Err(map_err_bootloader(err, action_install, actions));
+    }
+    actions.push(journal::done(action_install));
+
+    let loader_conf = format!("{location}{esp}/loader/loader.conf");
+    let action_loader_conf =
+        ActionBootloader::WriteLoaderConf(loader_conf.clone());
+    let content = fmt_loader_conf(&entry, conf.timeout);
+    if let Err(err) = write_file(&loader_conf, &content) {
+        return Err(map_err_bootloader(err, action_loader_conf, actions));
+    }
+    actions.push(journal::done(action_loader_conf));
+
+    if uki.is_some() {
+        return Ok(actions);
+    }
+
+    let loader_entry = format!("{location}{esp}/loader/entries/{entry}");
+    let action_loader_entry =
+        ActionBootloader::WriteLoaderEntry(loader_entry.clone());
+    let content = fmt_loader_entry(kernel, cmdline);
+    if let Err(err) = write_file(&loader_entry, &content) {
+        return Err(map_err_bootloader(err, action_loader_entry, actions));
+    }
+    actions.push(journal::done(action_loader_entry));
+
+    Ok(actions)
+}
+
+fn grub(
+    conf: &ManifestGrub,
+    esp: Option<&str>,
+    location: &str,
+    cmdline: &KernelCmdline,
+) -> Result<Vec<ActionBootloader>, AliError> {
+    let mut actions = Vec::new();
+
+    let args_target = match conf.target {
+        GrubTarget::Uefi => {
+            let esp = esp.ok_or(AliError::BadManifest(
+                &codes::E_VAL_BOOTLOADER,
+                "grub: no ESP found in mountpoints".to_string(),
+            ))?;
+
+            let id = conf.bootloader_id.as_deref().unwrap_or(DEFAULT_GRUB_ID);
+
+            vec![
+                "--target=x86_64-efi".to_string(),
+                format!("--efi-directory={esp}"),
+                format!("--bootloader-id={id}"),
+            ]
+        }
+
+        GrubTarget::Bios => {
+            let device = conf.device.as_ref().ok_or(AliError::BadManifest(
+                &codes::E_VAL_BOOTLOADER,
+                "grub: bios target requires device".to_string(),
+            ))?;
+
+            vec!["--target=i386-pc".to_string(), device.clone()]
+        }
+    };
+
+    let action_install = ActionBootloader::GrubInstall {
+        target: conf.target.to_string(),
+    };
+    let mut cmd_install = vec!["grub-install"];
+    cmd_install.extend(args_target.iter().map(String::as_str));
+    if let Err(err) = shell::arch_chroot(location, &cmd_install) {
+        return Err(map_err_bootloader(err, action_install, actions));
+    }
+    actions.push(journal::done(action_install));
+
+    // grub-mkconfig already generates root= and rw
+    let grub_cmdline = cmdline
+        .params()
+        .iter()
+        .filter(|p| !p.starts_with("root=") && p.as_str() != "rw")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let action_default_grub = ActionBootloader::WriteDefaultGrub {
+        cmdline: grub_cmdline.clone(),
+    };
+    let default_grub = format!("{location}/etc/default/grub");
+    let result = read_file(&default_grub).and_then(|orig| {
+        let content = fmt_default_grub(&orig, &grub_cmdline, conf.timeout);
+
+        write_file(&default_grub, &content)
+    });
+
+    if let Err(err) = result {
+        return Err(map_err_bootloader(err, action_default_grub, actions));
+    }
+    actions.push(journal::done(action_default_grub));
+
+    let action_mkconfig = ActionBootloader::GrubMkconfig;
+    let cmd_mkconfig = ["grub-mkconfig", "-o", "/boot/grub/grub.cfg"];
+    if let Err(err) = shell::arch_chroot(location, &cmd_mkconfig) {
+        return Err(map_err_bootloader(err, action_mkconfig, actions));
+    }
+    actions.push(journal::done(action_mkconfig));
+
+    Ok(actions)
+}
+
+fn fmt_loader_conf(entry: &str, timeout: Option<u32>) -> String {
+    let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT);
+
+    format!("default {entry}\ntimeout {timeout}\n")
+}
+
+fn fmt_loader_entry(kernel: &str, cmdline: &KernelCmdline) -> String {
+    format!(
+        "title   Arch Linux ({kernel})\nlinux   /vmlinuz-{kernel}\ninitrd  /initramfs-{kernel}.img\noptions {cmdline}\n"
+    )
+}
+
+// Replaces GRUB_CMDLINE_LINUX and GRUB_TIMEOUT in /etc/default/grub,
+// appending the entries if not found.
+fn fmt_default_grub(orig: &str, cmdline: &str, timeout: Option<u32>) -> String {
+    let entry_cmdline = format!("GRUB_CMDLINE_LINUX=\"{cmdline}\"");
+    let entry_timeout = timeout.map(|t| format!("GRUB_TIMEOUT={t}"));
+
+    let (mut has_cmdline, mut has_timeout) = (false, false);
+    let mut lines = Vec::new();
+
+    for line in orig.lines() {
+        if line.starts_with("GRUB_CMDLINE_LINUX=") {
+            has_cmdline = true;
+            lines.push(entry_cmdline.clone());
+
+            continue;
+        }
+
+        if let (true, Some(entry)) =
+            (line.starts_with("GRUB_TIMEOUT="), &entry_timeout)
+        {
+            has_timeout = true;
+            lines.push(entry.clone());
+
+            continue;
+        }
+
+        lines.push(line.to_string());
+    }
+
+    if !has_cmdline {
+        lines.push(entry_cmdline);
+    }
+
+    if let (false, Some(entry)) = (has_timeout, entry_timeout) {
+        lines.push(entry);
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+impl std::fmt::Display for GrubTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Uefi => write!(f, "uefi"),
+            Self::Bios => write!(f, "bios"),
+        }
+    }
+}
+
+#[test]
+fn test_fmt_default_grub() {
+    let orig = r#"# GRUB boot loader configuration
+
+GRUB_DEFAULT=0
+GRUB_TIMEOUT=5
+GRUB_CMDLINE_LINUX_DEFAULT="loglevel=3 quiet"
+GRUB_CMDLINE_LINUX=""
+"#;
+
+    let expected = r#"# GRUB boot loader configuration
+
+GRUB_DEFAULT=0
+GRUB_TIMEOUT=1
+GRUB_CMDLINE_LINUX_DEFAULT="loglevel=3 quiet"
+GRUB_CMDLINE_LINUX="rd.lvm.vg=archvg"
+"#;
+
+    assert_eq!(
+        expected,
+        fmt_default_grub(orig, "rd.lvm.vg=archvg", Some(1))
+    );
+
+    let expected = r#"GRUB_DEFAULT=0
+GRUB_CMDLINE_LINUX="rd.lvm.vg=archvg"
+"#;
+
+    assert_eq!(
+        expected,
+        fmt_default_grub("GRUB_DEFAULT=0", "rd.lvm.vg=archvg", None)
+    );
+}
@@ -1,29 +1,156 @@
-use std::collections::HashSet;
+use std::collections::{
+    BTreeSet,
+    HashSet,
+};
 
+use crate::ali::{
+    ManifestPackagePhase,
+    ManifestPacstrap,
+};
 use crate::errors::AliError;
+use crate::types::action::ActionBootstrap;
 use crate::utils::shell;
 
+use super::map_err::map_err_bootstrap;
+
+// Local packages are copied into the target package cache,
+// because arch-chroot mounts a fresh tmpfs on /tmp.
+const CACHE_DIR: &str = "/var/cache/pacman/pkg";
+
+/// Executes:
+/// ```shell
+/// pacstrap -K [-C ${{ pacman_conf }}] [opts] ${{ location }} ${{ packages }}
+/// ```
 pub fn pacstrap_to_location(
-    pacstraps: &Option<HashSet<String>>,
+    packages: &HashSet<String>,
     location: &str,
+    pacman_conf: Option<&str>,
+    opts: Option<&ManifestPacstrap>,
 ) -> Result<(), AliError> {
-    // Collect packages, with base as bare-minimum
-    let mut packages = HashSet::from(["base".to_string()]);
+    let args = pacstrap_args(packages, location, pacman_conf, opts);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
 
-    if let Some(pacstraps) = pacstraps.clone() {
-        packages.extend(pacstraps);
+    shell::exec("pacstrap", &args)
+}
+
+/// Installs a package phase with pacman in arch-chroot
+pub fn install_phase(
+    phase: &ManifestPackagePhase,
+    location: &str,
+    interactive: bool,
+) -> Result<(), AliError> {
+    let mut cmd = vec!["pacman", "-S", "--needed"];
+    if !interactive {
+        cmd.push("--noconfirm");
     }
 
-    let cmd_pacstrap = {
-        let mut cmd_parts = vec![
-            "pacstrap".to_string(),
-            "-K".to_string(),
-            location.to_string(),
+    let packages = BTreeSet::from_iter(phase.packages.iter());
+    cmd.extend(packages.into_iter().map(String::as_str));
+
+    shell::arch_chroot(location, &cmd)
+}
+
+fn pacstrap_args(
+    packages: &HashSet<String>,
+    location: &str,
+    pacman_conf: Option<&str>,
+    opts: Option<&ManifestPacstrap>,
+) -> Vec<String> {
+    let mut args = Vec::new();
+
+    if opts.is_none_or(|opts| opts.init_keyring) {
+        args.push("-K".to_string());
+    }
+
+    if let Some(conf) = pacman_conf {
+        args.extend(["-C".to_string(), conf.to_string()]);
+    }
+
+    if let Some(opts) = opts {
+        let flags = [
+            (opts.host_cache, "-c"),
+            (opts.no_copy_keyring, "-G"),
+            (opts.no_copy_mirrorlist, "-M"),
+            (opts.interactive, "-i"),
         ];
 
-        cmd_parts.extend(packages);
-        cmd_parts.join(" ")
+        for (enabled, flag) in flags {
+            if enabled {
+                args.push(flag.to_string());
+            }
+        }
+    }
+
+    args.push(location.to_string());
+
+    // Sorted for deterministic command line
+    let packages = BTreeSet::from_iter(packages.iter().cloned());
+    args.extend(packages);
+
+    args
+}
+
+/// Copies local package files into the target package cache,
+/// and installs them with `pacman -U` in arch-chroot.
+pub fn install_local_packages(
+    packages: &[String],
+    location: &str,
+) -> Result<ActionBootstrap, AliError> {
+    let action = ActionBootstrap::InstallLocalPackages {
+        packages: packages.to_vec(),
     };
 
-    shell::sh_c(&cmd_pacstrap)
+    let mut targets = Vec::new();
+    for pkg in packages {
+        let name = pkg.rsplit('/').next().unwrap_or(pkg);
+        let target = format!("{CACHE_DIR}/{name}");
+        let dst = format!("{location}{target}");
+
+        if let Err(err) = shell::exec("cp", &[pkg, &dst]) {
+            return Err(map_err_bootstrap(err, action, vec![]));
+        }
+
+        targets.push(target);
+    }
+
+    let mut cmd = vec!["pacman", "-U", "--noconfirm"];
+    cmd.extend(targets.iter().map(String::as_str));
+    if let Err(err) = shell::arch_chroot(location, &cmd) {
+        return Err(map_err_bootstrap(err, action, vec![]));
+    }
+
+    Ok(action)
+}
+
+#[test]
+fn test_pacstrap_args() {
+    let packages = HashSet::from(["linux".to_string(), "base".to_string()]);
+
+    assert_eq!(
+        vec!["-K", "/mnt", "base", "linux"],
+        pacstrap_args(&packages, "/mnt", None, None),
+    );
+
+    let opts: ManifestPacstrap = serde_yaml::from_str(
+        "init_keyring: false\nhost_cache: true\nno_copy_mirrorlist: true",
+    )
+    .expect("failed to parse pacstrap opts");
+
+    assert_eq!(
+        vec![
+            "-C",
+            "/tmp/ali-rs/pacman.conf",
+            "-c",
+            "-M",
+            "/mnt",
+            "base",
+            "linux"
+        ],
+        pacstrap_args(
+            &packages,
+            "/mnt",
+            Some("/tmp/ali-rs/pacman.conf"),
+            Some(&opts)
+        ),
+    );
 }
@@ -2,6 +2,7 @@ use crate::ali;
 use crate::errors::AliError;
 use crate::linux::fdisk;
 use crate::types::action::ActionMountpoints;
+use crate::utils::journal;
 
 use super::map_err::map_err_mountpoints;
 
@@ -25,12 +26,12 @@ pub fn apply_disks(
             }
             Ok(disk_actions) => {
                 actions.extend(disk_actions);
-                actions.push(action_apply_disk);
+                actions.push(journal::done(action_apply_disk));
             }
         }
     }
 
-    actions.push(ActionMountpoints::ApplyDisks);
+    actions.push(journal::done(ActionMountpoints::ApplyDisks));
 
     Ok(actions)
 }
@@ -49,7 +50,7 @@ pub fn apply_disk(
         return Err(map_err_mountpoints(err, action_create_table, actions));
     }
 
-    actions.push(action_create_table);
+    actions.push(journal::done(action_create_table));
 
     // Actions:
     // 1. Create partition
@@ -73,7 +74,7 @@ pub fn apply_disk(
             ));
         }
 
-        actions.push(action_create_partition);
+        actions.push(journal::done(action_create_partition));
 
         let action_set_part_type = ActionMountpoints::SetPartitionType {
             device: disk.device.clone(),
@@ -93,7 +94,7 @@ pub fn apply_disk(
             ));
         }
 
-        actions.push(action_set_part_type);
+        actions.push(journal::done(action_set_part_type));
     }
 
     Ok(actions)
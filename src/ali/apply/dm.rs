@@ -6,6 +6,8 @@ use crate::ali::{
 use crate::errors::AliError;
 use crate::linux;
 use crate::types::action::ActionMountpoints;
+use crate::types::secret::Secret;
+use crate::utils::journal;
 
 use super::map_err::map_err_mountpoints;
 
@@ -24,7 +26,7 @@ pub fn apply_dms(dms: &[Dm]) -> Result<Vec<ActionMountpoints>, AliError> {
         };
     }
 
-    actions.push(action_dms);
+    actions.push(journal::done(action_dms));
 
     Ok(actions)
 }
@@ -37,13 +39,13 @@ pub fn apply_dm(dm: &Dm) -> Result<Vec<ActionMountpoints>, AliError> {
             passphrase,
             name,
         }) => {
-            let passphrase = passphrase.as_ref().map(|p| p.as_str());
+            let passphrase = passphrase.as_ref().map(Secret::expose);
             let action_create = ActionMountpoints::CreateDmLuks {
                 device: device.clone(),
             };
 
             linux::luks::format(device, passphrase)?;
-            actions.push(action_create);
+            actions.push(journal::done(action_create));
 
             let action_open = ActionMountpoints::OpenDmLuks {
                 device: device.clone(),
@@ -51,7 +53,7 @@ pub fn apply_dm(dm: &Dm) -> Result<Vec<ActionMountpoints>, AliError> {
             };
 
             linux::luks::open(device, passphrase, name)?;
-            actions.push(action_open);
+            actions.push(journal::done(action_open));
         }
 
         // For each LVM entry, do PV, then VG, then LV
@@ -62,7 +64,7 @@ pub fn apply_dm(dm: &Dm) -> Result<Vec<ActionMountpoints>, AliError> {
                         ActionMountpoints::CreateDmLvmPv(pv.clone());
 
                     linux::lvm::create_pv(pv)?;
-                    actions.push(action_create_pv);
+                    actions.push(journal::done(action_create_pv));
                 }
             }
 
@@ -75,7 +77,7 @@ pub fn apply_dm(dm: &Dm) -> Result<Vec<ActionMountpoints>, AliError> {
                     };
 
                     linux::lvm::create_vg(vg)?;
-                    actions.push(action_create_vg);
+                    actions.push(journal::done(action_create_vg));
                 }
             }
 
@@ -89,7 +91,7 @@ pub fn apply_dm(dm: &Dm) -> Result<Vec<ActionMountpoints>, AliError> {
                     };
 
                     linux::lvm::create_lv(lv)?;
-                    actions.push(action_create_lv);
+                    actions.push(journal::done(action_create_lv));
                 }
             }
         }
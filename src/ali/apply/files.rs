@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+
+use crate::ali::{
+    Manifest,
+    ManifestFile,
+};
+use crate::constants::ENV_ALI_KERNEL_CMDLINE;
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::hooks::Downloader;
+use crate::types::action::ActionChrootAli;
+use crate::utils::fs::{
+    fetch,
+    set_mode,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::map_err::map_err_chroot_ali;
+
+/// Writes files defined in manifest into the target, with one action
+/// per file. Ownership is set inside chroot, after users were created.
+pub fn write_files(
+    manifest: &Manifest,
+    location: &str,
+) -> Result<Vec<ActionChrootAli>, AliError> {
+    let mut actions = Vec::new();
+
+    let files = match manifest.files {
+        Some(ref files) => files,
+        None => return Ok(actions),
+    };
+
+    for file in files {
+        let action_write = ActionChrootAli::WriteFile(file.dest.clone());
+        if let Err(err) = write(manifest, file, location) {
+            return Err(map_err_chroot_ali(err, action_write, actions));
+        }
+
+        actions.push(journal::done(action_write));
+    }
+
+    Ok(actions)
+}
+
+fn write(
+    manifest: &Manifest,
+    file: &ManifestFile,
+    location: &str,
+) -> Result<(), AliError> {
+    let mut content = read_content(file)?;
+
+    if file.template {
+        let template = String::from_utf8(content).map_err(|err| {
+            AliError::BadManifest(
+                &codes::E_VAL_FILES,
+                format!("template for {} is not string: {err}", file.dest),
+            )
+        })?;
+
+        content = render(&template, &vars(manifest, file)).into_bytes();
+    }
+
+    let dst = format!("{location}{}", file.dest);
+    write_file(&dst, content)?;
+
+    if let Some(mode) = file.mode_bits()? {
+        set_mode(&dst, mode)?;
+    }
+
+    match (&file.owner, &file.group) {
+        (None, None) => Ok(()),
+        (owner, group) => {
+            let owner = owner.as_deref().unwrap_or_default();
+            let group = group.as_deref().unwrap_or_default();
+
+            let owner = match group.is_empty() {
+                true => owner.to_string(),
+                false => format!("{owner}:{group}"),
+            };
+
+            shell::arch_chroot(location, &["chown", &owner, &file.dest])
+        }
+    }
+}
+
+fn read_content(file: &ManifestFile) -> Result<Vec<u8>, AliError> {
+    match (&file.content, &file.source) {
+        (Some(content), None) => Ok(content.clone().into_bytes()),
+
+        (None, Some(source)) => {
+            // If the source is a valid remote URL, download it
+            if Downloader::new_from_url(source).is_ok() {
+                return fetch(source);
+            }
+
+            std::fs::read(source).map_err(|err| {
+                AliError::FileError(err, format!("failed to read {source}"))
+            })
+        }
+
+        _ => {
+            Err(AliError::BadManifest(
+                &codes::E_VAL_FILES,
+                format!("file {} needs either content or source", file.dest),
+            ))
+        }
+    }
+}
+
+// Built-in vars can be overridden by file vars
+fn vars(manifest: &Manifest, file: &ManifestFile) -> BTreeMap<String, String> {
+    let mut vars = BTreeMap::new();
+
+    if let Some(ref hostname) = manifest.hostname {
+        vars.insert("hostname".to_string(), hostname.clone());
+    }
+
+    if let Some(fqdn) = manifest.fqdn() {
+        vars.insert("fqdn".to_string(), fqdn);
+    }
+
+    if let Ok(cmdline) = std::env::var(ENV_ALI_KERNEL_CMDLINE) {
+        vars.insert("kernel_cmdline".to_string(), cmdline);
+    }
+
+    if let Some(ref file_vars) = file.vars {
+        vars.extend(file_vars.clone());
+    }
+
+    vars
+}
+
+// Replaces {{ key }} with value, like hook @replace-token
+fn render(template: &str, vars: &BTreeMap<String, String>) -> String {
+    vars.iter().fold(template.to_string(), |s, (key, value)| {
+        s.replace(&format!("{{{{ {key} }}}}"), value)
+    })
+}
+
+#[test]
+fn test_render() {
+    let vars = BTreeMap::from([
+        ("hostname".to_string(), "foo".to_string()),
+        ("port".to_string(), "2222".to_string()),
+    ]);
+
+    assert_eq!(
+        "Port 2222\n# foo {{ bar }} {{port}}\n",
+        render(
+            "Port {{ port }}\n# {{ hostname }} {{ bar }} {{port}}\n",
+            &vars
+        )
+    );
+}
@@ -5,6 +5,7 @@ use crate::ali::{
 use crate::errors::AliError;
 use crate::linux;
 use crate::types::action::ActionMountpoints;
+use crate::utils::journal;
 
 use super::map_err::map_err_mountpoints;
 
@@ -54,7 +55,7 @@ pub fn create_filesystems(
                     actions,
                 ));
             }
-            Ok(action) => actions.push(action),
+            Ok(action) => actions.push(journal::done(action)),
         }
     }
 
@@ -80,7 +81,7 @@ pub fn mount_filesystems(
                 return Err(map_err_mountpoints(err, action_mount_fs, actions));
             }
             Ok(action) => {
-                actions.push(action);
+                actions.push(journal::done(action));
             }
         }
     }
@@ -0,0 +1,141 @@
+use crate::ali::{
+    ManifestKey,
+    ManifestKeyring,
+};
+use crate::errors::AliError;
+use crate::types::action::ActionBootstrap;
+use crate::utils::{
+    fs,
+    journal,
+    shell,
+};
+
+use super::map_err::map_err_bootstrap;
+
+/// Prepares the live system's pacman keyring before pacstrap,
+/// so that stale keyrings on old ISOs do not fail pacstrap.
+///
+/// `pacman_conf` is used when upgrading `archlinux-keyring`.
+pub fn prepare_keyring(
+    keyring: &ManifestKeyring,
+    pacman_conf: Option<&str>,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    let mut actions = Vec::new();
+
+    if keyring.init {
+        let action_init = ActionBootstrap::InitKeyring;
+        if let Err(err) = shell::exec("pacman-key", &["--init"]) {
+            return Err(map_err_bootstrap(err, action_init, actions));
+        }
+
+        actions.push(journal::done(action_init));
+
+        let action_populate = ActionBootstrap::PopulateKeyring;
+        if let Err(err) = shell::exec("pacman-key", &["--populate"]) {
+            return Err(map_err_bootstrap(err, action_populate, actions));
+        }
+
+        actions.push(journal::done(action_populate));
+    }
+
+    if keyring.upgrade {
+        let mut args = Vec::new();
+        if let Some(conf) = pacman_conf {
+            args.extend(["--config", conf]);
+        }
+        args.extend(["-Sy", "--noconfirm", "--needed", "archlinux-keyring"]);
+
+        let action_upgrade = ActionBootstrap::UpgradeKeyring;
+        if let Err(err) = shell::exec("pacman", &args) {
+            return Err(map_err_bootstrap(err, action_upgrade, actions));
+        }
+
+        actions.push(journal::done(action_upgrade));
+    }
+
+    for key in &keyring.keys {
+        let action_import = ActionBootstrap::ImportKey(key.id.clone());
+        if let Err(err) = import_key(key) {
+            return Err(map_err_bootstrap(err, action_import, actions));
+        }
+
+        actions.push(journal::done(action_import));
+    }
+
+    Ok(actions)
+}
+
+// Imports key from file or keyserver, and locally signs it
+fn import_key(key: &ManifestKey) -> Result<(), AliError> {
+    match (&key.file, &key.keyserver) {
+        (Some(file), _) => shell::exec("pacman-key", &["--add", file])?,
+        (None, Some(keyserver)) => {
+            shell::exec(
+                "pacman-key",
+                &["--keyserver", keyserver, "--recv-keys", &key.id],
+            )?
+        }
+        (None, None) => shell::exec("pacman-key", &["--recv-keys", &key.id])?,
+    }
+
+    shell::exec("pacman-key", &["--lsign-key", &key.id])
+}
+
+/// Imports and locally signs keyring keys in the target after pacstrap,
+/// because `pacstrap -K` initializes a fresh keyring in the target
+/// without the keys imported on the live system.
+pub fn import_target_keys(
+    keyring: &ManifestKeyring,
+    location: &str,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    let mut actions = Vec::new();
+
+    for key in &keyring.keys {
+        let action_import = ActionBootstrap::ImportTargetKey(key.id.clone());
+        if let Err(err) = import_target_key(key, location) {
+            return Err(map_err_bootstrap(err, action_import, actions));
+        }
+
+        actions.push(journal::done(action_import));
+    }
+
+    Ok(actions)
+}
+
+// Key files are on the live system, so they are piped to
+// pacman-key in the target, which reads keys from stdin
+fn import_target_key(
+    key: &ManifestKey,
+    location: &str,
+) -> Result<(), AliError> {
+    match (&key.file, &key.keyserver) {
+        (Some(file), _) => {
+            let armored = fs::read_file(file)?;
+            shell::exec_with_stdin(
+                "arch-chroot",
+                &[location, "pacman-key", "--add"],
+                armored.as_bytes(),
+            )?
+        }
+        (None, Some(keyserver)) => {
+            shell::arch_chroot(
+                location,
+                &[
+                    "pacman-key",
+                    "--keyserver",
+                    keyserver,
+                    "--recv-keys",
+                    &key.id,
+                ],
+            )?
+        }
+        (None, None) => {
+            shell::arch_chroot(
+                location,
+                &["pacman-key", "--recv-keys", &key.id],
+            )?
+        }
+    }
+
+    shell::arch_chroot(location, &["pacman-key", "--lsign-key", &key.id])
+}
@@ -0,0 +1,180 @@
+use crate::ali::ManifestLocale;
+use crate::constants::defaults;
+
+// Uncomments entries in locale.gen content orig,
+// appending only the entries not found in orig.
+pub(super) fn fmt_locale_gen(orig: &str, entries: &[String]) -> String {
+    let mut lines: Vec<String> = orig.lines().map(String::from).collect();
+
+    for entry in entries {
+        let enabled = lines.iter().any(|line| matches_entry(line, entry));
+        if enabled {
+            continue;
+        }
+
+        // Only uncomment lines like "#en_US.UTF-8 UTF-8",
+        // and not comments like "#  <locale> <charset>"
+        let commented = lines.iter_mut().find(|line| {
+            line.strip_prefix('#').is_some_and(|rest| {
+                !rest.starts_with(char::is_whitespace)
+                    && matches_entry(rest, entry)
+            })
+        });
+
+        match commented {
+            Some(line) => *line = line.trim_start_matches('#').to_string(),
+            None => lines.push(full_entry(entry)),
+        }
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+pub(super) fn fmt_locale_conf(locale: &Option<ManifestLocale>) -> String {
+    let locale = match locale {
+        Some(locale) => locale,
+        None => return format!("{}\n", defaults::LOCALE_CONF),
+    };
+
+    let lang = match locale.lang {
+        Some(ref lang) => lang.clone(),
+        None => {
+            match locale.gen.as_ref().and_then(|gen| gen.first()) {
+                Some(entry) => locale_name(entry).to_string(),
+                None => return format!("{}\n", defaults::LOCALE_CONF),
+            }
+        }
+    };
+
+    let mut lines = vec![format!("LANG={lang}")];
+    for (key, value) in locale.lc.iter().flatten() {
+        lines.push(format!("{key}={value}"));
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+pub(super) fn fmt_vconsole_conf(locale: &ManifestLocale) -> Option<String> {
+    let mut lines = Vec::new();
+
+    if let Some(ref keymap) = locale.keymap {
+        lines.push(format!("KEYMAP={keymap}"));
+    }
+
+    if let Some(ref font) = locale.font {
+        lines.push(format!("FONT={font}"));
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    Some(s)
+}
+
+// Same format as written by localectl set-x11-keymap
+pub(super) fn fmt_x11_keyboard(layout: &str) -> String {
+    format!(
+        r#"Section "InputClass"
+        Identifier "system-keyboard"
+        MatchIsKeyboard "on"
+        Option "XkbLayout" "{layout}"
+EndSection
+"#
+    )
+}
+
+/// Returns locale name from locale.gen entry,
+/// e.g. "en_US.UTF-8" from "en_US.UTF-8 UTF-8"
+pub(super) fn locale_name(entry: &str) -> &str {
+    entry.split_whitespace().next().unwrap_or_default()
+}
+
+// Entry can be either "<locale> <charset>" or "<locale>"
+fn matches_entry(line: &str, entry: &str) -> bool {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let wanted: Vec<&str> = entry.split_whitespace().collect();
+
+    match wanted.len() {
+        1 => fields.first() == wanted.first(),
+        _ => fields == wanted,
+    }
+}
+
+// Guesses charset for entries without one, e.g. "en_US.UTF-8"
+fn full_entry(entry: &str) -> String {
+    if entry.contains(char::is_whitespace) {
+        return entry.to_string();
+    }
+
+    match entry.split_once('.') {
+        Some((_, charset)) => format!("{entry} {charset}"),
+        None => entry.to_string(),
+    }
+}
+
+#[test]
+fn test_fmt_locale_gen() {
+    let orig = r#"# Configuration file for locale-gen
+#
+#  <locale> <charset>
+#
+#en_US ISO-8859-1
+#en_US.UTF-8 UTF-8
+#th_TH.UTF-8 UTF-8
+de_DE.UTF-8 UTF-8
+"#;
+
+    let expected = r#"# Configuration file for locale-gen
+#
+#  <locale> <charset>
+#
+#en_US ISO-8859-1
+en_US.UTF-8 UTF-8
+th_TH.UTF-8 UTF-8
+de_DE.UTF-8 UTF-8
+foo_BAR.UTF-8 UTF-8
+"#;
+
+    let entries = vec![
+        "en_US.UTF-8 UTF-8".to_string(),
+        "th_TH.UTF-8".to_string(),
+        "de_DE.UTF-8".to_string(),
+        "foo_BAR.UTF-8".to_string(),
+    ];
+
+    assert_eq!(expected, fmt_locale_gen(orig, &entries));
+
+    // Applying twice does not duplicate entries
+    assert_eq!(expected, fmt_locale_gen(expected, &entries));
+}
+
+#[test]
+fn test_fmt_locale_conf() {
+    use std::collections::BTreeMap;
+
+    assert_eq!("LANG=en_US.UTF-8\n", fmt_locale_conf(&None));
+
+    let locale = ManifestLocale {
+        gen: Some(vec!["th_TH.UTF-8 UTF-8".to_string()]),
+        lc: Some(BTreeMap::from([(
+            "LC_TIME".to_string(),
+            "en_GB.UTF-8".to_string(),
+        )])),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        "LANG=th_TH.UTF-8\nLC_TIME=en_GB.UTF-8\n",
+        fmt_locale_conf(&Some(locale))
+    );
+}
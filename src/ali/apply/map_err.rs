@@ -1,20 +1,27 @@
 use crate::errors::AliError;
 use crate::types::action::{
     Action,
+    ActionBootloader,
+    ActionBootstrap,
     ActionChrootAli,
     ActionChrootUser,
     ActionMountpoints,
+    ActionPostInstallUser,
     ActionRoutine,
 };
+use crate::utils::journal;
 
 pub(super) fn map_err_mountpoints(
     err: AliError,
     action_failed: ActionMountpoints,
     actions_performed: Vec<ActionMountpoints>,
 ) -> AliError {
+    let action_failed = Action::Mountpoints(action_failed);
+
     AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
         error: Box::new(err),
-        action_failed: Box::new(Action::Mountpoints(action_failed)),
+        action_failed: Box::new(action_failed),
         actions_performed: actions_performed
             .into_iter()
             .map(Action::Mountpoints)
@@ -22,14 +29,35 @@ pub(super) fn map_err_mountpoints(
     }
 }
 
+pub(super) fn map_err_bootstrap(
+    err: AliError,
+    action_failed: ActionBootstrap,
+    actions_performed: Vec<ActionBootstrap>,
+) -> AliError {
+    let action_failed = Action::Bootstrap(action_failed);
+
+    AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
+        error: Box::new(err),
+        action_failed: Box::new(action_failed),
+        actions_performed: actions_performed
+            .into_iter()
+            .map(Action::Bootstrap)
+            .collect(),
+    }
+}
+
 pub(super) fn map_err_routine(
     err: AliError,
     action_failed: ActionRoutine,
     actions_performed: Vec<ActionRoutine>,
 ) -> AliError {
+    let action_failed = Action::Routines(action_failed);
+
     AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
         error: Box::new(err),
-        action_failed: Box::new(Action::Routines(action_failed)),
+        action_failed: Box::new(action_failed),
         actions_performed: actions_performed
             .into_iter()
             .map(Action::Routines)
@@ -42,9 +70,12 @@ pub(super) fn map_err_chroot_ali(
     action_failed: ActionChrootAli,
     actions_performed: Vec<ActionChrootAli>,
 ) -> AliError {
+    let action_failed = Action::ChrootAli(action_failed);
+
     AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
         error: Box::new(err),
-        action_failed: Box::new(Action::ChrootAli(action_failed)),
+        action_failed: Box::new(action_failed),
         actions_performed: actions_performed
             .into_iter()
             .map(Action::ChrootAli)
@@ -52,17 +83,56 @@ pub(super) fn map_err_chroot_ali(
     }
 }
 
+pub(super) fn map_err_bootloader(
+    err: AliError,
+    action_failed: ActionBootloader,
+    actions_performed: Vec<ActionBootloader>,
+) -> AliError {
+    let action_failed = Action::Bootloader(action_failed);
+
+    AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
+        error: Box::new(err),
+        action_failed: Box::new(action_failed),
+        actions_performed: actions_performed
+            .into_iter()
+            .map(Action::Bootloader)
+            .collect(),
+    }
+}
+
 pub(super) fn map_err_chroot_user(
     err: AliError,
     action_failed: ActionChrootUser,
     actions_performed: Vec<ActionChrootUser>,
 ) -> AliError {
+    let action_failed = Action::ChrootUser(action_failed);
+
     AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
         error: Box::new(err),
-        action_failed: Box::new(Action::ChrootUser(action_failed)),
+        action_failed: Box::new(action_failed),
         actions_performed: actions_performed
             .into_iter()
             .map(Action::ChrootUser)
             .collect(),
     }
 }
+
+pub(super) fn map_err_postinstall_user(
+    err: AliError,
+    action_failed: ActionPostInstallUser,
+    actions_performed: Vec<ActionPostInstallUser>,
+) -> AliError {
+    let action_failed = Action::UserPostInstall(action_failed);
+
+    AliError::ApplyError {
+        commands: journal::failed(&action_failed, &err),
+        error: Box::new(err),
+        action_failed: Box::new(action_failed),
+        actions_performed: actions_performed
+            .into_iter()
+            .map(Action::UserPostInstall)
+            .collect(),
+    }
+}
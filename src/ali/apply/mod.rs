@@ -1,55 +1,574 @@
 mod archchroot;
+mod bootloader;
 mod bootstrap;
 mod disks;
 mod dm;
+mod files;
 mod fs;
+mod keyring;
+mod locale;
 mod map_err;
+mod network;
+mod pacman;
 mod routines;
+mod services;
 mod stages;
+mod uki;
+mod users;
+
+pub use self::pacman::host_conf;
 
 use std::collections::HashSet;
 
+use crate::ali::cmdline::{
+    self,
+    KernelCmdline,
+};
 use crate::ali::Manifest;
 use crate::errors::AliError;
+use crate::types::blockdev::BlockDevPaths;
 use crate::types::stage::{
     self,
     Stage,
     StageActions,
 };
-
-type ApplyFn = fn(&Manifest, &str, &mut StageActions) -> Result<(), AliError>;
+use crate::utils::journal;
+use crate::{
+    constants,
+    linux,
+};
 
 /// Use `manifest` to install a new system to `install_location`
 /// skipping any stages in `skip`, and maps `AliError::ApplyError`
 /// to `AliError::InstallError` with StageActions embedded.
+///
+/// `block_devs` is used to compute kernel command line,
+/// which is exported to later stages via env `ALI_KERNEL_CMDLINE`.
 pub fn apply_manifest(
     manifest: &Manifest,
     install_location: &str,
+    block_devs: &BlockDevPaths,
     skip: HashSet<Stage>,
 ) -> Result<Box<StageActions>, AliError> {
     let mut progress = Box::default();
+    let mut kernel_cmdline: Option<KernelCmdline> = None;
 
     for stage in stage::STAGES {
         if skip.contains(&stage) {
             continue;
         }
 
-        let f: ApplyFn = match stage {
-            Stage::Mountpoints => stages::mountpoints,
-            Stage::Bootstrap => stages::bootstrap,
-            Stage::Routines => stages::routines,
-            Stage::ChrootAli => stages::chroot_ali,
-            Stage::ChrootUser => stages::chroot_user,
-            Stage::PostInstallUser => stages::postinstall_user,
+        // Devices should now exist, either created by us or by the user,
+        // so kernel command line is computed before the first stage after
+        // stage-mountpoints, and only if manifest needs it
+        if stage != Stage::Mountpoints
+            && kernel_cmdline.is_none()
+            && needs_kernel_cmdline(manifest)
+        {
+            match export_kernel_cmdline(manifest, block_devs) {
+                Ok(cmdline) => kernel_cmdline = Some(cmdline),
+                Err(err) => {
+                    return Err(AliError::InstallError {
+                        error: Box::new(err),
+                        stages_performed: progress,
+                    });
+                }
+            }
+        }
+
+        journal::stage_start(&stage);
+
+        let (m, loc, p) = (manifest, install_location, &mut progress);
+        let result = match stage {
+            Stage::Mountpoints => stages::mountpoints(m, loc, p),
+            Stage::Bootstrap => stages::bootstrap(m, loc, p),
+            Stage::Routines => stages::routines(m, loc, p),
+            Stage::ChrootAli => stages::chroot_ali(m, loc, p),
+            Stage::Bootloader => {
+                let cmdline = kernel_cmdline.clone().unwrap_or_default();
+                stages::bootloader(m, loc, &cmdline, p)
+            }
+            Stage::ChrootUser => stages::chroot_user(m, loc, p),
+            Stage::PostInstallUser => stages::postinstall_user(m, loc, p),
         };
 
-        if let Err(err) = f(manifest, install_location, &mut progress) {
+        if let Err(err) = result {
+            journal::stage_failed(&err);
+
             return Err(AliError::InstallError {
                 error: Box::new(err),
                 stages_performed: progress,
             });
         }
+
+        journal::stage_end();
     }
 
     Ok(progress)
 }
+
+/// Kernel command line is only needed by bootloader and UKI,
+/// or if explicitly configured with key `cmdline` for files and hooks
+fn needs_kernel_cmdline(manifest: &Manifest) -> bool {
+    manifest.cmdline.is_some()
+        || manifest.bootloader.is_some()
+        || manifest.uki.is_some()
+}
+
+fn export_kernel_cmdline(
+    manifest: &Manifest,
+    block_devs: &BlockDevPaths,
+) -> Result<KernelCmdline, AliError> {
+    let cmdline =
+        cmdline::kernel_cmdline(manifest, block_devs, linux::blkid::uuid)?;
+
+    std::env::set_var(constants::ENV_ALI_KERNEL_CMDLINE, cmdline.to_string());
+
+    Ok(cmdline)
+}
+
+#[test]
+fn test_apply_examples() {
+    use std::rc::Rc;
+
+    use crate::types::secret::Secret;
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+    use crate::utils::journal;
+
+    let tests = vec![
+        (
+            include_str!("../examples/uefi-root-on-lvm.yaml"),
+            vec![
+                "printf \"g\nw\n\" | fdisk /dev/vda",
+                "printf \"n\n1\n\n+300M\nw\n\" | fdisk /dev/vda",
+                "printf \"t\nef\nw\n\" | fdisk /dev/vda",
+                "printf \"n\n2\n\n\nw\n\" | fdisk /dev/vda",
+                "printf \"t\n2\n8e\nw\n\" | fdisk /dev/vda",
+                "pvcreate /dev/vda2",
+                "vgcreate archvg /dev/vda2",
+                "lvcreate -L 8G archvg -n swaplv",
+                "lvcreate -l \"100%FREE\" archvg -n rootlv",
+                "mkfs.btrfs -L rootfs /dev/archvg/rootlv",
+                "mkfs.vfat -F 32 -L BOOT /dev/vda1",
+                "mkdir -p /alitarget",
+                "mount -o compress:zstd:3 /dev/archvg/rootlv /alitarget/",
+                "mkdir -p /alitarget/boot",
+                "mount /dev/vda1 /alitarget/boot",
+                "blkid -s UUID -o value /dev/archvg/rootlv",
+                "pacstrap -K /alitarget base base-devel git helix",
+                "arch-chroot /alitarget chpasswd -e < <stdin>",
+                "genfstab -U /alitarget",
+                "write /alitarget/etc/fstab",
+                "write /alitarget/etc/hostname",
+                "write /alitarget/etc/hosts",
+                "write /alitarget/etc/locale.conf",
+                "arch-chroot /alitarget ln -s /usr/share/zoneinfo/Asia/Bangkok /etc/localtime",
+                "write /alitarget/etc/locale.gen",
+                "arch-chroot /alitarget locale-gen",
+                "arch-chroot /alitarget bootctl \"--esp-path=/boot\" install",
+                "write /alitarget/boot/loader/loader.conf",
+                "write /alitarget/boot/loader/entries/arch-linux.conf",
+                "arch-chroot /alitarget sh -c \"curl https://example.com/arch/boot-entries > setup-boot.sh\"",
+                "arch-chroot /alitarget sh -c \"chmod +x setup-boot.sh\"",
+                "arch-chroot /alitarget sh -c ./setup-boot.sh",
+            ],
+        ),
+        (
+            include_str!("../examples/uefi-root-on-lvm-on-luks.yaml"),
+            vec![
+                "printf \"o\nw\n\" | fdisk /dev/vda",
+                "printf \"n\np\n1\n\n+300M\nw\n\" | fdisk /dev/vda",
+                "printf \"t\nef\nw\n\" | fdisk /dev/vda",
+                "printf \"n\np\n2\n\n\nw\n\" | fdisk /dev/vda",
+                "printf \"t\n2\n8e\nw\n\" | fdisk /dev/vda",
+                "cryptsetup -q luksFormat \"--key-file=-\" /dev/vda2 < <stdin>",
+                "cryptsetup luksOpen \"--key-file=-\" /dev/vda2 crypty < <stdin>",
+                "pvcreate /dev/mapper/crypty",
+                "vgcreate archvg /dev/mapper/crypty",
+                "lvcreate -L 8G archvg -n swaplv",
+                "lvcreate -l \"100%FREE\" archvg -n rootlv",
+                "mkfs.btrfs -L rootfs /dev/archvg/rootlv",
+                "mkfs.vfat -F 32 -L BOOT /dev/vda1",
+                "mkdir -p /alitarget",
+                "mount -o compress:zstd:3 /dev/archvg/rootlv /alitarget/",
+                "mkdir -p /alitarget/boot",
+                "mount /dev/vda1 /alitarget/boot",
+                "blkid -s UUID -o value /dev/archvg/rootlv",
+                "pacstrap -K /alitarget base base-devel curl git helix",
+                "arch-chroot /alitarget chpasswd -e < <stdin>",
+                "genfstab -U /alitarget",
+                "write /alitarget/etc/fstab",
+                "write /alitarget/etc/hostname",
+                "write /alitarget/etc/hosts",
+                "write /alitarget/etc/locale.conf",
+                "arch-chroot /alitarget ln -s /usr/share/zoneinfo/US/Pacific /etc/localtime",
+                "write /alitarget/etc/locale.gen",
+                "arch-chroot /alitarget locale-gen",
+                "fetch https://example.com/arch-templates/lvm-on-luks/boot-entries/main.conf",
+                "write /alitarget/boot/loader/entries/main.conf",
+                "fetch https://example.com/arch-templates/lvm-on-luks/boot-entries/setup-boot.sh",
+                "write /alitarget/setup-boot.sh",
+                "chmod 755 /alitarget/setup-boot.sh",
+                "arch-chroot /alitarget sh -c \"bootctl --path=/boot install\"",
+                "arch-chroot /alitarget sh -c ./setup-boot.sh",
+            ],
+        ),
+    ];
+
+    for (yaml, expected) in tests {
+        let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+        // Default root password is hashed with a random salt
+        manifest
+            .rootpasswd
+            .get_or_insert(Secret::from("$6$salt$hash"));
+
+        let mock = Rc::new(Mock::default().reply("blkid", "uuid-rootlv\n"));
+        executor::set_executor(mock.clone());
+
+        // Journal is shared by all tests on this thread
+        let logged = journal::actions().len();
+
+        apply_manifest(&manifest, "/alitarget", &vec![], HashSet::new())
+            .expect("failed to apply example");
+
+        assert_eq!(expected, mock.records());
+
+        // Every command is logged under the action that ran it
+        let commands: Vec<String> = journal::actions()
+            .into_iter()
+            .skip(logged)
+            .flat_map(|action| action.commands)
+            .map(|cmd| cmd.cmd)
+            .collect();
+
+        let expected_commands: Vec<&str> = expected
+            .into_iter()
+            .filter(|r| {
+                !r.starts_with("write ")
+                    && !r.starts_with("chmod ")
+                    && !r.starts_with("fetch ")
+            })
+            .collect();
+
+        assert_eq!(expected_commands, commands);
+    }
+}
+
+#[test]
+fn test_lazy_kernel_cmdline() {
+    use std::rc::Rc;
+
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = r#"
+hostname: foo
+rootfs:
+  device: /dev/vda2
+  fstype: btrfs
+"#;
+
+    let manifest = Manifest::from_yaml(yaml).unwrap();
+    let mock = Rc::new(Mock::default().fail("blkid", 2));
+    executor::set_executor(mock.clone());
+
+    let skip = HashSet::from_iter(
+        stage::STAGES
+            .into_iter()
+            .filter(|stage| *stage != Stage::Bootstrap),
+    );
+
+    // No bootloader, UKI, or cmdline, so blkid is never called
+    apply_manifest(&manifest, "/alitarget", &vec![], skip)
+        .expect("failed to apply without kernel cmdline");
+
+    assert!(mock.records().iter().all(|r| !r.starts_with("blkid")));
+}
+
+#[test]
+fn test_postinstall_apply_error() {
+    use std::rc::Rc;
+
+    use crate::types::action::{
+        Action,
+        ActionPostInstallUser,
+    };
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = r#"
+hostname: foo
+rootfs:
+  device: /dev/vda2
+  fstype: btrfs
+postinstall:
+  - echo ok
+  - exit 1
+"#;
+
+    let manifest = Manifest::from_yaml(yaml).unwrap();
+    let mock = Rc::new(Mock::default().fail("sh -c \"exit 1", 1));
+    executor::set_executor(mock.clone());
+
+    let skip = HashSet::from_iter(
+        stage::STAGES
+            .into_iter()
+            .filter(|stage| *stage != Stage::PostInstallUser),
+    );
+
+    let err = apply_manifest(&manifest, "/alitarget", &vec![], skip)
+        .expect_err("postinstall should fail");
+
+    let AliError::InstallError { error, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+
+    let AliError::ApplyError {
+        action_failed,
+        actions_performed,
+        commands,
+        ..
+    } = *error
+    else {
+        panic!("postinstall error is not ApplyError");
+    };
+
+    assert!(matches!(
+        *action_failed,
+        Action::UserPostInstall(ActionPostInstallUser::UserPostInstallCmd(
+            ref cmd
+        )) if cmd == "exit 1"
+    ));
+    assert_eq!(actions_performed.len(), 1);
+    assert_eq!(commands.len(), 1);
+}
+
+#[test]
+fn test_postinstall_journal() {
+    use std::rc::Rc;
+
+    use crate::types::action::{
+        Action,
+        ActionPostInstallUser,
+    };
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = r#"
+hostname: foo
+rootfs:
+  device: /dev/vda2
+  fstype: btrfs
+postinstall:
+  - "@download https://example.com/motd /etc/motd"
+  - echo ok
+"#;
+
+    let manifest = Manifest::from_yaml(yaml).unwrap();
+    executor::set_executor(Rc::new(Mock::default()));
+
+    let skip = HashSet::from_iter(
+        stage::STAGES
+            .into_iter()
+            .filter(|stage| *stage != Stage::PostInstallUser),
+    );
+
+    // Journal is shared by all tests on this thread
+    let logged = journal::actions().len();
+
+    apply_manifest(&manifest, "/alitarget", &vec![], skip)
+        .expect("failed to apply postinstall");
+
+    // Both hook and command are timed as actions
+    let actions: Vec<Action> = journal::actions()
+        .into_iter()
+        .skip(logged)
+        .map(|log| log.action)
+        .collect();
+
+    assert_eq!(actions.len(), 2);
+    assert!(matches!(
+        actions[0],
+        Action::UserPostInstall(ActionPostInstallUser::Hook(_))
+    ));
+    assert!(matches!(
+        actions[1],
+        Action::UserPostInstall(ActionPostInstallUser::UserPostInstallCmd(_))
+    ));
+}
+
+#[test]
+fn test_pacstrap_error_after_unbind() {
+    use std::rc::Rc;
+
+    use crate::types::action::{
+        Action,
+        ActionBootstrap,
+    };
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = r#"
+hostname: foo
+rootfs:
+  device: /dev/vda2
+  fstype: btrfs
+pacman:
+  bind_cache: true
+"#;
+
+    let manifest = Manifest::from_yaml(yaml).unwrap();
+    let mock = Rc::new(Mock::default().fail("pacstrap", 1).fail("umount", 32));
+    executor::set_executor(mock.clone());
+
+    let skip = HashSet::from_iter(
+        stage::STAGES
+            .into_iter()
+            .filter(|stage| *stage != Stage::Bootstrap),
+    );
+
+    let err = apply_manifest(&manifest, "/alitarget", &vec![], skip)
+        .expect_err("pacstrap should fail");
+
+    // Failed umount does not hide the pacstrap error
+    let AliError::InstallError { error, .. } = err else {
+        panic!("unexpected error {err:?}");
+    };
+
+    let AliError::ApplyError { action_failed, .. } = *error else {
+        panic!("pacstrap error is not ApplyError");
+    };
+
+    assert!(matches!(
+        *action_failed,
+        Action::Bootstrap(ActionBootstrap::InstallPackages { .. })
+    ));
+    assert!(mock.records().iter().any(|r| r.starts_with("umount")));
+}
+
+#[test]
+fn test_prepare_keyring() {
+    use std::rc::Rc;
+
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = r#"
+hostname: foo
+rootfs:
+  device: /dev/vda2
+  fstype: btrfs
+keyring:
+  init: true
+  keys:
+    - id: F3B607488DB35A47
+      keyserver: hkps://keyserver.ubuntu.com
+"#;
+
+    let manifest = Manifest::from_yaml(yaml).unwrap();
+    let mock = Rc::new(Mock::default());
+    executor::set_executor(mock.clone());
+
+    let skip = HashSet::from_iter(
+        stage::STAGES
+            .into_iter()
+            .filter(|stage| *stage != Stage::Bootstrap),
+    );
+
+    apply_manifest(&manifest, "/alitarget", &vec![], skip)
+        .expect("failed to apply with keyring");
+
+    let records = mock.records();
+    let position = |prefix: &str| {
+        records
+            .iter()
+            .position(|r| r.starts_with(prefix))
+            .unwrap_or_else(|| panic!("no record {prefix}: {records:?}"))
+    };
+
+    // Live system keyring is prepared before pacstrap,
+    // and keys are imported again into the fresh target keyring
+    let init = position("pacman-key --init");
+    let populate = position("pacman-key --populate");
+    let recv = position("pacman-key --keyserver");
+    let lsign = position("pacman-key --lsign-key F3B607488DB35A47");
+    let pacstrap = position("pacstrap");
+    let recv_target = position("arch-chroot /alitarget pacman-key --keyserver");
+    let lsign_target =
+        position("arch-chroot /alitarget pacman-key --lsign-key");
+
+    assert!(init < populate && populate < recv && recv < lsign);
+    assert!(lsign < pacstrap && pacstrap < recv_target);
+    assert!(recv_target < lsign_target);
+
+    // Keyring alone does not write pacman.conf
+    assert!(records.iter().all(|r| !r.contains("pacman.conf")));
+}
+
+#[test]
+fn test_phases_after_files() {
+    use std::rc::Rc;
+
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = r#"
+hostname: foo
+rootfs:
+  device: /dev/vda2
+  fstype: btrfs
+pacstrap_opts:
+  phases:
+    - name: drivers
+      packages: [mesa]
+    - name: desktop
+      packages: [sddm]
+      after: files
+files:
+  - dest: /etc/sddm.conf.d/10-theme.conf
+    content: |
+      [Theme]
+"#;
+
+    let manifest = Manifest::from_yaml(yaml).unwrap();
+    let mock = Rc::new(Mock::default());
+    executor::set_executor(mock.clone());
+
+    let skip =
+        HashSet::from_iter(stage::STAGES.into_iter().filter(|stage| {
+            !matches!(stage, Stage::Bootstrap | Stage::ChrootAli)
+        }));
+
+    apply_manifest(&manifest, "/alitarget", &vec![], skip)
+        .expect("failed to apply with package phases");
+
+    let records = mock.records();
+    let position = |needle: &str| {
+        records
+            .iter()
+            .position(|r| r.contains(needle))
+            .unwrap_or_else(|| panic!("no record {needle}: {records:?}"))
+    };
+
+    let drivers = position("pacman -S --needed --noconfirm mesa");
+    let file = position("write /alitarget/etc/sddm.conf.d/10-theme.conf");
+    let desktop = position("pacman -S --needed --noconfirm sddm");
+
+    assert!(drivers < file && file < desktop);
+}
@@ -0,0 +1,237 @@
+use crate::ali::{
+    ManifestNetwork,
+    ManifestWifi,
+    ManifestWifiNetwork,
+    NetworkBackend,
+    WifiNetworking,
+};
+
+use super::{
+    NetworkFile,
+    HEADER,
+};
+
+pub(super) const UNITS: &[&str] = &["iwd.service"];
+
+// Network profiles may contain secrets
+const MODE_PSK: u32 = 0o600;
+
+const DIR_PROFILES: &str = "/var/lib/iwd";
+const MAIN_CONF: &str = "/etc/iwd/main.conf";
+const DIR_NETWORKD: &str = "/etc/systemd/network";
+
+const DEFAULT_INTERFACE: &str = "wl*";
+
+const TOKEN_SSID: &str = "{{ ssid }}";
+const TOKEN_SECRET_KEY: &str = "{{ secret_key }}";
+const TOKEN_SECRET: &str = "{{ secret }}";
+const TOKEN_ENABLE_NETCONF: &str = "{{ enable_netconf }}";
+const TOKEN_INTERFACE: &str = "{{ inf }}";
+
+const FILENAME_PSK: &str = "{{ ssid }}.psk";
+
+const PSK: &str = r#"[Security]
+{{ secret_key }}={{ secret }}
+"#;
+
+const PSK_SETTINGS: &str = r#"
+[Settings]
+"#;
+
+const IWD_MAIN_CONF: &str = r#"[General]
+EnableNetworkConfiguration={{ enable_netconf }}
+"#;
+
+const IWD_RESOLVED: &str = r#"
+[Network]
+NameResolvingService=systemd
+"#;
+
+const FILENAME_NETWORKD: &str = "40-wireless_{{ inf }}.network";
+
+const NETWORKD_WIRELESS: &str = r#"[Match]
+Name={{ inf }}
+
+[Network]
+DHCP=yes
+IgnoreCarrierLoss=3s
+"#;
+
+pub(super) fn render(network: &ManifestNetwork) -> Vec<NetworkFile> {
+    let wifi = match network.wifi {
+        Some(ref wifi) => wifi,
+        None => return Vec::new(),
+    };
+
+    let mut files: Vec<NetworkFile> = wifi
+        .networks
+        .iter()
+        .map(|net| {
+            let filename =
+                FILENAME_PSK.replace(TOKEN_SSID, &encode_ssid(&net.ssid));
+
+            NetworkFile {
+                path: format!("{DIR_PROFILES}/{filename}"),
+                content: fmt_psk(net),
+                mode: Some(MODE_PSK),
+            }
+        })
+        .collect();
+
+    files.push(NetworkFile {
+        path: MAIN_CONF.to_string(),
+        content: fmt_main_conf(wifi, &network.backend),
+        mode: None,
+    });
+
+    if wifi.networking == WifiNetworking::Networkd {
+        let inf = wifi.interface.as_deref().unwrap_or(DEFAULT_INTERFACE);
+        let filename =
+            FILENAME_NETWORKD.replace(TOKEN_INTERFACE, &inf.replace('*', ""));
+
+        files.push(NetworkFile {
+            path: format!("{DIR_NETWORKD}/{filename}"),
+            content: format!(
+                "{HEADER}\n{}",
+                NETWORKD_WIRELESS.replace(TOKEN_INTERFACE, inf)
+            ),
+            mode: None,
+        });
+    }
+
+    files
+}
+
+/// Returns systemd-networkd units required for DHCP handoff,
+/// if not already enabled by the network backend.
+pub(super) fn networkd_units(
+    network: &ManifestNetwork,
+) -> &'static [&'static str] {
+    let handoff = network
+        .wifi
+        .as_ref()
+        .is_some_and(|wifi| wifi.networking == WifiNetworking::Networkd);
+
+    match (handoff, &network.backend) {
+        (true, NetworkBackend::Netctl) => super::networkd::UNITS,
+        _ => &[],
+    }
+}
+
+fn fmt_psk(net: &ManifestWifiNetwork) -> String {
+    let (key, secret) = match (&net.psk, &net.passphrase) {
+        (Some(psk), _) => ("PreSharedKey", psk.expose()),
+        (None, Some(passphrase)) => ("Passphrase", passphrase.expose()),
+        (None, None) => ("Passphrase", ""),
+    };
+
+    let mut s = PSK
+        .replace(TOKEN_SECRET_KEY, key)
+        .replace(TOKEN_SECRET, secret);
+
+    let mut settings = Vec::new();
+    if net.hidden {
+        settings.push("Hidden=true");
+    }
+    if net.auto_connect == Some(false) {
+        settings.push("AutoConnect=false");
+    }
+
+    if !settings.is_empty() {
+        s.push_str(PSK_SETTINGS);
+        s.push_str(&settings.join("\n"));
+        s.push('\n');
+    }
+
+    s
+}
+
+fn fmt_main_conf(wifi: &ManifestWifi, backend: &NetworkBackend) -> String {
+    let enable_netconf = match wifi.networking {
+        WifiNetworking::Iwd => "true",
+        WifiNetworking::Networkd => "false",
+    };
+
+    let mut s = format!(
+        "{HEADER}\n{}",
+        IWD_MAIN_CONF.replace(TOKEN_ENABLE_NETCONF, enable_netconf)
+    );
+
+    // systemd-resolved is enabled with networkd backend
+    if wifi.networking == WifiNetworking::Iwd
+        && *backend == NetworkBackend::Networkd
+    {
+        s.push_str(IWD_RESOLVED);
+    }
+
+    s
+}
+
+// iwd uses SSID as filename if it only contains alphanumerics,
+// spaces, underscores, and hyphens. Otherwise, "=" followed by
+// hex-encoded SSID is used.
+fn encode_ssid(ssid: &str) -> String {
+    let plain = ssid
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == ' ' || c == '_' || c == '-');
+
+    if plain {
+        return ssid.to_string();
+    }
+
+    let hex: String = ssid.bytes().map(|b| format!("{b:02x}")).collect();
+    format!("={hex}")
+}
+
+#[test]
+fn test_tokens() {
+    assert!(FILENAME_PSK.contains(TOKEN_SSID));
+    assert!(PSK.contains(TOKEN_SECRET_KEY));
+    assert!(PSK.contains(TOKEN_SECRET));
+    assert!(IWD_MAIN_CONF.contains(TOKEN_ENABLE_NETCONF));
+    assert!(FILENAME_NETWORKD.contains(TOKEN_INTERFACE));
+    assert!(NETWORKD_WIRELESS.contains(TOKEN_INTERFACE));
+}
+
+#[test]
+fn test_render_iwd() {
+    let yaml = r#"
+wifi:
+  networking: networkd
+  interface: wlan0
+  networks:
+    - ssid: Home Wifi
+      passphrase: foobarbaz
+    - ssid: "cafe's"
+      psk: abcd
+      hidden: true
+      auto_connect: false
+"#;
+
+    let network: ManifestNetwork =
+        serde_yaml::from_str(yaml).expect("failed to parse network");
+
+    let files = render(&network);
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+
+    assert_eq!(
+        vec![
+            "/var/lib/iwd/Home Wifi.psk",
+            "/var/lib/iwd/=636166652773.psk",
+            "/etc/iwd/main.conf",
+            "/etc/systemd/network/40-wireless_wlan0.network",
+        ],
+        paths,
+    );
+
+    assert_eq!("[Security]\nPassphrase=foobarbaz\n", files[0].content);
+    assert_eq!(
+        "[Security]\nPreSharedKey=abcd\n\n[Settings]\nHidden=true\nAutoConnect=false\n",
+        files[1].content
+    );
+    assert_eq!(Some(0o600), files[1].mode);
+    assert_eq!(
+        "# Installed by ali-rs\n[General]\nEnableNetworkConfiguration=false\n",
+        files[2].content
+    );
+}
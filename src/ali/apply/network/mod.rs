@@ -0,0 +1,170 @@
+mod iwd;
+mod netctl;
+mod networkd;
+mod networkmanager;
+
+use crate::ali::{
+    Manifest,
+    ManifestNetwork,
+    NetworkBackend,
+};
+use crate::errors::AliError;
+use crate::types::action::{
+    ActionChrootAli,
+    ActionRoutine,
+};
+use crate::utils::fs::{
+    set_mode,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::map_err::{
+    map_err_chroot_ali,
+    map_err_routine,
+};
+
+const HEADER: &str = "# Installed by ali-rs";
+
+/// Network configuration file rendered by a backend,
+/// with path relative to the target root.
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct NetworkFile {
+    pub path: String,
+    pub content: String,
+    pub mode: Option<u32>,
+}
+
+/// Writes network configuration files for the manifest backend
+/// into the target, one action per file.
+pub fn write_network(
+    manifest: &Manifest,
+    location: &str,
+) -> Result<Vec<ActionRoutine>, AliError> {
+    let mut actions = Vec::new();
+
+    let network = match manifest.network {
+        Some(ref network) => network,
+        None => return Ok(actions),
+    };
+
+    for file in render(network) {
+        let dst = format!("{location}{}", file.path);
+        let action_write = ActionRoutine::WriteNetworkConfig(file.path);
+
+        let result = write_file(&dst, &file.content).and_then(|_| {
+            match file.mode {
+                Some(mode) => set_mode(&dst, mode),
+                None => Ok(()),
+            }
+        });
+
+        if let Err(err) = result {
+            return Err(map_err_routine(err, action_write, actions));
+        }
+
+        actions.push(journal::done(action_write));
+    }
+
+    Ok(actions)
+}
+
+/// Enables services (or netctl profiles) for the manifest backend
+/// inside chroot.
+pub fn enable_network(
+    manifest: &Manifest,
+    location: &str,
+) -> Result<Vec<ActionChrootAli>, AliError> {
+    let mut actions = Vec::new();
+
+    let network = match manifest.network {
+        Some(ref network) => network,
+        None => return Ok(actions),
+    };
+
+    let mut enables = match network.backend {
+        NetworkBackend::Networkd => networkd::UNITS.to_vec(),
+        NetworkBackend::NetworkManager => networkmanager::UNITS.to_vec(),
+        NetworkBackend::Netctl => vec![],
+    };
+
+    if network.wifi.is_some() {
+        enables.extend(iwd::UNITS);
+        enables.extend(iwd::networkd_units(network));
+    }
+
+    for unit in enables {
+        let action_enable = ActionChrootAli::EnableUnit {
+            unit: unit.to_string(),
+            global: false,
+        };
+
+        let cmd_enable = ["systemctl", "enable", unit];
+        if let Err(err) = shell::arch_chroot(location, &cmd_enable) {
+            return Err(map_err_chroot_ali(err, action_enable, actions));
+        }
+
+        actions.push(journal::done(action_enable));
+    }
+
+    if network.backend != NetworkBackend::Netctl {
+        return Ok(actions);
+    }
+
+    for profile in netctl::profiles(network) {
+        let action_profile =
+            ActionChrootAli::EnableNetctlProfile(profile.clone());
+
+        let cmd_enable = ["netctl", "enable", &profile];
+        if let Err(err) = shell::arch_chroot(location, &cmd_enable) {
+            return Err(map_err_chroot_ali(err, action_profile, actions));
+        }
+
+        actions.push(journal::done(action_profile));
+    }
+
+    Ok(actions)
+}
+
+pub(super) fn render(network: &ManifestNetwork) -> Vec<NetworkFile> {
+    let mut files = match network.backend {
+        NetworkBackend::Networkd => networkd::render(network),
+        NetworkBackend::NetworkManager => networkmanager::render(network),
+        NetworkBackend::Netctl => netctl::render(network),
+    };
+
+    files.extend(iwd::render(network));
+
+    files
+}
+
+fn is_ipv6(addr: &str) -> bool {
+    addr.contains(':')
+}
+
+#[cfg(test)]
+fn test_network() -> ManifestNetwork {
+    let yaml = r#"
+interfaces:
+  - name: bond0
+    addresses: [10.0.0.10/24, fd00::10/64]
+    gateways: [10.0.0.1]
+    dns: [1.1.1.1]
+    routes:
+      - to: 10.1.0.0/16
+        via: 10.0.0.254
+    bond:
+      mode: 802.3ad
+      slaves: [eno1, eno2]
+  - name: vlan100
+    dhcp: true
+    vlan:
+      parent: bond0
+      id: 100
+"#;
+
+    serde_yaml::from_str(yaml).expect("failed to parse network")
+}
@@ -0,0 +1,166 @@
+use crate::ali::{
+    ManifestInterface,
+    ManifestNetwork,
+};
+
+use super::{
+    is_ipv6,
+    NetworkFile,
+    HEADER,
+};
+
+const DIR: &str = "/etc/netctl";
+
+pub(super) fn render(network: &ManifestNetwork) -> Vec<NetworkFile> {
+    network
+        .interfaces
+        .iter()
+        .map(|iface| {
+            NetworkFile {
+                path: format!("{DIR}/{}", iface.name),
+                content: format!("{HEADER}\n{}", fmt_profile(iface)),
+                mode: None,
+            }
+        })
+        .collect()
+}
+
+/// Returns netctl profile names to enable
+pub(super) fn profiles(network: &ManifestNetwork) -> Vec<String> {
+    network
+        .interfaces
+        .iter()
+        .map(|iface| iface.name.clone())
+        .collect()
+}
+
+fn fmt_profile(iface: &ManifestInterface) -> String {
+    let connection = match (&iface.bond, &iface.vlan) {
+        (Some(_), _) => "bond",
+        (_, Some(_)) => "vlan",
+        _ => "ethernet",
+    };
+
+    let mut lines = vec![
+        format!("Description='ali-rs {}'", iface.name),
+        format!("Interface={}", iface.name),
+        format!("Connection={connection}"),
+    ];
+
+    if let Some(ref bond) = iface.bond {
+        lines.push(format!("BindsToInterfaces=({})", bond.slaves.join(" ")));
+        lines.push(format!("Mode={}", bond.mode));
+    }
+
+    if let Some(ref vlan) = iface.vlan {
+        lines.push(format!("BindsToInterfaces={}", vlan.parent));
+        lines.push(format!("VLANID={}", vlan.id));
+    }
+
+    let (v4, v6): (Vec<_>, Vec<_>) =
+        iface.addresses.iter().partition(|addr| !is_ipv6(addr));
+
+    let ip = match (iface.dhcp, v4.is_empty()) {
+        (true, _) => "dhcp",
+        (false, false) => "static",
+        (false, true) => "no",
+    };
+    lines.push(format!("IP={ip}"));
+
+    if !v4.is_empty() {
+        lines.push(format!("Address=({})", quote(&v4)));
+    }
+
+    let ip6 = match (iface.dhcp, v6.is_empty()) {
+        (_, false) => Some("static"),
+        (true, true) => Some("stateless"),
+        (false, true) => None,
+    };
+    if let Some(ip6) = ip6 {
+        lines.push(format!("IP6={ip6}"));
+    }
+
+    if !v6.is_empty() {
+        lines.push(format!("Address6=({})", quote(&v6)));
+    }
+
+    for gateway in &iface.gateways {
+        match is_ipv6(gateway) {
+            true => lines.push(format!("Gateway6='{gateway}'")),
+            false => lines.push(format!("Gateway='{gateway}'")),
+        }
+    }
+
+    let (routes, routes6): (Vec<_>, Vec<_>) = iface
+        .routes
+        .iter()
+        .map(|route| {
+            match route.via {
+                Some(ref via) => format!("{} via {via}", route.to),
+                None => route.to.clone(),
+            }
+        })
+        .partition(|route| !is_ipv6(route));
+
+    if !routes.is_empty() {
+        lines.push(format!("Routes=({})", quote(&routes)));
+    }
+
+    if !routes6.is_empty() {
+        lines.push(format!("Routes6=({})", quote(&routes6)));
+    }
+
+    if !iface.dns.is_empty() {
+        lines.push(format!("DNS=({})", quote(&iface.dns)));
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+fn quote<S: AsRef<str>>(values: &[S]) -> String {
+    values
+        .iter()
+        .map(|v| format!("'{}'", v.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[test]
+fn test_render_netctl() {
+    let files = render(&super::test_network());
+
+    assert_eq!(2, files.len());
+    assert_eq!(
+        r#"# Installed by ali-rs
+Description='ali-rs bond0'
+Interface=bond0
+Connection=bond
+BindsToInterfaces=(eno1 eno2)
+Mode=802.3ad
+IP=static
+Address=('10.0.0.10/24')
+IP6=static
+Address6=('fd00::10/64')
+Gateway='10.0.0.1'
+Routes=('10.1.0.0/16 via 10.0.0.254')
+DNS=('1.1.1.1')
+"#,
+        files[0].content,
+    );
+
+    assert_eq!(
+        r#"# Installed by ali-rs
+Description='ali-rs vlan100'
+Interface=vlan100
+Connection=vlan
+BindsToInterfaces=bond0
+VLANID=100
+IP=dhcp
+IP6=stateless
+"#,
+        files[1].content,
+    );
+}
@@ -0,0 +1,172 @@
+use crate::ali::{
+    ManifestInterface,
+    ManifestNetwork,
+};
+
+use super::{
+    NetworkFile,
+    HEADER,
+};
+
+pub(super) const UNITS: &[&str] =
+    &["systemd-networkd.service", "systemd-resolved.service"];
+
+const DIR: &str = "/etc/systemd/network";
+
+pub(super) fn render(network: &ManifestNetwork) -> Vec<NetworkFile> {
+    let mut files = Vec::new();
+
+    for iface in &network.interfaces {
+        if let Some(ref bond) = iface.bond {
+            files.push(file(
+                &format!("25-{}.netdev", iface.name),
+                format!(
+                    "[NetDev]\nName={}\nKind=bond\n\n[Bond]\nMode={}\n",
+                    iface.name, bond.mode
+                ),
+            ));
+
+            for slave in &bond.slaves {
+                files.push(file(
+                    &format!("20-{slave}.network"),
+                    format!(
+                        "[Match]\nName={slave}\n\n[Network]\nBond={}\n",
+                        iface.name
+                    ),
+                ));
+            }
+        }
+
+        if let Some(ref vlan) = iface.vlan {
+            files.push(file(
+                &format!("25-{}.netdev", iface.name),
+                format!(
+                    "[NetDev]\nName={}\nKind=vlan\n\n[VLAN]\nId={}\n",
+                    iface.name, vlan.id
+                ),
+            ));
+
+            // Parent not configured in manifest only carries the VLAN
+            let has_parent =
+                network.interfaces.iter().any(|i| i.name == vlan.parent);
+
+            if !has_parent {
+                files.push(file(
+                    &format!("30-{}.network", vlan.parent),
+                    format!(
+                        "[Match]\nName={}\n\n[Network]\nVLAN={}\n",
+                        vlan.parent, iface.name
+                    ),
+                ));
+            }
+        }
+
+        files.push(file(
+            &format!("30-{}.network", iface.name),
+            fmt_network(iface, network),
+        ));
+    }
+
+    files
+}
+
+fn fmt_network(iface: &ManifestInterface, network: &ManifestNetwork) -> String {
+    let mut lines = vec![
+        "[Match]".to_string(),
+        format!("Name={}", iface.name),
+        String::new(),
+        "[Network]".to_string(),
+    ];
+
+    if iface.dhcp {
+        lines.push("DHCP=yes".to_string());
+    }
+
+    for addr in &iface.addresses {
+        lines.push(format!("Address={addr}"));
+    }
+
+    for gateway in &iface.gateways {
+        lines.push(format!("Gateway={gateway}"));
+    }
+
+    for dns in &iface.dns {
+        lines.push(format!("DNS={dns}"));
+    }
+
+    let vlans = network.interfaces.iter().filter_map(|i| {
+        i.vlan
+            .as_ref()
+            .filter(|vlan| vlan.parent == iface.name)
+            .map(|_| &i.name)
+    });
+
+    for vlan in vlans {
+        lines.push(format!("VLAN={vlan}"));
+    }
+
+    for route in &iface.routes {
+        lines.push(String::new());
+        lines.push("[Route]".to_string());
+        lines.push(format!("Destination={}", route.to));
+
+        if let Some(ref via) = route.via {
+            lines.push(format!("Gateway={via}"));
+        }
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+fn file(filename: &str, content: String) -> NetworkFile {
+    NetworkFile {
+        path: format!("{DIR}/{filename}"),
+        content: format!("{HEADER}\n{content}"),
+        mode: None,
+    }
+}
+
+#[test]
+fn test_render_networkd() {
+    let files = render(&super::test_network());
+
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(
+        vec![
+            "/etc/systemd/network/25-bond0.netdev",
+            "/etc/systemd/network/20-eno1.network",
+            "/etc/systemd/network/20-eno2.network",
+            "/etc/systemd/network/30-bond0.network",
+            "/etc/systemd/network/25-vlan100.netdev",
+            "/etc/systemd/network/30-vlan100.network",
+        ],
+        paths,
+    );
+
+    assert_eq!(
+        r#"# Installed by ali-rs
+[Match]
+Name=bond0
+
+[Network]
+Address=10.0.0.10/24
+Address=fd00::10/64
+Gateway=10.0.0.1
+DNS=1.1.1.1
+VLAN=vlan100
+
+[Route]
+Destination=10.1.0.0/16
+Gateway=10.0.0.254
+"#,
+        files[3].content,
+    );
+
+    assert_eq!(
+        "# Installed by ali-rs\n[NetDev]\nName=vlan100\nKind=vlan\n\n[VLAN]\nId=100\n",
+        files[4].content,
+    );
+}
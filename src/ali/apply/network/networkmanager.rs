@@ -0,0 +1,170 @@
+use crate::ali::{
+    ManifestInterface,
+    ManifestNetwork,
+};
+
+use super::{
+    is_ipv6,
+    NetworkFile,
+    HEADER,
+};
+
+pub(super) const UNITS: &[&str] = &["NetworkManager.service"];
+
+const DIR: &str = "/etc/NetworkManager/system-connections";
+
+// NetworkManager ignores keyfiles readable by others
+const MODE: u32 = 0o600;
+
+pub(super) fn render(network: &ManifestNetwork) -> Vec<NetworkFile> {
+    let mut files = Vec::new();
+
+    for iface in &network.interfaces {
+        if let Some(ref bond) = iface.bond {
+            for slave in &bond.slaves {
+                files.push(file(
+                    slave,
+                    format!(
+                        "[connection]\nid={slave}\ntype=ethernet\ninterface-name={slave}\nmaster={}\nslave-type=bond\n",
+                        iface.name
+                    ),
+                ));
+            }
+        }
+
+        files.push(file(&iface.name, fmt_connection(iface)));
+    }
+
+    files
+}
+
+fn fmt_connection(iface: &ManifestInterface) -> String {
+    let conn_type = match (&iface.bond, &iface.vlan) {
+        (Some(_), _) => "bond",
+        (_, Some(_)) => "vlan",
+        _ => "ethernet",
+    };
+
+    let mut lines = vec![
+        "[connection]".to_string(),
+        format!("id={}", iface.name),
+        format!("type={conn_type}"),
+        format!("interface-name={}", iface.name),
+    ];
+
+    if let Some(ref bond) = iface.bond {
+        lines.push(String::new());
+        lines.push("[bond]".to_string());
+        lines.push(format!("mode={}", bond.mode));
+    }
+
+    if let Some(ref vlan) = iface.vlan {
+        lines.push(String::new());
+        lines.push("[vlan]".to_string());
+        lines.push(format!("id={}", vlan.id));
+        lines.push(format!("parent={}", vlan.parent));
+    }
+
+    lines.extend(fmt_ip(iface, false));
+    lines.extend(fmt_ip(iface, true));
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+fn fmt_ip(iface: &ManifestInterface, v6: bool) -> Vec<String> {
+    let section = if v6 { "[ipv6]" } else { "[ipv4]" };
+    let is_family = |addr: &&String| is_ipv6(addr) == v6;
+
+    let addresses: Vec<_> = iface.addresses.iter().filter(is_family).collect();
+    let method = match (iface.dhcp, addresses.is_empty(), v6) {
+        (true, _, _) => "auto",
+        (false, false, _) => "manual",
+        (false, true, false) => "disabled",
+        (false, true, true) => "ignore",
+    };
+
+    let mut lines = vec![
+        String::new(),
+        section.to_string(),
+        format!("method={method}"),
+    ];
+
+    for (i, addr) in addresses.iter().enumerate() {
+        lines.push(format!("address{}={addr}", i + 1));
+    }
+
+    if let Some(gateway) = iface.gateways.iter().find(is_family) {
+        lines.push(format!("gateway={gateway}"));
+    }
+
+    let dns: Vec<_> = iface.dns.iter().filter(is_family).collect();
+    if !dns.is_empty() {
+        let dns: Vec<_> = dns.iter().map(|d| format!("{d};")).collect();
+        lines.push(format!("dns={}", dns.join("")));
+    }
+
+    let routes = iface.routes.iter().filter(|r| is_ipv6(&r.to) == v6);
+    for (i, route) in routes.enumerate() {
+        let line = match route.via {
+            Some(ref via) => format!("route{}={},{via}", i + 1, route.to),
+            None => format!("route{}={}", i + 1, route.to),
+        };
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn file(name: &str, content: String) -> NetworkFile {
+    NetworkFile {
+        path: format!("{DIR}/{name}.nmconnection"),
+        content: format!("{HEADER}\n{content}"),
+        mode: Some(MODE),
+    }
+}
+
+#[test]
+fn test_render_networkmanager() {
+    let files = render(&super::test_network());
+
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(
+        vec![
+            "/etc/NetworkManager/system-connections/eno1.nmconnection",
+            "/etc/NetworkManager/system-connections/eno2.nmconnection",
+            "/etc/NetworkManager/system-connections/bond0.nmconnection",
+            "/etc/NetworkManager/system-connections/vlan100.nmconnection",
+        ],
+        paths,
+    );
+
+    assert!(files.iter().all(|f| f.mode == Some(0o600)));
+
+    assert_eq!(
+        r#"# Installed by ali-rs
+[connection]
+id=bond0
+type=bond
+interface-name=bond0
+
+[bond]
+mode=802.3ad
+
+[ipv4]
+method=manual
+address1=10.0.0.10/24
+gateway=10.0.0.1
+dns=1.1.1.1;
+route1=10.1.0.0/16,10.0.0.254
+
+[ipv6]
+method=manual
+address1=fd00::10/64
+"#,
+        files[2].content,
+    );
+}
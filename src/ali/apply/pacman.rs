@@ -0,0 +1,275 @@
+use crate::ali::ManifestPacman;
+use crate::errors::AliError;
+use crate::types::action::ActionBootstrap;
+use crate::utils::fs::write_file;
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::map_err::map_err_bootstrap;
+
+/// pacman.conf on the live system used by `pacstrap -C`
+pub const HOST_PACMAN_CONF: &str = "/tmp/ali-rs/pacman.conf";
+const HOST_MIRRORLIST: &str = "/tmp/ali-rs/mirrorlist";
+
+const PACMAN_CONF: &str = "/etc/pacman.conf";
+const MIRRORLIST: &str = "/etc/pacman.d/mirrorlist";
+
+const REPOS_DEFAULT: [&str; 2] = ["core", "extra"];
+
+const CACHE_DIR: &str = "/var/cache/pacman/pkg";
+
+/// Writes pacman.conf (and mirrorlist if mirrors are given)
+/// on the live system for pacstrap.
+pub fn write_host_conf(
+    conf: &ManifestPacman,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    let mirrorlist = match conf.mirrors.is_empty() {
+        true => MIRRORLIST,
+        false => HOST_MIRRORLIST,
+    };
+
+    write_conf(conf, "", HOST_PACMAN_CONF, mirrorlist)
+}
+
+/// Returns pacman.conf on the live system to resolve packages with,
+/// writing one from `conf` if given.
+pub fn host_conf(
+    conf: &Option<ManifestPacman>,
+) -> Result<&'static str, AliError> {
+    match conf {
+        Some(conf) => {
+            write_host_conf(conf)?;
+            Ok(HOST_PACMAN_CONF)
+        }
+        None => Ok(PACMAN_CONF),
+    }
+}
+
+/// Writes pacman.conf and mirrorlist into the target after pacstrap,
+/// so that the installed system uses the same configuration.
+pub fn write_target_conf(
+    conf: &ManifestPacman,
+    location: &str,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    write_conf(conf, location, PACMAN_CONF, MIRRORLIST)
+}
+
+/// Bind-mounts host package cache into the target,
+/// so that packages are not downloaded twice.
+pub fn bind_cache(
+    conf: &ManifestPacman,
+    location: &str,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    let mut actions = Vec::new();
+
+    let src = match (&conf.cache_dir, conf.bind_cache) {
+        (Some(cache_dir), _) => cache_dir.as_str(),
+        (None, true) => CACHE_DIR,
+        (None, false) => return Ok(actions),
+    };
+
+    let dst = format!("{location}{CACHE_DIR}");
+    let action_bind = ActionBootstrap::BindMountCache {
+        src: src.to_string(),
+        dst: dst.clone(),
+    };
+
+    let result = shell::exec("mkdir", &["-p", &dst])
+        .and_then(|_| shell::exec("mount", &["--bind", src, &dst]));
+
+    if let Err(err) = result {
+        return Err(map_err_bootstrap(err, action_bind, actions));
+    }
+
+    actions.push(journal::done(action_bind));
+
+    Ok(actions)
+}
+
+/// Unmounts package cache bind-mounted by [`bind_cache`]
+pub fn unbind_cache(
+    conf: &ManifestPacman,
+    location: &str,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    let mut actions = Vec::new();
+
+    if conf.cache_dir.is_none() && !conf.bind_cache {
+        return Ok(actions);
+    }
+
+    let dst = format!("{location}{CACHE_DIR}");
+    let action_unmount = ActionBootstrap::UnmountCache(dst.clone());
+    if let Err(err) = shell::exec("umount", &[&dst]) {
+        return Err(map_err_bootstrap(err, action_unmount, actions));
+    }
+
+    actions.push(journal::done(action_unmount));
+
+    Ok(actions)
+}
+
+fn write_conf(
+    conf: &ManifestPacman,
+    location: &str,
+    pacman_conf: &str,
+    mirrorlist: &str,
+) -> Result<Vec<ActionBootstrap>, AliError> {
+    let mut actions = Vec::new();
+
+    if !conf.mirrors.is_empty() {
+        let dst = format!("{location}{mirrorlist}");
+        let action_mirrorlist = ActionBootstrap::WriteMirrorlist(dst.clone());
+        if let Err(err) = write_file(&dst, fmt_mirrorlist(&conf.mirrors)) {
+            return Err(map_err_bootstrap(err, action_mirrorlist, actions));
+        }
+
+        actions.push(journal::done(action_mirrorlist));
+    }
+
+    let dst = format!("{location}{pacman_conf}");
+    let action_conf = ActionBootstrap::WritePacmanConf(dst.clone());
+    if let Err(err) = write_file(&dst, fmt_pacman_conf(conf, mirrorlist)) {
+        return Err(map_err_bootstrap(err, action_conf, actions));
+    }
+
+    actions.push(journal::done(action_conf));
+
+    Ok(actions)
+}
+
+fn fmt_mirrorlist(mirrors: &[String]) -> String {
+    let mut lines = vec!["# Installed by ali-rs".to_string()];
+    for mirror in mirrors {
+        lines.push(format!("Server = {mirror}"));
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+// Same [options] defaults as pacman.conf shipped by Arch Linux
+fn fmt_pacman_conf(conf: &ManifestPacman, mirrorlist: &str) -> String {
+    let mut lines = vec![
+        "# Installed by ali-rs".to_string(),
+        "[options]".to_string(),
+        "HoldPkg = pacman glibc".to_string(),
+        "Architecture = auto".to_string(),
+        "CheckSpace".to_string(),
+        "SigLevel = Required DatabaseOptional".to_string(),
+        "LocalFileSigLevel = Optional".to_string(),
+    ];
+
+    if let Some(n) = conf.parallel_downloads {
+        lines.push(format!("ParallelDownloads = {n}"));
+    }
+
+    if conf.color {
+        lines.push("Color".to_string());
+    }
+
+    if !conf.ignore_pkg.is_empty() {
+        lines.push(format!("IgnorePkg = {}", conf.ignore_pkg.join(" ")));
+    }
+
+    // Offline installs only use repos from manifest
+    let repos_default = match conf.offline {
+        true => &[][..],
+        false => &REPOS_DEFAULT[..],
+    };
+
+    for repo in repos_default {
+        lines.push(String::new());
+        lines.push(format!("[{repo}]"));
+        lines.push(format!("Include = {mirrorlist}"));
+    }
+
+    for repo in &conf.repos {
+        lines.push(String::new());
+        lines.push(format!("[{}]", repo.name));
+
+        if let Some(ref sig_level) = repo.sig_level {
+            lines.push(format!("SigLevel = {sig_level}"));
+        }
+
+        match repo.servers.is_empty() {
+            true => lines.push(format!("Include = {mirrorlist}")),
+            false => {
+                for server in &repo.servers {
+                    lines.push(format!("Server = {server}"));
+                }
+            }
+        }
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+#[test]
+fn test_fmt_pacman_conf() {
+    let yaml = r#"
+mirrors: [http://mirror.local/archlinux/$repo/os/$arch]
+parallel_downloads: 5
+color: true
+ignore_pkg: [linux, linux-headers]
+repos:
+  - name: multilib
+  - name: custom
+    servers: [http://repo.local/$arch]
+    sig_level: Optional TrustAll
+"#;
+
+    let conf: ManifestPacman =
+        serde_yaml::from_str(yaml).expect("failed to parse pacman");
+
+    let expected = r#"# Installed by ali-rs
+[options]
+HoldPkg = pacman glibc
+Architecture = auto
+CheckSpace
+SigLevel = Required DatabaseOptional
+LocalFileSigLevel = Optional
+ParallelDownloads = 5
+Color
+IgnorePkg = linux linux-headers
+
+[core]
+Include = /tmp/ali-rs/mirrorlist
+
+[extra]
+Include = /tmp/ali-rs/mirrorlist
+
+[multilib]
+Include = /tmp/ali-rs/mirrorlist
+
+[custom]
+SigLevel = Optional TrustAll
+Server = http://repo.local/$arch
+"#;
+
+    assert_eq!(expected, fmt_pacman_conf(&conf, HOST_MIRRORLIST));
+
+    let offline = ManifestPacman {
+        offline: true,
+        repos: vec![crate::ali::ManifestRepo {
+            name: "local".to_string(),
+            servers: vec!["file:///srv/repo".to_string()],
+            sig_level: None,
+        }],
+        ..Default::default()
+    };
+
+    assert!(fmt_pacman_conf(&offline, MIRRORLIST).ends_with(
+        "LocalFileSigLevel = Optional\n\n[local]\nServer = file:///srv/repo\n"
+    ));
+    assert_eq!(
+        "# Installed by ali-rs\nServer = http://mirror.local/archlinux/$repo/os/$arch\n",
+        fmt_mirrorlist(&conf.mirrors)
+    );
+}
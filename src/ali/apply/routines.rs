@@ -1,10 +1,28 @@
-use crate::ali::Manifest;
+use crate::ali::{
+    Manifest,
+    ManifestRoot,
+};
 use crate::constants::defaults;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 use crate::types::action::ActionRoutine;
-use crate::utils::shell;
+use crate::types::secret::Secret;
+use crate::utils::fs::{
+    read_file,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
 
 use super::map_err::map_err_routine;
+use super::{
+    locale,
+    users,
+};
 
 pub fn ali_routines(
     manifest: &Manifest,
@@ -12,35 +30,77 @@ pub fn ali_routines(
 ) -> Result<Vec<ActionRoutine>, AliError> {
     let mut actions = Vec::new();
 
-    let action_rootpasswd = ActionRoutine::RootPasswd;
-    if let Err(err) = root_password(&manifest.rootpasswd, install_location) {
-        return Err(map_err_routine(err, action_rootpasswd, actions));
+    let action_root = match manifest.root_account {
+        Some(ManifestRoot { lock: true, .. }) => ActionRoutine::LockRoot,
+        _ => ActionRoutine::RootPasswd,
+    };
+    if let Err(err) = root_password(manifest, install_location) {
+        return Err(map_err_routine(err, action_root, actions));
     }
-    actions.push(action_rootpasswd);
+    actions.push(journal::done(action_root));
 
     let action_genfstab = ActionRoutine::GenFstab;
     if let Err(err) = genfstab_uuid(install_location) {
         return Err(map_err_routine(err, action_genfstab, actions));
     }
-    actions.push(action_genfstab);
+    actions.push(journal::done(action_genfstab));
 
     let action_set_hostname = ActionRoutine::SetHostname;
     if let Err(err) = hostname(&manifest.hostname, install_location) {
         return Err(map_err_routine(err, action_set_hostname, actions));
     }
-    actions.push(action_set_hostname);
+    actions.push(journal::done(action_set_hostname));
+
+    let action_hosts = ActionRoutine::WriteHosts;
+    let etc_hosts = format!("{install_location}/etc/hosts");
+    if let Err(err) = write_file(&etc_hosts, fmt_hosts(manifest)) {
+        return Err(map_err_routine(err, action_hosts, actions));
+    }
+    actions.push(journal::done(action_hosts));
 
     let action_locale_conf = ActionRoutine::LocaleConf;
-    if let Err(err) = locale_conf(install_location) {
+    if let Err(err) = locale_conf(manifest, install_location) {
         return Err(map_err_routine(err, action_locale_conf, actions));
     }
-    actions.push(action_locale_conf);
+    actions.push(journal::done(action_locale_conf));
+
+    let manifest_locale = match manifest.locale {
+        Some(ref manifest_locale) => manifest_locale,
+        None => return Ok(actions),
+    };
+
+    if let Some(vconsole) = locale::fmt_vconsole_conf(manifest_locale) {
+        let action_vconsole = ActionRoutine::VconsoleConf;
+        let dst = format!("{install_location}/etc/vconsole.conf");
+        if let Err(err) = write_file(&dst, vconsole) {
+            return Err(map_err_routine(err, action_vconsole, actions));
+        }
+        actions.push(journal::done(action_vconsole));
+    }
+
+    if let Some(ref layout) = manifest_locale.x11_layout {
+        let action_x11 = ActionRoutine::X11KeyboardConf;
+        let dst =
+            format!("{install_location}/etc/X11/xorg.conf.d/00-keyboard.conf");
+        if let Err(err) = write_file(&dst, locale::fmt_x11_keyboard(layout)) {
+            return Err(map_err_routine(err, action_x11, actions));
+        }
+        actions.push(journal::done(action_x11));
+    }
 
     Ok(actions)
 }
 
+// Appends genfstab output to /etc/fstab, which is created by pacstrap
 fn genfstab_uuid(install_location: &str) -> Result<(), AliError> {
-    shell::sh_c(&cmd_genfstab_uuid(install_location))
+    let entries =
+        shell::exec_with_output("genfstab", &["-U", install_location])?;
+
+    let fstab = format!("{install_location}/etc/fstab");
+    let mut content = read_file(&fstab).unwrap_or_default().into_bytes();
+    content.extend(entries);
+
+    write_file(&fstab, content)
 }
 
 fn hostname(
@@ -51,39 +111,157 @@ fn hostname(
 
     let etc_hostname = format!("{install_location}/etc/hostname");
 
-    std::fs::write(&etc_hostname, hostname).map_err(|err| {
-        AliError::FileError(
-            err,
-            format!("failed to write hostname to {etc_hostname}"),
-        )
-    })
+    write_file(&etc_hostname, hostname)
 }
 
-fn locale_conf(install_location: &str) -> Result<(), AliError> {
+fn locale_conf(
+    manifest: &Manifest,
+    install_location: &str,
+) -> Result<(), AliError> {
     let dst = format!("{install_location}/etc/locale.conf");
+    let content = locale::fmt_locale_conf(&manifest.locale);
 
-    std::fs::write(&dst, defaults::LOCALE_CONF).map_err(|err| {
-        AliError::FileError(
-            err,
-            format!("failed to create new locale.conf {dst}"),
-        )
-    })
+    write_file(&dst, content)
 }
 
+// Sets root password from rootpasswd or root_account, or locks root.
+// If neither is given, the default password is used,
+// which validation warns about.
 fn root_password(
-    hashed_root_passwd: &Option<String>,
+    manifest: &Manifest,
     install_location: &str,
 ) -> Result<(), AliError> {
-    let password = hashed_root_passwd
-        .clone()
-        .unwrap_or(defaults::hashed_password());
+    let hash = match (&manifest.rootpasswd, &manifest.root_account) {
+        (Some(hash), None) => hash.clone(),
+        (None, Some(root)) => {
+            if root.lock {
+                return shell::arch_chroot(
+                    install_location,
+                    &["passwd", "--lock", "root"],
+                );
+            }
+
+            match (&root.password, &root.password_hash) {
+                (Some(plain), None) => {
+                    users::hash_password("root", plain, root.scheme)?
+                }
+                (None, Some(hash)) => hash.clone(),
+                (None, None) => Secret::from(defaults::hashed_password()),
+                (Some(_), Some(_)) => {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_USER,
+                        "root password and password_hash are mutually exclusive"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+        (None, None) => Secret::from(defaults::hashed_password()),
+        (Some(_), Some(_)) => {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                "rootpasswd and root_account are mutually exclusive"
+                    .to_string(),
+            ));
+        }
+    };
+
+    users::chpasswd("root", hash.expose(), install_location)
+}
+
+// Writes localhost entries, the hostname mapping on 127.0.1.1,
+// and extra host entries from manifest
+fn fmt_hosts(manifest: &Manifest) -> String {
+    let hostname = manifest.hostname.as_deref().unwrap_or(defaults::HOSTNAME);
+    let names = match manifest.fqdn() {
+        Some(fqdn) => format!("{fqdn} {hostname}"),
+        None => hostname.to_string(),
+    };
+
+    let mut lines = vec![
+        "127.0.0.1\tlocalhost".to_string(),
+        "::1\t\tlocalhost".to_string(),
+        format!("127.0.1.1\t{names}"),
+    ];
 
-    let cmd = format!("echo 'username:{password}' | chpasswd -e");
+    for host in manifest.hosts.iter().flatten() {
+        lines.push(format!("{}\t{}", host.ip, host.names.join(" ")));
+    }
+
+    let mut s = lines.join("\n");
+    s.push('\n');
+
+    s
+}
+
+#[test]
+fn test_fmt_hosts() {
+    use crate::ali::ManifestHost;
+
+    let yaml = include_str!("../examples/uefi-root-on-lvm.yaml");
+    let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+    manifest.hostname = Some("foo".to_string());
+    manifest.fqdn = None;
+    manifest.domain = None;
+    manifest.hosts = None;
+
+    assert_eq!(
+        "127.0.0.1\tlocalhost\n::1\t\tlocalhost\n127.0.1.1\tfoo\n",
+        fmt_hosts(&manifest),
+    );
+
+    manifest.domain = Some("example.com".to_string());
+    manifest.hosts = Some(vec![ManifestHost {
+        ip: "10.0.0.2".to_string(),
+        names: vec!["bar.example.com".to_string(), "bar".to_string()],
+    }]);
 
-    shell::arch_chroot(install_location, &cmd)
+    assert_eq!(
+        "127.0.0.1\tlocalhost\n::1\t\tlocalhost\n127.0.1.1\tfoo.example.com foo\n10.0.0.2\tbar.example.com bar\n",
+        fmt_hosts(&manifest),
+    );
 }
 
-#[inline(always)]
-fn cmd_genfstab_uuid(install_location: &str) -> String {
-    format!("genfstab -U {install_location} >> {install_location}/etc/fstab")
+#[test]
+fn test_root_password() {
+    use std::rc::Rc;
+
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let yaml = include_str!("../examples/uefi-root-on-lvm.yaml");
+    let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+    manifest.rootpasswd = None;
+    manifest.root_account = Some(ManifestRoot {
+        lock: true,
+        ..Default::default()
+    });
+
+    let mock = Rc::new(Mock::default());
+    executor::set_executor(mock.clone());
+
+    root_password(&manifest, "/alitarget").expect("failed to lock root");
+
+    manifest.root_account = Some(ManifestRoot {
+        password: Some(Secret::from("archalirs")),
+        ..Default::default()
+    });
+
+    root_password(&manifest, "/alitarget")
+        .expect("failed to set root password");
+
+    manifest.rootpasswd = Some(Secret::from("$6$salt$hash"));
+    assert!(root_password(&manifest, "/alitarget").is_err());
+
+    assert_eq!(
+        vec![
+            "arch-chroot /alitarget passwd --lock root",
+            "arch-chroot /alitarget chpasswd -e < <stdin>",
+        ],
+        mock.records(),
+    );
 }
@@ -0,0 +1,118 @@
+use crate::ali::{
+    validation,
+    Manifest,
+    ManifestUnits,
+};
+use crate::errors::AliError;
+use crate::types::action::ActionChrootAli;
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::map_err::map_err_chroot_ali;
+
+/// Enables, disables, and masks systemd units defined in manifest
+/// inside chroot. Unit files are checked before each `systemctl` call,
+/// so that typos are reported with the unit name.
+pub fn apply_services(
+    manifest: &Manifest,
+    location: &str,
+) -> Result<Vec<ActionChrootAli>, AliError> {
+    let mut actions = Vec::new();
+
+    let services = match manifest.services {
+        Some(ref services) => services,
+        None => return Ok(actions),
+    };
+
+    apply_units(&services.system, false, location, &mut actions)?;
+    apply_units(&services.user, true, location, &mut actions)?;
+
+    Ok(actions)
+}
+
+fn apply_units(
+    units: &ManifestUnits,
+    global: bool,
+    location: &str,
+    actions: &mut Vec<ActionChrootAli>,
+) -> Result<(), AliError> {
+    let enables = units.enable.iter().map(|unit| {
+        ActionChrootAli::EnableUnit {
+            unit: unit.clone(),
+            global,
+        }
+    });
+
+    let disables = units.disable.iter().map(|unit| {
+        ActionChrootAli::DisableUnit {
+            unit: unit.clone(),
+            global,
+        }
+    });
+
+    let masks = units.mask.iter().map(|unit| {
+        ActionChrootAli::MaskUnit {
+            unit: unit.clone(),
+            global,
+        }
+    });
+
+    for action in enables.chain(disables).chain(masks) {
+        if let Err(err) = systemctl(&action, location) {
+            return Err(map_err_chroot_ali(err, action, actions.clone()));
+        }
+
+        actions.push(journal::done(action));
+    }
+
+    Ok(())
+}
+
+fn systemctl(action: &ActionChrootAli, location: &str) -> Result<(), AliError> {
+    let (verb, unit, global) = match action {
+        ActionChrootAli::EnableUnit { unit, global } => {
+            ("enable", unit, global)
+        }
+        ActionChrootAli::DisableUnit { unit, global } => {
+            ("disable", unit, global)
+        }
+        ActionChrootAli::MaskUnit { unit, global } => ("mask", unit, global),
+        _ => {
+            return Err(AliError::AliRsBug(format!(
+                "unexpected systemctl action {action:?}"
+            )))
+        }
+    };
+
+    // Masking does not require unit files
+    if verb != "mask" {
+        validation::unit_installed(unit, *global, location)?;
+    }
+
+    shell::arch_chroot(location, &cmd_systemctl(verb, unit, *global))
+}
+
+fn cmd_systemctl<'a>(
+    verb: &'a str,
+    unit: &'a str,
+    global: bool,
+) -> Vec<&'a str> {
+    match global {
+        true => vec!["systemctl", "--global", verb, unit],
+        false => vec!["systemctl", verb, unit],
+    }
+}
+
+#[test]
+fn test_cmd_systemctl() {
+    assert_eq!(
+        vec!["systemctl", "enable", "sshd.service"],
+        cmd_systemctl("enable", "sshd.service", false)
+    );
+    assert_eq!(
+        vec!["systemctl", "--global", "mask", "pipewire.socket"],
+        cmd_systemctl("mask", "pipewire.socket", true)
+    );
+}
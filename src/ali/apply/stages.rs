@@ -1,27 +1,48 @@
 use std::collections::HashSet;
 
+use colored::Colorize;
+
+use super::map_err::{
+    map_err_bootstrap,
+    map_err_chroot_ali,
+    map_err_postinstall_user,
+};
 use super::{
     archchroot,
+    bootloader,
     bootstrap,
     disks,
     dm,
+    files,
     fs,
+    keyring,
+    network,
+    pacman,
     routines,
+    services,
+    uki,
+    users,
 };
+use crate::ali::cmdline::KernelCmdline;
 use crate::ali::{
     Manifest,
     ManifestFs,
     ManifestMountpoint,
+    PhaseAfter,
 };
 use crate::errors::AliError;
 use crate::hooks;
 use crate::types::action::{
     ActionBootstrap,
+    ActionChrootAli,
     ActionMountpoints,
     ActionPostInstallUser,
 };
 use crate::types::stage::StageActions;
-use crate::utils::shell;
+use crate::utils::{
+    journal,
+    shell,
+};
 
 /// Prepare mountpoints for the new system on live system
 pub fn mountpoints(
@@ -44,7 +65,7 @@ pub fn mountpoints(
     // Create rootfs
     let rootfs: ManifestFs = manifest.rootfs.clone().into();
     let action_create_rootfs = fs::create_filesystem(&rootfs)?;
-    stages.mountpoints.push(action_create_rootfs);
+    stages.mountpoints.push(journal::done(action_create_rootfs));
 
     // Create other filesystems
     if let Some(filesystems) = &manifest.filesystems {
@@ -54,12 +75,14 @@ pub fn mountpoints(
 
     // mkdir rootfs chroot mount
     shell::exec("mkdir", &["-p", root_location])?;
-    stages.mountpoints.push(ActionMountpoints::MkdirRootFs);
+    stages
+        .mountpoints
+        .push(journal::done(ActionMountpoints::MkdirRootFs));
 
     // Mount rootfs
     let mnt_root: ManifestMountpoint = manifest.rootfs.clone().into();
     let action_mnt_rootfs = fs::mount_filesystem(&mnt_root, root_location)?;
-    stages.mountpoints.push(action_mnt_rootfs);
+    stages.mountpoints.push(journal::done(action_mnt_rootfs));
 
     // Mount other filesystems to /{DEFAULT_CHROOT_LOC}
     if let Some(mounts) = &manifest.mountpoints {
@@ -74,8 +97,8 @@ pub fn mountpoints(
 
         // mkdir -p /{DEFAULT_CHROOT_LOC}/{mkdir_path}
         for (dir, action_mkdir) in mountpoints {
-            shell::exec("mkdir", &[&dir])?;
-            stages.mountpoints.push(action_mkdir);
+            shell::exec("mkdir", &["-p", &format!("{root_location}{dir}")])?;
+            stages.mountpoints.push(journal::done(action_mkdir));
         }
 
         // Mount other filesystems under /{DEFAULT_CHROOT_LOC}
@@ -98,10 +121,108 @@ pub fn bootstrap(
         packages.extend(pacstraps);
     }
 
+    // Write pacman.conf for pacstrap -C on the live system
+    let mut pacman_conf = None;
+    if let Some(ref conf) = manifest.pacman {
+        let actions_conf = pacman::write_host_conf(conf)?;
+        stages.bootstrap.extend(actions_conf);
+
+        pacman_conf = Some(pacman::HOST_PACMAN_CONF);
+    }
+
+    // Prepare live system keyring before pacstrap
+    if let Some(ref keyring) = manifest.keyring {
+        let actions_keyring = keyring::prepare_keyring(keyring, pacman_conf)?;
+        stages.bootstrap.extend(actions_keyring);
+    }
+
+    // Bind-mount host package cache into install_location
+    if let Some(ref conf) = manifest.pacman {
+        let actions_bind = pacman::bind_cache(conf, install_location)?;
+        stages.bootstrap.extend(actions_bind);
+    }
+
     // Install packages (manifest.pacstraps) to install_location
-    let action_pacstrap = ActionBootstrap::InstallPackages { packages };
-    bootstrap::pacstrap_to_location(&manifest.pacstraps, install_location)?;
-    stages.bootstrap.push(action_pacstrap);
+    let action_pacstrap = ActionBootstrap::InstallPackages {
+        packages: packages.clone(),
+    };
+    let result = match bootstrap::pacstrap_to_location(
+        &packages,
+        install_location,
+        pacman_conf,
+        manifest.pacstrap_opts.as_ref(),
+    ) {
+        Ok(()) => {
+            stages.bootstrap.push(journal::done(action_pacstrap));
+            Ok(())
+        }
+        Err(err) => Err(map_err_bootstrap(err, action_pacstrap, vec![])),
+    };
+
+    // Always unmount package cache, even if pacstrap failed,
+    // in which case pacstrap error is returned instead of umount error
+    if let Some(ref conf) = manifest.pacman {
+        match pacman::unbind_cache(conf, install_location) {
+            Ok(actions_unbind) => stages.bootstrap.extend(actions_unbind),
+            Err(err) if result.is_err() => {
+                eprintln!(
+                    "{}",
+                    format!("WARN: failed to unmount package cache: {err}")
+                        .yellow()
+                );
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    result?;
+
+    // Write the same pacman.conf and mirrorlist into the target
+    if let Some(ref conf) = manifest.pacman {
+        let actions_conf = pacman::write_target_conf(conf, install_location)?;
+        stages.bootstrap.extend(actions_conf);
+    }
+
+    // Import extra keys into the fresh target keyring
+    if let Some(ref keyring) = manifest.keyring {
+        let actions_keys =
+            keyring::import_target_keys(keyring, install_location)?;
+        stages.bootstrap.extend(actions_keys);
+    }
+
+    // Install package phases in order with pacman in arch-chroot
+    if let Some(ref opts) = manifest.pacstrap_opts {
+        let phases = opts
+            .phases
+            .iter()
+            .filter(|phase| phase.after == PhaseAfter::Pacstrap);
+
+        for phase in phases {
+            let action_phase = ActionBootstrap::InstallPhase {
+                phase: phase.name.clone(),
+                packages: phase.packages.clone(),
+            };
+
+            if let Err(err) = bootstrap::install_phase(
+                phase,
+                install_location,
+                opts.interactive,
+            ) {
+                return Err(map_err_bootstrap(err, action_phase, vec![]));
+            }
+
+            stages.bootstrap.push(journal::done(action_phase));
+        }
+    }
+
+    // Install local package files with pacman -U in arch-chroot
+    if let Some(ref local_packages) = manifest.local_packages {
+        let action_local = bootstrap::install_local_packages(
+            local_packages,
+            install_location,
+        )?;
+        stages.bootstrap.push(journal::done(action_local));
+    }
 
     Ok(())
 }
@@ -115,6 +236,10 @@ pub fn routines(
     let actions_routine = routines::ali_routines(manifest, install_location)?;
     stages.routines.extend(actions_routine);
 
+    // Write network configuration files outside of arch-chroot
+    let actions_network = network::write_network(manifest, install_location)?;
+    stages.routines.extend(actions_network);
+
     Ok(())
 }
 
@@ -129,6 +254,67 @@ pub fn chroot_ali(
 
     stages.chroot_ali.extend(actions_archchroot);
 
+    // Create users and groups in arch-chroot
+    let actions_users = users::create_users(manifest, install_location)?;
+    stages.chroot_ali.extend(actions_users);
+
+    // Write manifest files, after users were created for chown
+    let actions_files = files::write_files(manifest, install_location)?;
+    stages.chroot_ali.extend(actions_files);
+
+    // Install package phases that run after manifest files
+    if let Some(ref opts) = manifest.pacstrap_opts {
+        let phases = opts
+            .phases
+            .iter()
+            .filter(|phase| phase.after == PhaseAfter::Files);
+
+        for phase in phases {
+            let action_phase = ActionChrootAli::InstallPhase {
+                phase: phase.name.clone(),
+                packages: phase.packages.clone(),
+            };
+
+            if let Err(err) = bootstrap::install_phase(
+                phase,
+                install_location,
+                opts.interactive,
+            ) {
+                return Err(map_err_chroot_ali(err, action_phase, vec![]));
+            }
+
+            stages.chroot_ali.push(journal::done(action_phase));
+        }
+    }
+
+    // Enable network backend services in arch-chroot
+    let actions_network = network::enable_network(manifest, install_location)?;
+    stages.chroot_ali.extend(actions_network);
+
+    // Enable, disable, and mask systemd units in arch-chroot
+    let actions_services =
+        services::apply_services(manifest, install_location)?;
+    stages.chroot_ali.extend(actions_services);
+
+    Ok(())
+}
+
+/// Build UKIs and install bootloader defined in manifest,
+/// with kernel command line computed from the storage stack.
+pub fn bootloader(
+    manifest: &Manifest,
+    install_location: &str,
+    cmdline: &KernelCmdline,
+    stages: &mut StageActions,
+) -> Result<(), AliError> {
+    let actions_uki = uki::build_uki(manifest, install_location, cmdline)?;
+    stages.bootloader.extend(actions_uki);
+
+    let actions_bootloader =
+        bootloader::install_bootloader(manifest, install_location, cmdline)?;
+
+    stages.bootloader.extend(actions_bootloader);
+
     Ok(())
 }
 
@@ -166,18 +352,26 @@ pub fn postinstall_user(
 
             stages
                 .postinstall_user
-                .push(ActionPostInstallUser::Hook(action_hook));
+                .push(journal::done(ActionPostInstallUser::Hook(action_hook)));
 
             continue;
         }
 
         // Apply manifest.postinstall with sh -c 'cmd'
-        shell::sh_c(cmd)?;
-
         let action_postinstall_cmd =
             ActionPostInstallUser::UserPostInstallCmd(cmd.clone());
 
-        stages.postinstall_user.push(action_postinstall_cmd);
+        if let Err(err) = shell::sh_c(cmd) {
+            return Err(map_err_postinstall_user(
+                err,
+                action_postinstall_cmd,
+                stages.postinstall_user.clone(),
+            ));
+        }
+
+        stages
+            .postinstall_user
+            .push(journal::done(action_postinstall_cmd));
     }
 
     Ok(())
@@ -0,0 +1,208 @@
+use crate::ali::cmdline::KernelCmdline;
+use crate::ali::{
+    Manifest,
+    ManifestUki,
+    ManifestUkiSign,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::hooks;
+use crate::types::action::ActionBootloader;
+use crate::utils::fs::{
+    file_exists,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::bootloader::DEFAULT_KERNEL;
+use super::map_err::map_err_bootloader;
+
+// Key directories of sbctl, new and legacy, relative to the new root
+const SBCTL_KEYS: [&str; 2] =
+    ["/var/lib/sbctl/keys", "/usr/share/secureboot/keys"];
+
+/// Configures mkinitcpio to build (and optionally sign)
+/// UKIs into `{esp}/EFI/Linux`, with kernel command line
+/// embedded from `/etc/kernel/cmdline`.
+pub fn build_uki(
+    manifest: &Manifest,
+    location: &str,
+    cmdline: &KernelCmdline,
+) -> Result<Vec<ActionBootloader>, AliError> {
+    let uki = match manifest.uki {
+        Some(ref uki) => uki,
+        None => return Ok(Vec::new()),
+    };
+
+    let esp = manifest.esp().map(|mnt| mnt.dest.as_str()).ok_or(
+        AliError::BadManifest(
+            &codes::E_VAL_BOOTLOADER,
+            "uki: no ESP found in mountpoints".to_string(),
+        ),
+    )?;
+
+    apply_uki(uki, esp, location, cmdline)
+}
+
+fn apply_uki(
+    uki: &ManifestUki,
+    esp: &str,
+    location: &str,
+    cmdline: &KernelCmdline,
+) -> Result<Vec<ActionBootloader>, AliError> {
+    let mut actions = Vec::new();
+    let kernel = uki.kernel.as_deref().unwrap_or(DEFAULT_KERNEL);
+
+    let kernel_cmdline = format!("{location}/etc/kernel/cmdline");
+    let action_cmdline =
+        ActionBootloader::WriteKernelCmdline(cmdline.to_string());
+    if let Err(err) = write_file(&kernel_cmdline, format!("{cmdline}\n")) {
+        return Err(map_err_bootloader(err, action_cmdline, actions));
+    }
+    actions.push(journal::done(action_cmdline));
+
+    let preset = format!("{location}/etc/mkinitcpio.d/{kernel}.preset");
+    let action_preset = ActionBootloader::WriteUkiPreset(preset.clone());
+    let content = hooks::fmt_uki_preset(kernel, esp);
+    if let Err(err) = write_file(&preset, content) {
+        return Err(map_err_bootloader(err, action_preset, actions));
+    }
+    actions.push(journal::done(action_preset));
+
+    let action_build = ActionBootloader::BuildUki {
+        kernel: kernel.to_string(),
+    };
+    let result =
+        shell::exec("mkdir", &["-p", &format!("{location}{esp}/EFI/Linux")])
+            .and_then(|_| {
+                shell::arch_chroot(location, &["mkinitcpio", "-p", kernel])
+            });
+
+    if let Err(err) = result {
+        return Err(map_err_bootloader(err, action_build, actions));
+    }
+    actions.push(journal::done(action_build));
+
+    let sign = match uki.sign {
+        Some(ref sign) => sign,
+        None => return Ok(actions),
+    };
+
+    // A fresh target has no sbctl keys to sign with
+    if *sign == ManifestUkiSign::Sbctl && !has_sbctl_keys(location) {
+        let action_keys = ActionBootloader::CreateSbctlKeys;
+        if let Err(err) =
+            shell::arch_chroot(location, &["sbctl", "create-keys"])
+        {
+            return Err(map_err_bootloader(err, action_keys, actions));
+        }
+        actions.push(journal::done(action_keys));
+    }
+
+    // Sign both default and fallback images
+    for image in uki_images(kernel, esp) {
+        let action_sign = ActionBootloader::SignUki(image.clone());
+        let result = match sign {
+            ManifestUkiSign::Sbsign { key, cert } => {
+                let image = format!("{location}{image}");
+                shell::exec(
+                    "sbsign",
+                    &["--key", key, "--cert", cert, "--output", &image, &image],
+                )
+            }
+            ManifestUkiSign::Sbctl => {
+                shell::arch_chroot(location, &["sbctl", "sign", "-s", &image])
+            }
+        };
+
+        if let Err(err) = result {
+            return Err(map_err_bootloader(err, action_sign, actions));
+        }
+        actions.push(journal::done(action_sign));
+    }
+
+    Ok(actions)
+}
+
+fn has_sbctl_keys(location: &str) -> bool {
+    SBCTL_KEYS
+        .iter()
+        .any(|keys| file_exists(format!("{location}{keys}")))
+}
+
+// Paths of UKIs built by the preset, relative to the new root
+fn uki_images(kernel: &str, esp: &str) -> [String; 2] {
+    [
+        format!("{esp}/EFI/Linux/arch-{kernel}.efi"),
+        format!("{esp}/EFI/Linux/arch-{kernel}-fallback.efi"),
+    ]
+}
+
+#[test]
+fn test_uki_preset() {
+    let preset = hooks::fmt_uki_preset("linux-lts", "/efi");
+
+    for image in uki_images("linux-lts", "/efi") {
+        assert!(preset.contains(&format!("_uki=\"{image}\"")));
+    }
+
+    assert!(preset.contains("ALL_kver=\"/boot/vmlinuz-linux-lts\""));
+}
+
+#[test]
+fn test_sign_uki() {
+    use std::rc::Rc;
+
+    use crate::utils::executor::{
+        self,
+        Mock,
+    };
+
+    let tests = vec![
+        (
+            ManifestUkiSign::Sbctl,
+            vec![
+                "arch-chroot /alitarget sbctl create-keys",
+                "arch-chroot /alitarget sbctl sign -s /efi/EFI/Linux/arch-linux.efi",
+                "arch-chroot /alitarget sbctl sign -s /efi/EFI/Linux/arch-linux-fallback.efi",
+            ],
+        ),
+        (
+            ManifestUkiSign::Sbsign {
+                key: "/root/keys/db.key".to_string(),
+                cert: "/root/keys/db.crt".to_string(),
+            },
+            vec![
+                "sbsign --key /root/keys/db.key --cert /root/keys/db.crt --output /alitarget/efi/EFI/Linux/arch-linux.efi /alitarget/efi/EFI/Linux/arch-linux.efi",
+                "sbsign --key /root/keys/db.key --cert /root/keys/db.crt --output /alitarget/efi/EFI/Linux/arch-linux-fallback.efi /alitarget/efi/EFI/Linux/arch-linux-fallback.efi",
+            ],
+        ),
+    ];
+
+    for (sign, expected) in tests {
+        let uki = ManifestUki {
+            kernel: None,
+            sign: Some(sign),
+        };
+
+        let mock = Rc::new(Mock::default());
+        executor::set_executor(mock.clone());
+
+        apply_uki(&uki, "/efi", "/alitarget", &KernelCmdline::default())
+            .expect("failed to apply uki");
+
+        let signing: Vec<String> = mock
+            .records()
+            .into_iter()
+            .skip_while(|r| !r.contains("mkinitcpio -p"))
+            .skip(1)
+            .collect();
+
+        assert_eq!(expected, signing);
+    }
+}
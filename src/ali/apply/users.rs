@@ -0,0 +1,325 @@
+use std::collections::HashSet;
+
+use crate::ali::{
+    Manifest,
+    ManifestHashScheme,
+    ManifestUser,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::types::action::ActionChrootAli;
+use crate::types::secret::Secret;
+use crate::utils::fs::{
+    set_mode,
+    write_file,
+};
+use crate::utils::{
+    journal,
+    shell,
+};
+
+use super::map_err::map_err_chroot_ali;
+
+const GROUP_WHEEL: &str = "wheel";
+const SUDOERS_WHEEL: &str = "/etc/sudoers.d/00-ali-rs-wheel";
+
+/// Creates groups and users defined in manifest inside chroot,
+/// and configures their passwords, SSH keys, and sudo access.
+pub fn create_users(
+    manifest: &Manifest,
+    location: &str,
+) -> Result<Vec<ActionChrootAli>, AliError> {
+    let mut actions = Vec::new();
+
+    let users = match manifest.users {
+        Some(ref users) => users,
+        None => return Ok(actions),
+    };
+
+    // Create all supplementary groups first
+    let mut groups = Vec::<&str>::new();
+    let mut dups = HashSet::new();
+    for user in users {
+        for group in user_groups(user) {
+            if dups.insert(group) {
+                groups.push(group);
+            }
+        }
+    }
+
+    for group in groups {
+        let action_group = ActionChrootAli::CreateGroup(group.to_string());
+
+        // -f exits successfully if group already exists
+        let cmd_groupadd = ["groupadd", "-f", group];
+        if let Err(err) = shell::arch_chroot(location, &cmd_groupadd) {
+            return Err(map_err_chroot_ali(err, action_group, actions));
+        }
+
+        actions.push(journal::done(action_group));
+    }
+
+    if users.iter().any(|user| user.sudo) {
+        let action_sudo = ActionChrootAli::EnableSudoWheel;
+        if let Err(err) = sudo_wheel(location) {
+            return Err(map_err_chroot_ali(err, action_sudo, actions));
+        }
+
+        actions.push(journal::done(action_sudo));
+    }
+
+    for user in users {
+        create_user(user, location, &mut actions)?;
+    }
+
+    Ok(actions)
+}
+
+fn create_user(
+    user: &ManifestUser,
+    location: &str,
+    actions: &mut Vec<ActionChrootAli>,
+) -> Result<(), AliError> {
+    let username = &user.username;
+
+    let action_user = ActionChrootAli::CreateUser(username.clone());
+    let cmd_useradd = cmd_useradd(user);
+    let cmd_useradd: Vec<&str> =
+        cmd_useradd.iter().map(String::as_str).collect();
+    if let Err(err) = shell::arch_chroot(location, &cmd_useradd) {
+        return Err(map_err_chroot_ali(err, action_user, actions.clone()));
+    }
+    actions.push(journal::done(action_user));
+
+    let action_passwd = ActionChrootAli::SetUserPassword(username.clone());
+    let result = hashed_password(user).and_then(|hash| {
+        match hash {
+            Some(hash) => chpasswd(username, hash.expose(), location),
+            None => Ok(()),
+        }
+    });
+    if let Err(err) = result {
+        return Err(map_err_chroot_ali(err, action_passwd, actions.clone()));
+    }
+    if user.password.is_some() || user.password_hash.is_some() {
+        actions.push(journal::done(action_passwd));
+    }
+
+    if let Some(ref keys) = user.ssh_keys {
+        let action_keys =
+            ActionChrootAli::WriteAuthorizedKeys(username.clone());
+
+        let result = match user.home() {
+            Some(home) => authorized_keys(username, &home, keys, location),
+            None => {
+                Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!(
+                        "user {username}: ssh_keys requires home directory"
+                    ),
+                ))
+            }
+        };
+
+        if let Err(err) = result {
+            return Err(map_err_chroot_ali(err, action_keys, actions.clone()));
+        }
+
+        actions.push(journal::done(action_keys));
+    }
+
+    Ok(())
+}
+
+fn user_groups(user: &ManifestUser) -> Vec<&str> {
+    let mut groups: Vec<&str> = user
+        .groups
+        .iter()
+        .flatten()
+        .map(|group| group.as_str())
+        .collect();
+
+    if user.sudo && !groups.contains(&GROUP_WHEEL) {
+        groups.push(GROUP_WHEEL);
+    }
+
+    groups
+}
+
+fn cmd_useradd(user: &ManifestUser) -> Vec<String> {
+    let mut cmd = vec!["useradd".to_string()];
+
+    if user.create_home.unwrap_or(true) {
+        cmd.push("-m".to_string());
+    }
+
+    if let Some(ref home) = user.home_dir {
+        cmd.extend(["-d".to_string(), home.clone()]);
+    }
+
+    if let Some(uid) = user.uid {
+        cmd.extend(["-u".to_string(), uid.to_string()]);
+    }
+
+    let groups = user_groups(user);
+    if !groups.is_empty() {
+        cmd.extend(["-G".to_string(), groups.join(",")]);
+    }
+
+    if let Some(ref shell) = user.shell {
+        cmd.extend(["-s".to_string(), shell.clone()]);
+    }
+
+    cmd.push(user.username.clone());
+    cmd
+}
+
+// Hashes plaintext password with SHA-512 crypt,
+// or returns the pre-hashed password as-is.
+fn hashed_password(user: &ManifestUser) -> Result<Option<Secret>, AliError> {
+    match (&user.password, &user.password_hash) {
+        (Some(plain), None) => {
+            hash_password(&user.username, plain, ManifestHashScheme::Sha512)
+                .map(Some)
+        }
+        (None, hash) => Ok(hash.clone()),
+        (Some(_), Some(_)) => {
+            let username = &user.username;
+
+            Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!(
+                    "user {username}: password and password_hash are mutually exclusive"
+                ),
+            ))
+        }
+    }
+}
+
+/// Hashes plaintext password for `username` with `scheme`
+///
+/// Yescrypt is not supported by pwhash, so yescrypt hashes
+/// must be given pre-hashed in password_hash.
+pub(super) fn hash_password(
+    username: &str,
+    plain: &Secret,
+    scheme: ManifestHashScheme,
+) -> Result<Secret, AliError> {
+    let result = match scheme {
+        ManifestHashScheme::Sha512 => {
+            pwhash::sha512_crypt::hash(plain.expose())
+        }
+        ManifestHashScheme::Bcrypt => pwhash::bcrypt::hash(plain.expose()),
+        ManifestHashScheme::Yescrypt => {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!(
+                    "user {username}: yescrypt cannot hash plaintext password, use password_hash instead"
+                ),
+            ));
+        }
+    };
+
+    result.map(Secret::from).map_err(|err| {
+        AliError::AliRsBug(format!(
+            "failed to hash password for user {username}: {err}"
+        ))
+    })
+}
+
+// Writes `username:hash` to stdin of chpasswd inside chroot,
+// so that the hash is neither interpreted by any shell nor shown in argv
+pub(super) fn chpasswd(
+    username: &str,
+    hash: &str,
+    location: &str,
+) -> Result<(), AliError> {
+    let entry = format!("{username}:{hash}\n");
+
+    shell::exec_with_stdin(
+        "arch-chroot",
+        &[location, "chpasswd", "-e"],
+        entry.as_bytes(),
+    )
+}
+
+fn authorized_keys(
+    username: &str,
+    home: &str,
+    keys: &[String],
+    location: &str,
+) -> Result<(), AliError> {
+    let ssh_dir = format!("{home}/.ssh");
+    let authorized_keys = format!("{location}{ssh_dir}/authorized_keys");
+
+    write_file(&authorized_keys, format!("{}\n", keys.join("\n")))?;
+    set_mode(&format!("{location}{ssh_dir}"), 0o700)?;
+    set_mode(&authorized_keys, 0o600)?;
+
+    let owner = format!("{username}:");
+    shell::arch_chroot(location, &["chown", "-R", &owner, &ssh_dir])
+}
+
+fn sudo_wheel(location: &str) -> Result<(), AliError> {
+    let sudoers = format!("{location}{SUDOERS_WHEEL}");
+
+    write_file(&sudoers, "%wheel ALL=(ALL:ALL) ALL\n")?;
+    set_mode(&sudoers, 0o440)
+}
+
+#[test]
+fn test_cmd_useradd() {
+    let user = ManifestUser {
+        username: "foo".to_string(),
+        uid: Some(1500),
+        groups: Some(vec!["video".to_string(), "audio".to_string()]),
+        shell: Some("/bin/zsh".to_string()),
+        password: None,
+        password_hash: None,
+        create_home: None,
+        home_dir: None,
+        ssh_keys: None,
+        sudo: true,
+    };
+
+    assert_eq!(
+        "useradd -m -u 1500 -G video,audio,wheel -s /bin/zsh foo",
+        cmd_useradd(&user).join(" ")
+    );
+
+    let user = ManifestUser {
+        username: "bar".to_string(),
+        uid: None,
+        groups: None,
+        shell: None,
+        password: Some(Secret::from("pass")),
+        password_hash: None,
+        create_home: Some(false),
+        home_dir: None,
+        ssh_keys: None,
+        sudo: false,
+    };
+
+    assert_eq!(vec!["useradd", "bar"], cmd_useradd(&user));
+
+    let home = ManifestUser {
+        create_home: None,
+        home_dir: Some("/srv/bar".to_string()),
+        ..user.clone()
+    };
+    assert_eq!("useradd -m -d /srv/bar bar", cmd_useradd(&home).join(" "));
+    assert_eq!(Some("/srv/bar".to_string()), home.home());
+    assert_eq!(None, user.home());
+
+    let hash = hashed_password(&user).unwrap().unwrap();
+    assert!(pwhash::unix::verify("pass", hash.expose()));
+
+    for scheme in [ManifestHashScheme::Sha512, ManifestHashScheme::Bcrypt] {
+        let hash = hash_password("root", &Secret::from("pass"), scheme)
+            .expect("failed to hash password");
+
+        assert!(pwhash::unix::verify("pass", hash.expose()));
+    }
+}
@@ -0,0 +1,265 @@
+//! Computes kernel command line from the manifest storage stack.
+//!
+//! The block device paths collected during validation are walked
+//! from the top-most device (rootfs or swap) down to its base,
+//! and each layer (LUKS, LVM) adds its own kernel parameters.
+
+use crate::ali::{
+    Initramfs,
+    Manifest,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::types::blockdev::{
+    BlockDevPaths,
+    BlockDevType,
+    DmType,
+};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KernelCmdline(Vec<String>);
+
+impl KernelCmdline {
+    pub fn params(&self) -> &[String] {
+        &self.0
+    }
+
+    fn push(&mut self, param: String) {
+        if !self.0.contains(&param) {
+            self.0.push(param);
+        }
+    }
+}
+
+impl std::fmt::Display for KernelCmdline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.join(" "))
+    }
+}
+
+/// Builds kernel parameters for booting `manifest.rootfs`,
+/// and resuming from the first swap if hibernation is enabled.
+///
+/// `uuid` maps a device path to its UUID, and is only called
+/// after the devices were created, e.g. with [`linux::blkid::uuid`].
+///
+/// [`linux::blkid::uuid`]: crate::linux::blkid::uuid
+pub fn kernel_cmdline<F>(
+    manifest: &Manifest,
+    block_devs: &BlockDevPaths,
+    uuid: F,
+) -> Result<KernelCmdline, AliError>
+where
+    F: Fn(&str) -> Result<String, AliError>,
+{
+    let conf = manifest.cmdline.clone().unwrap_or_default();
+    let mut cmdline = KernelCmdline::default();
+
+    let root = &manifest.rootfs.device;
+    stack_params(root, block_devs, &conf.initramfs, &uuid, &mut cmdline)?;
+    cmdline.push(format!("root=UUID={}", uuid(root)?));
+
+    if let Some(subvol) = btrfs_subvol(&manifest.rootfs.mnt_opts) {
+        cmdline.push(format!("rootflags=subvol={subvol}"));
+    }
+
+    if conf.hibernate {
+        let swap = manifest
+            .swap
+            .as_ref()
+            .and_then(|swaps| swaps.first())
+            .ok_or(AliError::BadManifest(
+                &codes::E_MAN_CMDLINE,
+                "cmdline: hibernation enabled but no swap found".to_string(),
+            ))?;
+
+        stack_params(swap, block_devs, &conf.initramfs, &uuid, &mut cmdline)?;
+        cmdline.push(format!("resume=UUID={}", uuid(swap)?));
+    }
+
+    // encrypt hook can only unlock 1 device
+    if conf.initramfs == Initramfs::Udev {
+        let crypts = cmdline
+            .params()
+            .iter()
+            .filter(|p| p.starts_with("cryptdevice="))
+            .count();
+
+        if crypts > 1 {
+            return Err(AliError::BadManifest(
+                &codes::E_MAN_CMDLINE,
+                format!(
+                    "cmdline: udev initramfs can only unlock 1 LUKS device, got {crypts}"
+                ),
+            ));
+        }
+    }
+
+    cmdline.push("rw".to_string());
+
+    if let Some(extra) = conf.extra {
+        for param in extra {
+            cmdline.push(param);
+        }
+    }
+
+    Ok(cmdline)
+}
+
+// Walks all paths leading to `device`, pushing LUKS and LVM parameters.
+// Devices not found in `block_devs` (e.g. plain existing partitions)
+// need no extra parameters.
+fn stack_params<F>(
+    device: &str,
+    block_devs: &BlockDevPaths,
+    initramfs: &Initramfs,
+    uuid: &F,
+    cmdline: &mut KernelCmdline,
+) -> Result<(), AliError>
+where
+    F: Fn(&str) -> Result<String, AliError>,
+{
+    let paths = block_devs.iter().filter(|path| {
+        path.back().is_some_and(|top| top.device.as_str() == device)
+    });
+
+    for path in paths {
+        let path: Vec<_> = path.iter().collect();
+
+        for (i, dev) in path.iter().enumerate() {
+            match dev.device_type {
+                BlockDevType::Dm(DmType::Luks) => {
+                    let base = i
+                        .checked_sub(1)
+                        .and_then(|j| path.get(j))
+                        .ok_or(AliError::AliRsBug(format!(
+                            "cmdline: luks {} has no base device",
+                            dev.device
+                        )))?;
+
+                    let name = dev
+                        .device
+                        .strip_prefix("/dev/mapper/")
+                        .unwrap_or(&dev.device);
+
+                    let base_uuid = uuid(&base.device)?;
+                    let param = match initramfs {
+                        Initramfs::Udev => {
+                            format!("cryptdevice=UUID={base_uuid}:{name}")
+                        }
+                        Initramfs::Systemd => {
+                            format!("rd.luks.name={base_uuid}={name}")
+                        }
+                    };
+
+                    cmdline.push(param);
+                }
+
+                BlockDevType::Dm(DmType::LvmVg) => {
+                    let vg =
+                        dev.device.strip_prefix("/dev/").unwrap_or(&dev.device);
+
+                    cmdline.push(format!("rd.lvm.vg={vg}"));
+                }
+
+                _ => continue,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn btrfs_subvol(mnt_opts: &Option<String>) -> Option<&str> {
+    mnt_opts
+        .as_ref()?
+        .split(',')
+        .find_map(|opt| opt.trim().strip_prefix("subvol="))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::LinkedList;
+
+    use super::*;
+    use crate::ali::ManifestCmdline;
+    use crate::types::blockdev::*;
+
+    fn fake_uuid(device: &str) -> Result<String, AliError> {
+        Ok(format!("uuid-{}", device.trim_start_matches("/dev/")))
+    }
+
+    #[test]
+    fn test_kernel_cmdline() {
+        let yaml = include_str!("./examples/uefi-root-on-lvm-on-luks.yaml");
+        let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+        let lvm_on_luks = |lv: &str| {
+            LinkedList::from([
+                BlockDev {
+                    device: "/dev/vda".into(),
+                    device_type: TYPE_DISK,
+                },
+                BlockDev {
+                    device: "/dev/vda2".into(),
+                    device_type: TYPE_PART,
+                },
+                BlockDev {
+                    device: "/dev/mapper/crypty".into(),
+                    device_type: TYPE_LUKS,
+                },
+                BlockDev {
+                    device: "/dev/mapper/crypty".into(),
+                    device_type: TYPE_PV,
+                },
+                BlockDev {
+                    device: "/dev/archvg".into(),
+                    device_type: TYPE_VG,
+                },
+                BlockDev {
+                    device: format!("/dev/archvg/{lv}"),
+                    device_type: TYPE_LV,
+                },
+            ])
+        };
+
+        let block_devs = vec![lvm_on_luks("swaplv"), lvm_on_luks("rootlv")];
+
+        let tests = vec![
+            (
+                ManifestCmdline::default(),
+                None,
+                "cryptdevice=UUID=uuid-vda2:crypty rd.lvm.vg=archvg root=UUID=uuid-archvg/rootlv rw",
+            ),
+            (
+                ManifestCmdline {
+                    initramfs: Initramfs::Systemd,
+                    hibernate: true,
+                    extra: Some(vec!["quiet".into()]),
+                },
+                Some("compress=zstd:3,subvol=@".to_string()),
+                "rd.luks.name=uuid-vda2=crypty rd.lvm.vg=archvg root=UUID=uuid-archvg/rootlv rootflags=subvol=@ resume=UUID=uuid-archvg/swaplv rw quiet",
+            ),
+        ];
+
+        for (conf, mnt_opts, expected) in tests {
+            manifest.cmdline = Some(conf);
+            manifest.rootfs.mnt_opts = mnt_opts;
+
+            let cmdline =
+                kernel_cmdline(&manifest, &block_devs, fake_uuid).unwrap();
+
+            assert_eq!(expected, cmdline.to_string());
+        }
+
+        // Plain rootfs not found in block_devs
+        manifest.cmdline = None;
+        manifest.rootfs.device = "/dev/sda1".into();
+        manifest.rootfs.mnt_opts = None;
+
+        let cmdline = kernel_cmdline(&manifest, &vec![], fake_uuid).unwrap();
+        assert_eq!("root=UUID=uuid-sda1 rw", cmdline.to_string());
+    }
+}
@@ -1,16 +1,27 @@
 pub mod apply;
+pub mod cmdline;
+pub mod packages;
+pub mod secrets;
 pub mod validation;
 
-use std::collections::HashSet;
+use std::collections::{
+    BTreeMap,
+    HashSet,
+};
 
 use serde::{
     Deserialize,
     Serialize,
 };
 
-use crate::errors::AliError;
+use crate::constants::defaults;
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::types::secret::Secret;
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
 pub struct Manifest {
     #[serde(alias = "location", alias = "install_location")]
     pub location: Option<String>,
@@ -18,6 +29,17 @@ pub struct Manifest {
     #[serde(alias = "name", alias = "host")]
     pub hostname: Option<String>,
 
+    // Domain name, used to form FQDN {hostname}.{domain}
+    #[serde(alias = "domainname", alias = "domain-name")]
+    pub domain: Option<String>,
+
+    // Fully qualified domain name, must start with {hostname}.
+    pub fqdn: Option<String>,
+
+    // Extra entries in /etc/hosts
+    #[serde(alias = "extra-hosts", alias = "extra_hosts", alias = "etc-hosts")]
+    pub hosts: Option<Vec<ManifestHost>>,
+
     #[serde(alias = "tz")]
     pub timezone: Option<String>,
 
@@ -45,19 +67,72 @@ pub struct Manifest {
     )]
     pub pacstraps: Option<HashSet<String>>,
 
+    // pacstrap(8) options and package phases installed after pacstraps
+    #[serde(alias = "pacstrap-opts", alias = "pacstrap_options")]
+    pub pacstrap_opts: Option<ManifestPacstrap>,
+
+    // Files with one package per line, merged into pacstraps
+    #[serde(alias = "package-lists", alias = "pacstrap-lists")]
+    pub package_lists: Option<Vec<String>>,
+
+    // Package groups, merged into pacstraps
+    #[serde(alias = "groups", alias = "package-groups")]
+    pub package_groups: Option<Vec<ManifestPackageGroup>>,
+
+    // Package files on the live system, installed with pacman -U in chroot
+    #[serde(alias = "local-packages")]
+    pub local_packages: Option<Vec<String>>,
+
     #[serde(
         alias = "password",
         alias = "passwd",
         alias = "root-password",
         alias = "root-passwd"
     )]
-    pub rootpasswd: Option<String>,
+    pub rootpasswd: Option<Secret>,
+
+    // Root account, mutually exclusive with rootpasswd.
+    // Key root is already an alias for rootfs.
+    #[serde(alias = "root-account")]
+    pub root_account: Option<ManifestRoot>,
 
     #[serde(alias = "arch-chroot")]
     pub chroot: Option<Vec<String>>,
 
     #[serde(alias = "post-install")]
     pub postinstall: Option<Vec<String>>,
+
+    #[serde(alias = "kernel-cmdline", alias = "kernel_cmdline")]
+    pub cmdline: Option<ManifestCmdline>,
+
+    #[serde(alias = "boot", alias = "boot-loader")]
+    pub bootloader: Option<ManifestBootloader>,
+
+    #[serde(alias = "unified-kernel-image")]
+    pub uki: Option<ManifestUki>,
+
+    #[serde(alias = "user")]
+    pub users: Option<Vec<ManifestUser>>,
+
+    #[serde(alias = "systemd", alias = "units")]
+    pub services: Option<ManifestServices>,
+
+    #[serde(alias = "locales")]
+    pub locale: Option<ManifestLocale>,
+
+    #[serde(alias = "networks", alias = "net")]
+    pub network: Option<ManifestNetwork>,
+
+    #[serde(alias = "file")]
+    pub files: Option<Vec<ManifestFile>>,
+
+    #[serde(alias = "pacman-conf", alias = "pacman_conf")]
+    pub pacman: Option<ManifestPacman>,
+
+    // Live system keyring preparation before pacstrap,
+    // and extra keys to import in the target after pacstrap
+    #[serde(alias = "pacman-keyring", alias = "pacman_keyring")]
+    pub keyring: Option<ManifestKeyring>,
 }
 
 impl Manifest {
@@ -65,8 +140,53 @@ impl Manifest {
     pub fn from_yaml(manifest_yaml: &str) -> Result<Self, AliError> {
         parse(manifest_yaml)
     }
+
+    /// Returns fully qualified domain name, either explicitly set
+    /// in `fqdn`, or formed from `hostname` and `domain`.
+    pub fn fqdn(&self) -> Option<String> {
+        if let Some(ref fqdn) = self.fqdn {
+            return Some(fqdn.clone());
+        }
+
+        let hostname = self.hostname.as_deref().unwrap_or(defaults::HOSTNAME);
+
+        self.domain
+            .as_ref()
+            .map(|domain| format!("{hostname}.{domain}"))
+    }
+
+    /// Returns mountpoint of EFI system partition,
+    /// either explicitly set in `bootloader`, or the first
+    /// vfat filesystem mounted on one of the usual ESP paths.
+    pub fn esp(&self) -> Option<&ManifestMountpoint> {
+        let mountpoints = self.mountpoints.as_ref()?;
+
+        let explicit = match self.bootloader {
+            Some(ManifestBootloader::SystemdBoot(ref b)) => b.esp.as_ref(),
+            Some(ManifestBootloader::Grub(ref b)) => b.esp.as_ref(),
+            None => None,
+        };
+
+        if let Some(esp) = explicit {
+            return mountpoints.iter().find(|m| &m.dest == esp);
+        }
+
+        let is_vfat = |device: &str| {
+            self.filesystems.as_ref().is_some_and(|filesystems| {
+                filesystems
+                    .iter()
+                    .any(|fs| fs.device == device && fs.fs_type == "vfat")
+            })
+        };
+
+        mountpoints.iter().find(|m| {
+            ESP_MOUNTPOINTS.contains(&m.dest.as_str()) && is_vfat(&m.device)
+        })
+    }
 }
 
+const ESP_MOUNTPOINTS: [&str; 3] = ["/boot", "/efi", "/boot/efi"];
+
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum PartitionTable {
     #[serde(rename = "gpt")]
@@ -114,7 +234,7 @@ pub struct ManifestMountpoint {
     pub mnt_opts: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ManifestRootFs {
     pub device: String,
 
@@ -134,9 +254,9 @@ pub struct ManifestLuks {
     pub name: String,
 
     // If passphrase is None, let cryptsetup prompt user for password,
-    // if it is Some(pass), pipe pass to cryptsetup
+    // if it is Some(pass), feed pass to cryptsetup via stdin
     #[serde(alias = "key")]
-    pub passphrase: Option<String>,
+    pub passphrase: Option<Secret>,
 }
 
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -169,6 +289,600 @@ pub enum Dm {
     Lvm(ManifestLvm),
 }
 
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestCmdline {
+    // Initramfs flavor decides how LUKS devices are unlocked at boot
+    #[serde(default)]
+    pub initramfs: Initramfs,
+
+    // If true, the first swap device is used as resume device
+    #[serde(default, alias = "resume")]
+    pub hibernate: bool,
+
+    // Extra kernel parameters appended as-is
+    #[serde(alias = "params", alias = "extra-params")]
+    pub extra: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub enum Initramfs {
+    // Busybox-based initramfs with `encrypt` mkinitcpio hook
+    #[default]
+    #[serde(rename = "udev", alias = "busybox")]
+    Udev,
+
+    // systemd-based initramfs with `sd-encrypt` mkinitcpio hook
+    #[serde(rename = "systemd")]
+    Systemd,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ManifestBootloader {
+    #[serde(
+        rename = "systemd-boot",
+        alias = "systemd_boot",
+        alias = "bootctl"
+    )]
+    SystemdBoot(ManifestSystemdBoot),
+
+    #[serde(rename = "grub")]
+    Grub(ManifestGrub),
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestSystemdBoot {
+    // ESP mountpoint, e.g. /boot. If None, ESP is detected from mountpoints
+    pub esp: Option<String>,
+
+    pub timeout: Option<u32>,
+
+    // Kernel package name, used in loader entry (e.g. linux-lts)
+    pub kernel: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestGrub {
+    #[serde(default)]
+    pub target: GrubTarget,
+
+    // ESP mountpoint for UEFI target. If None, ESP is detected from mountpoints
+    pub esp: Option<String>,
+
+    // Disk to install GRUB to for BIOS target, e.g. /dev/sda
+    pub device: Option<String>,
+
+    #[serde(alias = "bootloader-id", alias = "id")]
+    pub bootloader_id: Option<String>,
+
+    pub timeout: Option<u32>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub enum GrubTarget {
+    #[default]
+    #[serde(rename = "uefi", alias = "efi")]
+    Uefi,
+
+    #[serde(rename = "bios", alias = "mbr")]
+    Bios,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestUki {
+    // Kernel package name, used to find mkinitcpio preset (e.g. linux-lts)
+    pub kernel: Option<String>,
+
+    pub sign: Option<ManifestUkiSign>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(tag = "tool")]
+pub enum ManifestUkiSign {
+    // Signs UKI on live system with key and cert files
+    #[serde(rename = "sbsign")]
+    Sbsign { key: String, cert: String },
+
+    // Signs UKI in chroot with keys in target's sbctl database
+    #[serde(rename = "sbctl")]
+    Sbctl,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestUser {
+    #[serde(alias = "name")]
+    pub username: String,
+
+    pub uid: Option<u32>,
+
+    // Supplementary groups, created if missing
+    pub groups: Option<Vec<String>>,
+
+    pub shell: Option<String>,
+
+    // Plaintext password, hashed locally before use
+    pub password: Option<Secret>,
+
+    // Hashed password in crypt(3) format
+    #[serde(alias = "hashed-password", alias = "hashed_password")]
+    pub password_hash: Option<Secret>,
+
+    // Defaults to true
+    #[serde(alias = "create-home", alias = "home")]
+    pub create_home: Option<bool>,
+
+    // Home directory, defaults to /home/{username}
+    #[serde(alias = "home-dir")]
+    pub home_dir: Option<String>,
+
+    #[serde(alias = "ssh-keys", alias = "authorized_keys")]
+    pub ssh_keys: Option<Vec<String>>,
+
+    // Adds user to group wheel, and allows wheel to use sudo
+    #[serde(default)]
+    pub sudo: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestRoot {
+    // Plaintext password, hashed locally with `scheme`
+    pub password: Option<Secret>,
+
+    // Hashed password in crypt(3) format
+    #[serde(alias = "hashed-password", alias = "hashed_password")]
+    pub password_hash: Option<Secret>,
+
+    #[serde(default, alias = "hash", alias = "hash-scheme")]
+    pub scheme: ManifestHashScheme,
+
+    // Locks root account instead of setting any password
+    #[serde(default)]
+    pub lock: bool,
+}
+
+// Schemes for hashing plaintext passwords
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum ManifestHashScheme {
+    #[default]
+    #[serde(rename = "sha512", alias = "sha512-crypt")]
+    Sha512,
+
+    #[serde(rename = "bcrypt")]
+    Bcrypt,
+
+    // Only valid with pre-hashed password_hash
+    #[serde(rename = "yescrypt")]
+    Yescrypt,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestServices {
+    // System units, i.e. `systemctl <verb> <unit>`
+    #[serde(flatten)]
+    pub system: ManifestUnits,
+
+    // User units for all users, i.e. `systemctl --global <verb> <unit>`
+    #[serde(default, alias = "global")]
+    pub user: ManifestUnits,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestUnits {
+    #[serde(default)]
+    pub enable: Vec<String>,
+
+    #[serde(default)]
+    pub disable: Vec<String>,
+
+    #[serde(default)]
+    pub mask: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestLocale {
+    // Entries in /etc/locale.gen, e.g. "en_US.UTF-8 UTF-8" or "en_US.UTF-8"
+    #[serde(alias = "locale-gen", alias = "locale_gen", alias = "generate")]
+    pub gen: Option<Vec<String>>,
+
+    // LANG in /etc/locale.conf, defaults to the first locale in gen
+    pub lang: Option<String>,
+
+    // LC_* overrides in /etc/locale.conf, e.g. LC_TIME: en_GB.UTF-8
+    #[serde(alias = "overrides")]
+    pub lc: Option<BTreeMap<String, String>>,
+
+    // KEYMAP in /etc/vconsole.conf
+    pub keymap: Option<String>,
+
+    // FONT in /etc/vconsole.conf
+    #[serde(alias = "console-font", alias = "console_font")]
+    pub font: Option<String>,
+
+    // XkbLayout in /etc/X11/xorg.conf.d/00-keyboard.conf
+    #[serde(alias = "x11-layout", alias = "x11_keymap", alias = "x11-keymap")]
+    pub x11_layout: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestHost {
+    #[serde(alias = "address", alias = "addr")]
+    pub ip: String,
+
+    #[serde(alias = "hostnames", alias = "name")]
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestNetwork {
+    #[serde(default)]
+    pub backend: NetworkBackend,
+
+    #[serde(default, alias = "interface", alias = "ifaces")]
+    pub interfaces: Vec<ManifestInterface>,
+
+    #[serde(alias = "wireless", alias = "iwd")]
+    pub wifi: Option<ManifestWifi>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub enum NetworkBackend {
+    #[default]
+    #[serde(
+        rename = "networkd",
+        alias = "systemd-networkd",
+        alias = "systemd_networkd"
+    )]
+    Networkd,
+
+    #[serde(rename = "networkmanager", alias = "NetworkManager", alias = "nm")]
+    NetworkManager,
+
+    #[serde(rename = "netctl")]
+    Netctl,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestInterface {
+    #[serde(alias = "interface", alias = "ifname")]
+    pub name: String,
+
+    // Enables DHCP (and SLAAC for IPv6) on the interface
+    #[serde(default)]
+    pub dhcp: bool,
+
+    // Static IPv4 and IPv6 addresses in CIDR notation
+    #[serde(default, alias = "address")]
+    pub addresses: Vec<String>,
+
+    #[serde(default, alias = "gateway")]
+    pub gateways: Vec<String>,
+
+    #[serde(default)]
+    pub dns: Vec<String>,
+
+    #[serde(default, alias = "route")]
+    pub routes: Vec<ManifestRoute>,
+
+    // Creates a bond interface with name from slave interfaces
+    pub bond: Option<ManifestBond>,
+
+    // Creates a VLAN interface with name on top of parent interface
+    pub vlan: Option<ManifestVlan>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestWifi {
+    // Who configures IP addresses on wireless interfaces
+    #[serde(default)]
+    pub networking: WifiNetworking,
+
+    // Wireless interface matched by systemd-networkd, defaults to wl*
+    pub interface: Option<String>,
+
+    #[serde(default)]
+    pub networks: Vec<ManifestWifiNetwork>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub enum WifiNetworking {
+    // iwd with EnableNetworkConfiguration=true
+    #[default]
+    #[serde(rename = "iwd")]
+    Iwd,
+
+    // iwd only connects, and systemd-networkd runs DHCP
+    #[serde(rename = "networkd", alias = "systemd-networkd")]
+    Networkd,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestWifiNetwork {
+    pub ssid: String,
+
+    pub passphrase: Option<Secret>,
+
+    // 64 hex digits pre-shared key, e.g. from wpa_passphrase
+    #[serde(alias = "pre-shared-key", alias = "pre_shared_key")]
+    pub psk: Option<Secret>,
+
+    #[serde(default)]
+    pub hidden: bool,
+
+    // Defaults to true
+    #[serde(alias = "auto-connect", alias = "autoconnect")]
+    pub auto_connect: Option<bool>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestRoute {
+    // Destination in CIDR notation
+    #[serde(alias = "destination", alias = "dest")]
+    pub to: String,
+
+    #[serde(alias = "gateway")]
+    pub via: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestBond {
+    // Bonding mode, e.g. active-backup or 802.3ad
+    #[serde(default = "default_bond_mode")]
+    pub mode: String,
+
+    #[serde(alias = "members", alias = "interfaces")]
+    pub slaves: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestVlan {
+    #[serde(alias = "link")]
+    pub parent: String,
+
+    pub id: u16,
+}
+
+impl ManifestUser {
+    /// Returns home directory created by useradd,
+    /// or None if `create_home` is false.
+    pub fn home(&self) -> Option<String> {
+        if !self.create_home.unwrap_or(true) {
+            return None;
+        }
+
+        match self.home_dir {
+            Some(ref home) => Some(home.clone()),
+            None => Some(format!("/home/{}", self.username)),
+        }
+    }
+}
+
+impl ManifestLocale {
+    /// Returns locale.gen entries to generate,
+    /// falling back to defaults::LOCALE_GEN.
+    pub fn gen_entries(&self) -> Vec<String> {
+        self.gen
+            .clone()
+            .filter(|gen| !gen.is_empty())
+            .unwrap_or(vec![defaults::LOCALE_GEN.to_string()])
+    }
+}
+
+impl ManifestNetwork {
+    /// Returns packages required by the backend, other than base
+    pub fn packages(&self) -> Vec<&'static str> {
+        let mut packages = match self.backend {
+            NetworkBackend::Networkd => vec![],
+            NetworkBackend::NetworkManager => vec!["networkmanager"],
+            NetworkBackend::Netctl => {
+                match self.interfaces.iter().any(|iface| iface.dhcp) {
+                    true => vec!["netctl", "dhcpcd"],
+                    false => vec!["netctl"],
+                }
+            }
+        };
+
+        if self.wifi.is_some() {
+            packages.push("iwd");
+        }
+
+        packages
+    }
+}
+
+impl ManifestFile {
+    /// Parses octal mode string, e.g. "0644" or "644"
+    pub fn mode_bits(&self) -> Result<Option<u32>, AliError> {
+        let mode = match self.mode {
+            Some(ref mode) => mode,
+            None => return Ok(None),
+        };
+
+        u32::from_str_radix(mode, 8)
+            .ok()
+            .filter(|bits| *bits <= 0o7777)
+            .map(Some)
+            .ok_or(AliError::BadManifest(
+                &codes::E_VAL_FILES,
+                format!("bad mode {mode} for file {}", self.dest),
+            ))
+    }
+}
+
+fn default_bond_mode() -> String {
+    "active-backup".to_string()
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    // Absolute path in the target system
+    #[serde(alias = "path", alias = "destination")]
+    pub dest: String,
+
+    // Inline file content
+    pub content: Option<String>,
+
+    // Local path on the live system, or remote URL
+    #[serde(alias = "src", alias = "url")]
+    pub source: Option<String>,
+
+    // Octal permission bits, e.g. "0600"
+    pub mode: Option<String>,
+
+    pub owner: Option<String>,
+
+    pub group: Option<String>,
+
+    // Replaces {{ key }} tokens with built-in and file vars
+    #[serde(default)]
+    pub template: bool,
+
+    pub vars: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestPacstrap {
+    // -K, initializes an empty keyring in the target
+    #[serde(default = "default_init_keyring", alias = "init-keyring")]
+    pub init_keyring: bool,
+
+    // -c, uses the package cache on the host rather than the target
+    #[serde(default, alias = "host-cache")]
+    pub host_cache: bool,
+
+    // -G, avoids copying the host's keyring to the target
+    #[serde(default, alias = "no-copy-keyring")]
+    pub no_copy_keyring: bool,
+
+    // -M, avoids copying the host's mirrorlist to the target
+    #[serde(default, alias = "no-copy-mirrorlist")]
+    pub no_copy_mirrorlist: bool,
+
+    // -i, prompts for package confirmation
+    #[serde(default)]
+    pub interactive: bool,
+
+    // Installed in order with pacman in arch-chroot after pacstrap,
+    // so that later phases can use the target's pacman.conf.
+    // Phases with after: files run later, after manifest files.
+    #[serde(default)]
+    pub phases: Vec<ManifestPackagePhase>,
+}
+
+fn default_init_keyring() -> bool {
+    true
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestPackagePhase {
+    pub name: String,
+    pub packages: HashSet<String>,
+
+    // Where the phase runs, defaults to right after pacstrap
+    #[serde(default)]
+    pub after: PhaseAfter,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum PhaseAfter {
+    // In bootstrap, after pacstrap and the target's pacman.conf
+    #[default]
+    #[serde(rename = "pacstrap", alias = "bootstrap")]
+    Pacstrap,
+
+    // In arch-chroot, after users were created and files were written,
+    // e.g. for packages whose install hooks read those files
+    #[serde(rename = "files")]
+    Files,
+}
+
+// Members of a package group to install.
+// include and exclude are mutually exclusive.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestPackageGroup {
+    pub name: String,
+
+    // Only install these members
+    #[serde(default, alias = "only")]
+    pub include: Vec<String>,
+
+    // Install all members except these
+    #[serde(default, alias = "except")]
+    pub exclude: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestPacman {
+    // Server URLs for the mirrorlist, e.g. http://mirror/$repo/os/$arch
+    #[serde(default, alias = "mirror", alias = "mirrorlist")]
+    pub mirrors: Vec<String>,
+
+    #[serde(alias = "parallel-downloads", alias = "ParallelDownloads")]
+    pub parallel_downloads: Option<u32>,
+
+    #[serde(default, alias = "Color")]
+    pub color: bool,
+
+    #[serde(default, alias = "ignore-pkg", alias = "IgnorePkg")]
+    pub ignore_pkg: Vec<String>,
+
+    // Repositories after core and extra, e.g. multilib or custom ones
+    #[serde(default, alias = "repositories")]
+    pub repos: Vec<ManifestRepo>,
+
+    // Omits core and extra, so that only repos (e.g. file://) are used
+    #[serde(default)]
+    pub offline: bool,
+
+    // Bind-mounts host package cache into the target during bootstrap
+    #[serde(default, alias = "bind-cache")]
+    pub bind_cache: bool,
+
+    // Package cache to bind-mount, defaults to /var/cache/pacman/pkg.
+    // Setting this implies bind_cache.
+    #[serde(alias = "cache-dir", alias = "CacheDir")]
+    pub cache_dir: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestRepo {
+    pub name: String,
+
+    // Uses the mirrorlist if omitted
+    #[serde(default, alias = "server")]
+    pub servers: Vec<String>,
+
+    #[serde(alias = "sig-level", alias = "SigLevel")]
+    pub sig_level: Option<String>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestKeyring {
+    // pacman-key --init and --populate
+    #[serde(default)]
+    pub init: bool,
+
+    // Upgrades archlinux-keyring on the live system
+    #[serde(default, alias = "refresh")]
+    pub upgrade: bool,
+
+    // Extra signing keys, e.g. for custom repos
+    #[serde(default)]
+    pub keys: Vec<ManifestKey>,
+}
+
+// Signing key to import and locally sign.
+// The key is received from keyserver, unless file is given.
+#[derive(Debug, Default, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ManifestKey {
+    #[serde(alias = "fingerprint")]
+    pub id: String,
+
+    pub keyserver: Option<String>,
+
+    // Armored key file on the live system
+    pub file: Option<String>,
+}
+
 impl From<ManifestRootFs> for ManifestFs {
     fn from(rootfs: ManifestRootFs) -> Self {
         ManifestFs {
@@ -192,7 +906,7 @@ impl From<ManifestRootFs> for ManifestMountpoint {
 #[inline]
 pub fn parse(manifest: &str) -> Result<Manifest, AliError> {
     serde_yaml::from_str(manifest)
-        .map_err(|err| AliError::BadManifest(err.to_string()))
+        .map_err(|err| AliError::BadManifest(&codes::E_MAN, err.to_string()))
 }
 
 #[test]
@@ -0,0 +1,271 @@
+//! Expands package lists and package groups into `manifest.pacstraps`.
+//!
+//! Package lists are expanded before validation, so that their packages
+//! are validated like other pacstraps. Resolving package groups syncs
+//! package databases, so it is only done by apply, like validate_sync.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::ali::{
+    apply,
+    Manifest,
+    ManifestPackageGroup,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::linux::pacman;
+
+const MSG: &str = "package expansion failed";
+
+/// Reads `manifest.package_lists`, adding the packages to `manifest.pacstraps`.
+/// Relative list paths are relative to `manifest_dir`.
+pub fn expand_lists(
+    manifest: &mut Manifest,
+    manifest_dir: &Path,
+) -> Result<(), AliError> {
+    let mut packages = HashSet::new();
+
+    for list in manifest.package_lists.iter().flatten() {
+        let path = manifest_dir.join(list);
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            AliError::NoSuchFile(err, path.to_string_lossy().to_string())
+        })?;
+
+        packages.extend(parse_list(&content));
+    }
+
+    add_pacstraps(manifest, packages);
+
+    Ok(())
+}
+
+/// Resolves `manifest.package_groups` in the sync databases,
+/// adding the selected members to `manifest.pacstraps`.
+///
+/// This syncs databases into [`pacman::DB_PATH`], which needs root
+/// and network access, so it is only done by `apply`.
+pub fn expand_groups(manifest: &mut Manifest) -> Result<(), AliError> {
+    let Some(ref groups) = manifest.package_groups else {
+        return Ok(());
+    };
+
+    let conf = apply::host_conf(&manifest.pacman)?;
+    pacman::sync_db(conf)?;
+
+    let mut packages = HashSet::new();
+    for group in groups {
+        let members = pacman::group_members(conf, &group.name)?;
+        packages.extend(select_members(group, members)?);
+    }
+
+    add_pacstraps(manifest, packages);
+
+    Ok(())
+}
+
+/// Adds `manifest.package_groups` to `manifest.pacstraps` without
+/// resolving them, so that pacstrap installs groups by name,
+/// or only the included members.
+///
+/// Excluding members needs the group resolved, so groups with `exclude`
+/// are rejected, unless `dry_run`, where they are kept by name.
+pub fn expand_group_names(
+    manifest: &mut Manifest,
+    dry_run: bool,
+) -> Result<(), AliError> {
+    let Some(ref groups) = manifest.package_groups else {
+        return Ok(());
+    };
+
+    let mut packages = HashSet::new();
+    for group in groups {
+        let name = &group.name;
+
+        if !group.include.is_empty() && !group.exclude.is_empty() {
+            return Err(AliError::BadManifest(
+                &codes::E_MAN_PACKAGES,
+                format!("{MSG}: group {name} has both include and exclude"),
+            ));
+        }
+
+        if !group.exclude.is_empty() && !dry_run {
+            return Err(AliError::BadManifest(
+                &codes::E_MAN_PACKAGES,
+                format!(
+                    "{MSG}: group {name} has exclude, which needs package database sync"
+                ),
+            ));
+        }
+
+        if group.include.is_empty() {
+            packages.insert(name.clone());
+        } else {
+            packages.extend(group.include.iter().cloned());
+        }
+    }
+
+    add_pacstraps(manifest, packages);
+
+    Ok(())
+}
+
+fn add_pacstraps(manifest: &mut Manifest, packages: HashSet<String>) {
+    if packages.is_empty() {
+        return;
+    }
+
+    match manifest.pacstraps.as_mut() {
+        Some(pacstraps) => pacstraps.extend(packages),
+        None => manifest.pacstraps = Some(packages),
+    }
+}
+
+// Parses package list with one package per line.
+// Everything after # is a comment, and blank lines are ignored.
+fn parse_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn select_members(
+    group: &ManifestPackageGroup,
+    members: Vec<String>,
+) -> Result<Vec<String>, AliError> {
+    let name = &group.name;
+
+    if members.is_empty() {
+        return Err(AliError::BadManifest(
+            &codes::E_MAN_PACKAGES,
+            format!("{MSG}: no such package group {name}"),
+        ));
+    }
+
+    if !group.include.is_empty() && !group.exclude.is_empty() {
+        return Err(AliError::BadManifest(
+            &codes::E_MAN_PACKAGES,
+            format!("{MSG}: group {name} has both include and exclude"),
+        ));
+    }
+
+    for pkg in group.include.iter().chain(group.exclude.iter()) {
+        if !members.contains(pkg) {
+            return Err(AliError::BadManifest(
+                &codes::E_MAN_PACKAGES,
+                format!("{MSG}: package {pkg} is not in group {name}"),
+            ));
+        }
+    }
+
+    if !group.include.is_empty() {
+        return Ok(group.include.clone());
+    }
+
+    Ok(members
+        .into_iter()
+        .filter(|pkg| !group.exclude.contains(pkg))
+        .collect())
+}
+
+#[test]
+fn test_parse_list() {
+    let content = r#"# Base role
+base-devel
+git # for AUR
+
+  neovim
+#tmux
+"#;
+
+    assert_eq!(vec!["base-devel", "git", "neovim"], parse_list(content));
+}
+
+#[test]
+fn test_select_members() {
+    let members: Vec<String> = ["gcc", "make", "patch", "pkgconf"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let group = |include: &[&str], exclude: &[&str]| {
+        ManifestPackageGroup {
+            name: "base-devel".to_string(),
+            include: include.iter().map(|s| s.to_string()).collect(),
+            exclude: exclude.iter().map(|s| s.to_string()).collect(),
+        }
+    };
+
+    let tests = vec![
+        (group(&[], &[]), vec!["gcc", "make", "patch", "pkgconf"]),
+        (group(&["make", "patch"], &[]), vec!["make", "patch"]),
+        (group(&[], &["gcc", "pkgconf"]), vec!["make", "patch"]),
+    ];
+
+    for (group, expected) in tests {
+        let result =
+            select_members(&group, members.clone()).expect("unexpected error");
+
+        assert_eq!(expected, result);
+    }
+
+    let invalids = vec![
+        group(&["make"], &["gcc"]),
+        group(&["clang"], &[]),
+        group(&[], &["clang"]),
+    ];
+
+    for group in invalids {
+        assert!(select_members(&group, members.clone()).is_err());
+    }
+
+    assert!(select_members(&group(&[], &[]), Vec::new()).is_err());
+}
+
+#[test]
+fn test_expand() {
+    let dir = std::env::temp_dir().join("ali-rs-test-package-lists");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("base.txt"), "git\nneovim # editor\n").unwrap();
+
+    let yaml = r#"
+rootfs: {device: /dev/vda1, fstype: btrfs}
+package_lists: [base.txt]
+package_groups:
+  - name: base-devel
+  - name: plasma
+    include: [plasma-desktop, kwin]
+  - name: gnome
+    exclude: [epiphany]
+"#;
+
+    let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+    // List path is relative to manifest directory, not cwd
+    expand_lists(&mut manifest, &dir).expect("failed to expand lists");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(expand_group_names(&mut manifest, false).is_err());
+    expand_group_names(&mut manifest, true).expect("failed to expand groups");
+
+    let mut pacstraps: Vec<_> =
+        manifest.pacstraps.unwrap().into_iter().collect();
+    pacstraps.sort();
+
+    assert_eq!(
+        vec![
+            "base-devel",
+            "git",
+            "gnome",
+            "kwin",
+            "neovim",
+            "plasma-desktop"
+        ],
+        pacstraps
+    );
+}
@@ -0,0 +1,300 @@
+//! Resolves secret references in manifest secrets.
+//!
+//! A secret value may reference where the actual secret is:
+//! - `env:VAR` reads environment variable `VAR`
+//! - `file:/path` reads file `/path`, without its trailing newline
+//! - `prompt` asks the user, without echo and with confirmation
+//!
+//! Redacted values (`<redacted>`, e.g. from a recorded manifest)
+//! are rejected, and other values are used literally. Resolution happens
+//! before validation, so that resolved secrets are validated like literal ones.
+
+use std::io::{
+    BufRead,
+    Write,
+};
+
+use nix::sys::termios;
+
+use crate::ali::{
+    Dm,
+    Manifest,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::types::secret::{
+    self,
+    Secret,
+};
+
+const MSG: &str = "secret resolution failed";
+
+#[derive(Debug, PartialEq)]
+enum SecretRef<'a> {
+    Env(&'a str),
+    File(&'a str),
+    Prompt,
+    Redacted,
+    Literal,
+}
+
+/// Replaces secret references in LUKS passphrases, `rootpasswd`, `root_account`,
+/// user passwords, and Wi-Fi credentials with the secrets they reference.
+pub fn resolve(manifest: &mut Manifest) -> Result<(), AliError> {
+    for dm in manifest.device_mappers.iter_mut().flatten() {
+        if let Dm::Luks(luks) = dm {
+            let name = format!("passphrase for luks {}", luks.name);
+            resolve_secret(&mut luks.passphrase, &name)?;
+        }
+    }
+
+    resolve_secret(&mut manifest.rootpasswd, "rootpasswd")?;
+
+    if let Some(ref mut root) = manifest.root_account {
+        resolve_secret(&mut root.password, "root password")?;
+        resolve_secret(&mut root.password_hash, "root password_hash")?;
+    }
+
+    for user in manifest.users.iter_mut().flatten() {
+        let username = &user.username;
+
+        resolve_secret(
+            &mut user.password,
+            &format!("password for {username}"),
+        )?;
+        resolve_secret(
+            &mut user.password_hash,
+            &format!("password_hash for {username}"),
+        )?;
+    }
+
+    let wifi = manifest
+        .network
+        .as_mut()
+        .and_then(|network| network.wifi.as_mut());
+
+    for net in wifi.into_iter().flat_map(|wifi| wifi.networks.iter_mut()) {
+        let ssid = &net.ssid;
+
+        resolve_secret(
+            &mut net.passphrase,
+            &format!("passphrase for ssid {ssid}"),
+        )?;
+        resolve_secret(&mut net.psk, &format!("psk for ssid {ssid}"))?;
+    }
+
+    Ok(())
+}
+
+fn resolve_secret(
+    secret: &mut Option<Secret>,
+    name: &str,
+) -> Result<(), AliError> {
+    let Some(value) = secret.as_ref() else {
+        return Ok(());
+    };
+
+    let resolved = match parse_ref(value.expose()) {
+        SecretRef::Literal => return Ok(()),
+        SecretRef::Env(var) => {
+            std::env::var(var).map_err(|err| {
+                AliError::BadManifest(
+                    &codes::E_MAN_SECRET,
+                    format!("{MSG}: {name} from env {var}: {err}"),
+                )
+            })?
+        }
+        SecretRef::File(path) => {
+            let content = std::fs::read_to_string(path)
+                .map_err(|err| AliError::NoSuchFile(err, path.to_string()))?;
+
+            trim_newline(content)
+        }
+        SecretRef::Prompt => prompt(name)?,
+        SecretRef::Redacted => {
+            return Err(AliError::BadManifest(
+                &codes::E_MAN_SECRET,
+                format!(
+                    "{MSG}: {name} is redacted, use env:, file:, or prompt"
+                ),
+            ));
+        }
+    };
+
+    if resolved.is_empty() {
+        return Err(AliError::BadManifest(
+            &codes::E_MAN_SECRET,
+            format!("{MSG}: {name} is empty"),
+        ));
+    }
+
+    *secret = Some(Secret::from(resolved));
+
+    Ok(())
+}
+
+fn parse_ref(value: &str) -> SecretRef<'_> {
+    if value == "prompt" {
+        return SecretRef::Prompt;
+    }
+    if value == secret::REDACTED {
+        return SecretRef::Redacted;
+    }
+    if let Some(var) = value.strip_prefix("env:") {
+        return SecretRef::Env(var);
+    }
+    if let Some(path) = value.strip_prefix("file:") {
+        return SecretRef::File(path);
+    }
+
+    SecretRef::Literal
+}
+
+// Secret files usually end with a newline, which is not part of the secret
+fn trim_newline(mut content: String) -> String {
+    if content.ends_with('\n') {
+        content.pop();
+    }
+    if content.ends_with('\r') {
+        content.pop();
+    }
+
+    content
+}
+
+// Reads secret from stdin twice with echo disabled
+fn prompt(name: &str) -> Result<String, AliError> {
+    let secret = read_no_echo(&format!("Enter {name}: "))?;
+    let confirm = read_no_echo(&format!("Confirm {name}: "))?;
+
+    if secret != confirm {
+        return Err(AliError::BadManifest(
+            &codes::E_MAN_SECRET,
+            format!("{MSG}: {name} confirmation does not match"),
+        ));
+    }
+
+    Ok(secret)
+}
+
+fn read_no_echo(prompt: &str) -> Result<String, AliError> {
+    let stdin = std::io::stdin();
+    let tty_err = |err: nix::Error| {
+        AliError::FileError(
+            std::io::Error::from(err),
+            "failed to disable echo on stdin".to_string(),
+        )
+    };
+
+    let original = termios::tcgetattr(&stdin).map_err(tty_err)?;
+    let mut no_echo = original.clone();
+    no_echo.local_flags.remove(termios::LocalFlags::ECHO);
+    no_echo.local_flags.insert(termios::LocalFlags::ECHONL);
+    termios::tcsetattr(&stdin, termios::SetArg::TCSANOW, &no_echo)
+        .map_err(tty_err)?;
+
+    eprint!("{prompt}");
+    let _ = std::io::stderr().flush();
+
+    let mut line = String::new();
+    let result = stdin.lock().read_line(&mut line);
+
+    // Always restore echo, even if reading failed
+    termios::tcsetattr(&stdin, termios::SetArg::TCSANOW, &original)
+        .map_err(tty_err)?;
+
+    result.map_err(|err| {
+        AliError::FileError(err, "failed to read secret from stdin".to_string())
+    })?;
+
+    Ok(trim_newline(line))
+}
+
+#[test]
+fn test_parse_ref() {
+    let tests = vec![
+        ("env:LUKS_PASS", SecretRef::Env("LUKS_PASS")),
+        (
+            "file:/run/secrets/luks",
+            SecretRef::File("/run/secrets/luks"),
+        ),
+        ("prompt", SecretRef::Prompt),
+        ("<redacted>", SecretRef::Redacted),
+        ("mysupersecret", SecretRef::Literal),
+        ("$6$salt$hash", SecretRef::Literal),
+    ];
+
+    for (value, expected) in tests {
+        assert_eq!(expected, parse_ref(value));
+    }
+}
+
+#[test]
+fn test_resolve() {
+    let path = std::env::temp_dir().join("ali-rs-test-secret");
+    std::fs::write(&path, "secret-from-file\n")
+        .expect("failed to write secret");
+
+    std::env::set_var("ALI_RS_TEST_SECRET", "secret-from-env");
+    std::env::set_var("ALI_RS_TEST_WIFI", "wifi-from-env");
+
+    let yaml = format!(
+        r#"
+hostname: foo
+rootpasswd: env:ALI_RS_TEST_SECRET
+dm:
+  - type: luks
+    device: /dev/vda2
+    name: crypty
+    key: file:{}
+users:
+  - username: foo
+    password: literal
+network:
+  wifi:
+    networks:
+      - ssid: home
+        passphrase: env:ALI_RS_TEST_WIFI
+rootfs:
+  device: /dev/mapper/crypty
+  fstype: btrfs
+"#,
+        path.display()
+    );
+
+    let mut manifest = Manifest::from_yaml(&yaml).expect("bad manifest");
+    resolve(&mut manifest).expect("failed to resolve secrets");
+    std::fs::remove_file(&path).expect("failed to remove secret file");
+
+    let Dm::Luks(ref luks) = manifest.device_mappers.as_ref().unwrap()[0]
+    else {
+        panic!("unexpected dm");
+    };
+
+    assert_eq!(Some(Secret::from("secret-from-env")), manifest.rootpasswd);
+    assert_eq!(Some(Secret::from("secret-from-file")), luks.passphrase);
+    assert_eq!(
+        Some(Secret::from("literal")),
+        manifest.users.as_ref().unwrap()[0].password,
+    );
+
+    let net = &manifest
+        .network
+        .as_ref()
+        .unwrap()
+        .wifi
+        .as_ref()
+        .unwrap()
+        .networks[0];
+    assert_eq!(Some(Secret::from("wifi-from-env")), net.passphrase);
+
+    // Wi-Fi credentials are redacted like other secrets
+    let yaml = serde_yaml::to_string(&manifest).expect("failed to serialize");
+    assert!(!yaml.contains("wifi-from-env"));
+    assert!(!format!("{net:?}").contains("wifi-from-env"));
+
+    manifest.rootpasswd = Some(Secret::from("env:ALI_RS_TEST_NO_SUCH_VAR"));
+    assert!(resolve(&mut manifest).is_err());
+}
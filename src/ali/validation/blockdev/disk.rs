@@ -4,7 +4,10 @@ use std::collections::{
 };
 
 use crate::ali::ManifestDisk;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 use crate::linux;
 use crate::types::blockdev::*;
 use crate::utils::fs::file_exists;
@@ -29,17 +32,17 @@ fn collect_valid(
     valids: &mut BlockDevPaths,
 ) -> Result<(), AliError> {
     if !file_exists(&disk.device) {
-        return Err(AliError::BadManifest(format!(
-            "no such disk device: {}",
-            disk.device
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_DISK,
+            format!("no such disk device: {}", disk.device),
+        ));
     }
 
     if let Some(fs) = sys_fs_devs.get(&disk.device) {
-        return Err(AliError::BadManifest(format!(
-            "disk {} already in use as {fs}",
-            disk.device
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_IN_USE,
+            format!("disk {} already in use as {fs}", disk.device),
+        ));
     }
 
     // Find if this disk has any used partitions
@@ -48,10 +51,13 @@ fn collect_valid(
         let partition_name = linux::partition_name(&disk.device, i);
 
         if let Some(fs) = sys_fs_devs.get(&partition_name) {
-            return Err(AliError::BadManifest(format!(
-                "disk {} already in use on {partition_name} as {fs}",
-                disk.device
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_IN_USE,
+                format!(
+                    "disk {} already in use on {partition_name} as {fs}",
+                    disk.device
+                ),
+            ));
         }
     }
 
@@ -75,28 +81,38 @@ fn collect_valid(
         // If multiple partitions are to be created on this disk,
         // only the last partition could be unsized
         if i != l - 1 && l != 1 && part.size.is_none() {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: unsized partition {partition_name} must be the last partition"
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_PART_OVERLAP,
+                format!(
+                    "{msg}: unsized partition {partition_name} must be the last partition"
+                ),
+            ));
         }
 
         if sys_fs_ready_devs.get(&partition_name).is_some() {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: partition {partition_name} already exists on system"
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_PARTITION,
+                format!(
+                    "{msg}: partition {partition_name} already exists on system"
+                ),
+            ));
         }
 
         if let Some(existing_fs) = sys_fs_devs.get(&partition_name) {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: partition {partition_name} is already used as {existing_fs}"
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_IN_USE,
+                format!(
+                    "{msg}: partition {partition_name} is already used as {existing_fs}"
+                ),
+            ));
         }
 
         if let Some(ref size) = part.size {
             if let Err(err) = parse_human_bytes(size) {
-                return Err(AliError::BadManifest(format!(
-                    "{msg}: bad partition size {size}: {err}"
-                )));
+                return Err(AliError::BadManifest(
+                    &codes::E_BLK_PARTITION,
+                    format!("{msg}: bad partition size {size}: {err}"),
+                ));
             }
         }
 
@@ -15,16 +15,20 @@ pub(super) fn collect_valid(
 
     let msg = "dm luks validation failed";
     if file_exists(&luks_path) {
-        return Err(AliError::BadManifest(format!(
-            "{msg}: device {luks_path} already exists"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_LUKS,
+            format!("{msg}: device {luks_path} already exists"),
+        ));
     }
 
     if let Some(fs_type) = sys_fs_devs.get(luks_base_path) {
-        return Err(AliError::BadManifest(format!(
-            "{msg}: luks {} base {luks_base_path} was already in use as {fs_type}",
-            luks.name
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_IN_USE,
+            format!(
+                "{msg}: luks {} base {luks_base_path} was already in use as {fs_type}",
+                luks.name
+            ),
+        ));
     }
 
     let mut found_vg: Option<BlockDev> = None;
@@ -43,10 +47,13 @@ pub(super) fn collect_valid(
             }
 
             if !is_luks_base(&top_most.device_type) {
-                return Err(AliError::BadManifest(format!(
-                    "{msg}: luks base {} (itself is an LVM from {}) cannot have type {}",
-                    luks_base_path, device_base, top_most.device_type
-                )));
+                return Err(AliError::BadManifest(
+                    &codes::E_BLK_LUKS,
+                    format!(
+                        "{msg}: luks base {} (itself is an LVM from {}) cannot have type {}",
+                        luks_base_path, device_base, top_most.device_type
+                    ),
+                ));
             }
 
             // We could really use unstable Cursor type here
@@ -126,10 +133,13 @@ pub(super) fn collect_valid(
         }
 
         if !is_luks_base(&top_most.device_type) {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: luks {} base {luks_base_path} cannot have type {}",
-                luks.name, top_most.device_type,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_LUKS,
+                format!(
+                    "{msg}: luks {} base {luks_base_path} cannot have type {}",
+                    luks.name, top_most.device_type,
+                ),
+            ));
         }
 
         found = true;
@@ -2,7 +2,10 @@ use std::collections::HashSet;
 
 use super::*;
 use crate::ali::ManifestLvmLv;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 use crate::types::blockdev::*;
 
 const MSG: &str = "lvm lv validation failed";
@@ -18,9 +21,12 @@ pub(super) fn collect_valid(
     let (vg_name, lv_name) = vg_lv_name(lv);
 
     if let Some(fs) = sys_fs_devs.get(&lv_name) {
-        return Err(AliError::BadManifest(format!(
-            "{MSG}: another lv with matching name {lv_name} was already used as filesystem {fs}"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_IN_USE,
+            format!(
+                "{MSG}: another lv with matching name {lv_name} was already used as filesystem {fs}"
+            ),
+        ));
     }
 
     let (target_vg, target_lv): (BlockDev, BlockDev) = lv.into();
@@ -33,9 +39,12 @@ pub(super) fn collect_valid(
     lv_paths.extend(lv_paths_valids);
 
     if lv_paths.is_empty() {
-        return Err(AliError::BadManifest(format!(
-            "{MSG}: lv {lv_name} no vg device matching {vg_name} in manifest or in the system"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_LV,
+            format!(
+                "{MSG}: lv {lv_name} no vg device matching {vg_name} in manifest or in the system"
+            ),
+        ));
     }
 
     valids.extend(lv_paths);
@@ -60,9 +69,10 @@ pub(super) fn validate_size(dms: &[ali::Dm]) -> Result<(), AliError> {
                 // Check if size string is valid
                 if let Some(ref size) = lv.size {
                     if let Err(err) = parse_human_bytes(size) {
-                        return Err(AliError::BadManifest(format!(
-                            "bad lv size {size}: {err}"
-                        )));
+                        return Err(AliError::BadManifest(
+                            &codes::E_BLK_LV,
+                            format!("bad lv size {size}: {err}"),
+                        ));
                     }
                 }
 
@@ -88,10 +98,10 @@ pub(super) fn validate_size(dms: &[ali::Dm]) -> Result<(), AliError> {
 
         for (i, lv) in lvs.into_iter().enumerate() {
             if lv.size.is_none() && (i != l - 1) {
-                return Err(AliError::BadManifest(format!(
-                    "lv {} on vg {vg} has None size",
-                    lv.name
-                )));
+                return Err(AliError::BadManifest(
+                    &codes::E_BLK_LV_EXCEEDS_VG,
+                    format!("lv {} on vg {vg} has None size", lv.name),
+                ));
             }
         }
     }
@@ -415,9 +425,17 @@ mod tests {
             assert!(result.is_ok());
         }
 
-        for (_i, t) in should_err.iter().enumerate() {
+        for (i, t) in should_err.iter().enumerate() {
             let result = validate_size(&t.dms);
             assert!(result.is_err());
+
+            // Only the first case has a bad size string,
+            // others have unsized LVs before the last LV
+            let expected = match i {
+                0 => &codes::E_BLK_LV,
+                _ => &codes::E_BLK_LV_EXCEEDS_VG,
+            };
+            assert_eq!(expected, codes::of(&result.unwrap_err()));
         }
     }
 
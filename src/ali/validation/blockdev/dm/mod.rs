@@ -13,7 +13,10 @@ use crate::ali::{
     self,
     Dm,
 };
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 use crate::types::blockdev::*;
 
 pub(super) fn collect_valids(
@@ -11,9 +11,10 @@ pub(super) fn collect_valid(
 ) -> Result<(), AliError> {
     let msg = "lvm pv validation failed";
     if let Some(fs_type) = sys_fs_devs.get(pv_path) {
-        return Err(AliError::BadManifest(format!(
-            "{msg}: pv {pv_path} base was already used as {fs_type}",
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_IN_USE,
+            format!("{msg}: pv {pv_path} base was already used as {fs_type}",),
+        ));
     }
 
     // Find and invalidate duplicate PV if it was used for other VG
@@ -23,10 +24,13 @@ pub(super) fn collect_valid(
                 continue;
             }
 
-            return Err(AliError::BadManifest(format!(
-                "{msg}: pv {pv_path} was already used for other vg {}",
-                node.device,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_IN_USE,
+                format!(
+                    "{msg}: pv {pv_path} was already used for other vg {}",
+                    node.device,
+                ),
+            ));
         }
     }
 
@@ -41,16 +45,20 @@ pub(super) fn collect_valid(
         }
 
         if top_most.device_type == TYPE_PV {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: duplicate pv {pv_path} in manifest"
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_PV,
+                format!("{msg}: duplicate pv {pv_path} in manifest"),
+            ));
         }
 
         if !is_pv_base(&top_most.device_type) {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: pv {} base cannot have type {}",
-                pv_path, top_most.device_type,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_PV,
+                format!(
+                    "{msg}: pv {} base cannot have type {}",
+                    pv_path, top_most.device_type,
+                ),
+            ));
         }
 
         list.push_back(BlockDev {
@@ -82,9 +90,10 @@ pub(super) fn collect_valid(
 
     // TODO: This may introduce error if such file is not a proper block device.
     if !file_exists(pv_path) {
-        return Err(AliError::BadManifest(format!(
-            "{msg}: no such pv device: {pv_path}"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_PV,
+            format!("{msg}: no such pv device: {pv_path}"),
+        ));
     }
 
     valids.push(LinkedList::from([
@@ -15,10 +15,13 @@ pub(super) fn collect_valid(
     'validate_vg_pv: for pv_base in &vg.pvs {
         // Invalidate VG if its PV was already used as FS partition
         if let Some(fs) = sys_fs_devs.get(pv_base) {
-            return Err(AliError::BadManifest(format!(
-                "{msg}: vg {} base {} was already used as filesystem {fs}",
-                vg.name, pv_base
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_IN_USE,
+                format!(
+                    "{msg}: vg {} base {} was already used as filesystem {fs}",
+                    vg.name, pv_base
+                ),
+            ));
         }
 
         // Invalidate VG if its PV was already used in sys LVM
@@ -28,10 +31,13 @@ pub(super) fn collect_valid(
                     continue;
                 }
 
-                return Err(AliError::BadManifest(format!(
-                    "{msg}: vg {} base {} was already used for other vg {}",
-                    vg.name, pv_base, node.device,
-                )));
+                return Err(AliError::BadManifest(
+                    &codes::E_BLK_IN_USE,
+                    format!(
+                        "{msg}: vg {} base {} was already used for other vg {}",
+                        vg.name, pv_base, node.device,
+                    ),
+                ));
             }
         }
 
@@ -46,10 +52,13 @@ pub(super) fn collect_valid(
             }
 
             if !is_vg_base(&top_most.device_type) {
-                return Err(AliError::BadManifest(format!(
-                    "{msg}: vg {} pv base {pv_base} cannot have type {}",
-                    vg.name, top_most.device_type,
-                )));
+                return Err(AliError::BadManifest(
+                    &codes::E_BLK_VG,
+                    format!(
+                        "{msg}: vg {} pv base {pv_base} cannot have type {}",
+                        vg.name, top_most.device_type,
+                    ),
+                ));
             }
 
             list.push_back(dev_vg.clone());
@@ -68,10 +77,10 @@ pub(super) fn collect_valid(
 
                 let top_most = top_most.unwrap();
                 if *top_most == dev_vg {
-                    return Err(AliError::BadManifest(format!(
-                        "{msg}: vg {} already exists",
-                        vg.name,
-                    )));
+                    return Err(AliError::BadManifest(
+                        &codes::E_BLK_VG,
+                        format!("{msg}: vg {} already exists", vg.name,),
+                    ));
                 }
 
                 if top_most.device.as_str() != pv_base {
@@ -79,10 +88,13 @@ pub(super) fn collect_valid(
                 }
 
                 if !is_vg_base(&top_most.device_type) {
-                    return Err(AliError::BadManifest(format!(
-                        "{msg}: vg {} pv base {pv_base} cannot have type {}",
-                        vg.name, top_most.device_type
-                    )));
+                    return Err(AliError::BadManifest(
+                        &codes::E_BLK_VG,
+                        format!(
+                            "{msg}: vg {} pv base {pv_base} cannot have type {}",
+                            vg.name, top_most.device_type
+                        ),
+                    ));
                 }
 
                 let mut new_list = sys_lvm.clone();
@@ -96,9 +108,12 @@ pub(super) fn collect_valid(
             }
         }
 
-        return Err(AliError::BadManifest(format!(
-            "{msg}: no pv device matching {pv_base} in manifest or in the system"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_VG,
+            format!(
+                "{msg}: no pv device matching {pv_base} in manifest or in the system"
+            ),
+        ));
     }
 
     Ok(())
@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use crate::ali::ManifestFs;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 
 pub(super) fn validate_rootfs(
     rootfs: &String,
@@ -11,15 +14,17 @@ pub(super) fn validate_rootfs(
     const MSG: &str = "rootfs validation failed";
 
     if !fs_ready_devs.contains(rootfs) {
-        return Err(AliError::BadManifest(format!(
-            "{MSG}: no top-level fs-ready device for rootfs: {rootfs}",
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_ROOTFS,
+            format!("{MSG}: no top-level fs-ready device for rootfs: {rootfs}",),
+        ));
     }
 
     if let Some(thing) = fs_devs.get(rootfs) {
-        return Err(AliError::BadManifest(format!(
-            "{MSG}: found duplicate fs: {thing}",
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_ROOTFS,
+            format!("{MSG}: found duplicate fs: {thing}",),
+        ));
     }
 
     Ok(())
@@ -36,12 +41,15 @@ pub(super) fn collect_fs_devs(
 
     for (i, fs) in filesystems.iter().enumerate() {
         if !fs_ready_devs.contains(&fs.device) {
-            return Err(AliError::BadManifest(format!(
-                "{MSG}: device {} for fs #{} ({}) is not fs-ready",
-                fs.device,
-                i + 1,
-                fs.fs_type,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_FS,
+                format!(
+                    "{MSG}: device {} for fs #{} ({}) is not fs-ready",
+                    fs.device,
+                    i + 1,
+                    fs.fs_type,
+                ),
+            ));
         }
 
         // Remove used up fs-ready device
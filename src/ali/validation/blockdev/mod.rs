@@ -379,6 +379,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -412,6 +413,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -460,6 +462,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -514,6 +517,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -579,6 +583,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -645,6 +650,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -720,6 +726,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -794,6 +801,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -842,6 +850,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -894,6 +903,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -937,6 +947,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -994,6 +1005,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1060,6 +1072,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1139,6 +1152,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1225,6 +1239,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1316,6 +1331,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1419,6 +1435,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1532,6 +1549,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1659,6 +1677,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1752,6 +1771,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1863,6 +1883,7 @@ mod tests {
                 hostname: None,
                 timezone: None,
                 rootpasswd: None,
+                ..Default::default()
             },
         }];
 
@@ -1893,6 +1914,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1925,6 +1947,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1959,6 +1982,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -1999,6 +2023,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2041,6 +2066,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2086,6 +2112,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2139,6 +2166,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2208,6 +2236,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2268,6 +2297,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2334,6 +2364,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2404,6 +2435,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2474,6 +2506,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2530,6 +2563,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2597,6 +2631,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2664,6 +2699,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2731,6 +2767,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2798,6 +2835,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2865,6 +2903,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2932,6 +2971,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -2992,6 +3032,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3065,6 +3106,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3142,6 +3184,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3219,6 +3262,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3307,6 +3351,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3402,6 +3447,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3510,6 +3556,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3618,6 +3665,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3740,6 +3788,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
 
@@ -3867,6 +3916,7 @@ mod tests {
                     hostname: None,
                     timezone: None,
                     rootpasswd: None,
+                    ..Default::default()
                 },
             },
         ];
@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 
 use crate::ali::ManifestMountpoint;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 
 const MSG: &str = "mountpoint validation failed";
 
@@ -12,17 +15,20 @@ pub(super) fn validate_dups(
 
     for mnt in mountpoints {
         if mnt.dest.as_str() == "/" {
-            return Err(AliError::BadManifest(format!(
-                "{MSG}: bad mountpoint / for non-rootfs {}",
-                mnt.device,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_MOUNT,
+                format!(
+                    "{MSG}: bad mountpoint / for non-rootfs {}",
+                    mnt.device,
+                ),
+            ));
         }
 
         if !dups.insert(mnt.dest.as_str()) {
-            return Err(AliError::BadManifest(format!(
-                "{MSG}: duplicate mountpoints {}",
-                mnt.dest,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_MOUNT,
+                format!("{MSG}: duplicate mountpoints {}", mnt.dest,),
+            ));
         }
     }
 
@@ -38,12 +44,15 @@ pub(super) fn validate(
             continue;
         }
 
-        return Err(AliError::BadManifest(format!(
-            "{MSG}: mountpoint {} for device #{} ({}) is not fs-ready",
-            mnt.dest,
-            i + 1,
-            mnt.device,
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_BLK_MOUNT,
+            format!(
+                "{MSG}: mountpoint {} for device #{} ({}) is not fs-ready",
+                mnt.dest,
+                i + 1,
+                mnt.device,
+            ),
+        ));
     }
 
     Ok(())
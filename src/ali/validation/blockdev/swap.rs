@@ -1,6 +1,9 @@
 use std::collections::HashSet;
 
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 
 pub(super) fn validate(
     swaps: &[String],
@@ -10,10 +13,13 @@ pub(super) fn validate(
 
     for (i, swap) in swaps.iter().enumerate() {
         if !fs_ready_devs.contains(swap) {
-            return Err(AliError::BadManifest(format!(
-                "{MSG}: device {swap} for swap #{} is not fs-ready",
-                i + 1,
-            )));
+            return Err(AliError::BadManifest(
+                &codes::E_BLK_SWAP,
+                format!(
+                    "{MSG}: device {swap} for swap #{} is not fs-ready",
+                    i + 1,
+                ),
+            ));
         }
 
         fs_ready_devs.remove(swap);
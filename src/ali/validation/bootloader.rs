@@ -0,0 +1,182 @@
+use crate::ali::{
+    GrubTarget,
+    Manifest,
+    ManifestBootloader,
+    ManifestUkiSign,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::linux;
+use crate::utils::fs::file_exists;
+use crate::utils::shell;
+
+const MSG: &str = "bootloader validation failed";
+
+pub fn validate(manifest: &Manifest) -> Result<(), AliError> {
+    validate_uki(manifest)?;
+
+    match &manifest.bootloader {
+        None => Ok(()),
+
+        Some(ManifestBootloader::SystemdBoot(_)) => {
+            let esp = validate_esp(manifest)?;
+
+            // systemd-boot can only load kernels from its own partition,
+            // unless the kernels are bundled in UKIs
+            if manifest.uki.is_none() && esp != "/boot" {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_BOOTLOADER,
+                    format!(
+                        "{MSG}: systemd-boot ESP must be mounted on /boot, got {esp}"
+                    ),
+                ));
+            }
+
+            Ok(())
+        }
+
+        Some(ManifestBootloader::Grub(grub)) => {
+            match grub.target {
+                GrubTarget::Uefi => validate_esp(manifest).map(|_| ()),
+                GrubTarget::Bios => {
+                    match &grub.device {
+                        None => {
+                            Err(AliError::BadManifest(
+                                &codes::E_VAL_BOOTLOADER,
+                                format!(
+                                    "{MSG}: grub bios target requires device"
+                                ),
+                            ))
+                        }
+                        Some(device) if !file_exists(device) => {
+                            Err(AliError::BadManifest(
+                                &codes::E_VAL_BOOTLOADER,
+                                format!("{MSG}: no such grub device {device}"),
+                            ))
+                        }
+                        Some(_) => Ok(()),
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn validate_uki(manifest: &Manifest) -> Result<(), AliError> {
+    let uki = match manifest.uki {
+        Some(ref uki) => uki,
+        None => return Ok(()),
+    };
+
+    if let Some(ManifestBootloader::Grub(_)) = manifest.bootloader {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_BOOTLOADER,
+            format!("{MSG}: uki is not supported with grub"),
+        ));
+    }
+
+    validate_esp(manifest)?;
+
+    if let Some(ManifestUkiSign::Sbsign { key, cert }) = &uki.sign {
+        if !shell::in_path("sbsign") {
+            return Err(AliError::Validation(
+                &codes::E_VAL_COMMAND,
+                "command sbsign not in path".to_string(),
+            ));
+        }
+
+        for file in [key, cert] {
+            if !file_exists(file) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_BOOTLOADER,
+                    format!("{MSG}: no such uki signing file {file}"),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// ESP must be mounted, and must be vfat.
+// If ESP filesystem is not created by manifest,
+// its type is read from the live system.
+fn validate_esp(manifest: &Manifest) -> Result<&str, AliError> {
+    let esp = manifest.esp().ok_or(AliError::BadManifest(
+        &codes::E_VAL_BOOTLOADER,
+        format!("{MSG}: no ESP found in mountpoints"),
+    ))?;
+
+    let manifest_fs = manifest
+        .filesystems
+        .as_ref()
+        .and_then(|filesystems| {
+            filesystems.iter().find(|fs| fs.device == esp.device)
+        })
+        .map(|fs| fs.fs_type.clone());
+
+    let fs_type = match manifest_fs {
+        Some(fs_type) => fs_type,
+        None => linux::blkid::fs_type(&esp.device)?,
+    };
+
+    if fs_type != "vfat" {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_BOOTLOADER,
+            format!(
+                "{MSG}: ESP {} on {} must be vfat, got {fs_type}",
+                esp.dest, esp.device,
+            ),
+        ));
+    }
+
+    Ok(&esp.dest)
+}
+
+#[test]
+fn test_validate_bootloader() {
+    use crate::ali::ManifestSystemdBoot;
+
+    let yaml = include_str!("../examples/uefi-root-on-lvm.yaml");
+    let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+    validate(&manifest).expect("unexpected error from example manifest");
+
+    // ESP not mounted
+    manifest.bootloader =
+        Some(ManifestBootloader::SystemdBoot(ManifestSystemdBoot {
+            esp: Some("/efi".to_string()),
+            ..Default::default()
+        }));
+
+    assert!(validate(&manifest).is_err());
+
+    // ESP not on vfat
+    manifest.bootloader = Some(ManifestBootloader::SystemdBoot(
+        ManifestSystemdBoot::default(),
+    ));
+    manifest.filesystems.as_mut().unwrap()[0].fs_type = "ext4".to_string();
+
+    assert!(validate(&manifest).is_err());
+
+    // ESP not on /boot is ok with UKI
+    manifest.filesystems.as_mut().unwrap()[0].fs_type = "vfat".to_string();
+    manifest.mountpoints.as_mut().unwrap()[0].dest = "/efi".to_string();
+
+    assert!(validate(&manifest).is_err());
+
+    manifest.uki = Some(crate::ali::ManifestUki::default());
+    validate(&manifest).expect("unexpected error from uki manifest");
+
+    // sbctl is added to pacstraps by apply, and sbsign key files must exist
+    manifest.uki.as_mut().unwrap().sign = Some(ManifestUkiSign::Sbctl);
+    validate(&manifest).expect("unexpected error from sbctl manifest");
+
+    manifest.uki.as_mut().unwrap().sign = Some(ManifestUkiSign::Sbsign {
+        key: "/no/such/db.key".to_string(),
+        cert: "/no/such/db.crt".to_string(),
+    });
+    assert!(validate(&manifest).is_err());
+}
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use crate::ali::ManifestFile;
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::hooks::Downloader;
+use crate::utils::fs::file_exists;
+
+use super::users::is_valid_name;
+
+const MSG: &str = "files validation failed";
+
+pub fn validate(files: &[ManifestFile]) -> Result<(), AliError> {
+    let mut dups = HashSet::new();
+
+    for file in files {
+        let dest = &file.dest;
+
+        if !dest.starts_with('/') || dest.ends_with('/') {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_FILES,
+                format!("{MSG}: dest {dest} is not an absolute file path"),
+            ));
+        }
+
+        if !dups.insert(dest) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_FILES,
+                format!("{MSG}: duplicate dest {dest}"),
+            ));
+        }
+
+        match (&file.content, &file.source) {
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_FILES,
+                    format!("{MSG}: {dest} needs either content or source"),
+                ));
+            }
+            (None, Some(source)) => {
+                let is_url = Downloader::new_from_url(source).is_ok();
+                if !is_url && !file_exists(source) {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_FILES,
+                        format!("{MSG}: no such source {source} for {dest}"),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        file.mode_bits()?;
+
+        for name in [&file.owner, &file.group].into_iter().flatten() {
+            if !is_valid_name(name) && name.parse::<u32>().is_err() {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_FILES,
+                    format!("{MSG}: bad owner or group {name} for {dest}"),
+                ));
+            }
+        }
+
+        if file.vars.is_some() && !file.template {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_FILES,
+                format!("{MSG}: {dest} has vars but template is false"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_validate_files() {
+    let file = |dest: &str| {
+        ManifestFile {
+            dest: dest.to_string(),
+            content: Some("foo".to_string()),
+            ..Default::default()
+        }
+    };
+
+    let mut with_mode = file("/etc/foo");
+    with_mode.mode = Some("0600".to_string());
+    with_mode.owner = Some("foo".to_string());
+    with_mode.group = Some("1000".to_string());
+
+    let mut with_url = file("/etc/bar");
+    with_url.content = None;
+    with_url.source = Some("https://example.com/bar".to_string());
+
+    validate(&[with_mode, with_url]).expect("unexpected error");
+
+    let mut bad_mode = file("/etc/foo");
+    bad_mode.mode = Some("0800".to_string());
+
+    let mut no_source = file("/etc/foo");
+    no_source.content = None;
+    no_source.source = Some("./no/such/file".to_string());
+
+    let mut both = file("/etc/foo");
+    both.source = Some("https://example.com/foo".to_string());
+
+    let mut bad_owner = file("/etc/foo");
+    bad_owner.owner = Some("foo bar".to_string());
+
+    let invalids = vec![
+        vec![file("etc/foo")],
+        vec![file("/etc/")],
+        vec![file("/etc/foo"), file("/etc/foo")],
+        vec![bad_mode],
+        vec![no_source],
+        vec![both],
+        vec![bad_owner],
+    ];
+
+    for files in invalids {
+        assert!(validate(&files).is_err());
+    }
+}
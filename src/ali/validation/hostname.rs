@@ -0,0 +1,161 @@
+use std::net::IpAddr;
+
+use crate::ali::Manifest;
+use crate::constants::defaults;
+use crate::errors::{
+    codes,
+    AliError,
+};
+
+const MSG: &str = "hostname validation failed";
+
+/// Validates hostname, FQDN, and extra hosts entries
+/// according to RFC 1123 hostname labels.
+pub fn validate(manifest: &Manifest) -> Result<(), AliError> {
+    let hostname = manifest.hostname.as_deref().unwrap_or(defaults::HOSTNAME);
+
+    // /etc/hostname should only contain a single label
+    if !is_valid_label(hostname) {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_HOSTNAME,
+            format!("{MSG}: bad hostname \"{hostname}\""),
+        ));
+    }
+
+    if let Some(ref domain) = manifest.domain {
+        if !is_valid_name(domain) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_HOSTNAME,
+                format!("{MSG}: bad domain \"{domain}\""),
+            ));
+        }
+    }
+
+    if let Some(fqdn) = manifest.fqdn() {
+        if !is_valid_name(&fqdn) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_HOSTNAME,
+                format!("{MSG}: bad fqdn \"{fqdn}\""),
+            ));
+        }
+
+        if !fqdn.starts_with(&format!("{hostname}.")) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_HOSTNAME,
+                format!(
+                    "{MSG}: fqdn {fqdn} does not start with hostname {hostname}"
+                ),
+            ));
+        }
+
+        // Explicit fqdn conflicts with domain
+        if let (Some(_), Some(domain)) = (&manifest.fqdn, &manifest.domain) {
+            if fqdn != format!("{hostname}.{domain}") {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_HOSTNAME,
+                    format!(
+                        "{MSG}: fqdn {fqdn} conflicts with domain {domain}"
+                    ),
+                ));
+            }
+        }
+    }
+
+    for host in manifest.hosts.iter().flatten() {
+        if host.ip.parse::<IpAddr>().is_err() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_HOSTNAME,
+                format!("{MSG}: bad ip address {} in hosts", host.ip),
+            ));
+        }
+
+        if host.names.is_empty() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_HOSTNAME,
+                format!("{MSG}: no names for host {}", host.ip),
+            ));
+        }
+
+        if let Some(name) = host.names.iter().find(|name| !is_valid_name(name))
+        {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_HOSTNAME,
+                format!("{MSG}: bad name \"{name}\" for host {}", host.ip),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Dot-separated labels, at most 253 characters
+fn is_valid_name(name: &str) -> bool {
+    name.len() <= 253 && name.split('.').all(is_valid_label)
+}
+
+// RFC 1123: 1-63 alphanumerics or hyphens, not starting or ending with hyphen
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && !label.starts_with('-')
+        && !label.ends_with('-')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+#[test]
+fn test_validate_hostname() {
+    use crate::ali::ManifestHost;
+
+    let yaml = include_str!("../examples/uefi-root-on-lvm.yaml");
+    let mut manifest = Manifest::from_yaml(yaml).unwrap();
+
+    let valids = vec![
+        (Some("foo"), None, None),
+        (Some("1foo-bar"), Some("example.com"), None),
+        (Some("foo"), None, Some("foo.example.com")),
+        (Some("foo"), Some("example.com"), Some("foo.example.com")),
+        (None, None, None),
+    ];
+
+    for (hostname, domain, fqdn) in valids {
+        manifest.hostname = hostname.map(String::from);
+        manifest.domain = domain.map(String::from);
+        manifest.fqdn = fqdn.map(String::from);
+
+        validate(&manifest).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        (Some(""), None, None),
+        (Some("-foo"), None, None),
+        (Some("foo_bar"), None, None),
+        (Some("foo.example.com"), None, None),
+        (Some("foo"), Some("example..com"), None),
+        (Some("foo"), None, Some("bar.example.com")),
+        (Some("foo"), Some("example.org"), Some("foo.example.com")),
+    ];
+
+    for (hostname, domain, fqdn) in invalids {
+        manifest.hostname = hostname.map(String::from);
+        manifest.domain = domain.map(String::from);
+        manifest.fqdn = fqdn.map(String::from);
+
+        assert!(validate(&manifest).is_err());
+    }
+
+    manifest.hostname = Some("foo".to_string());
+    manifest.domain = None;
+    manifest.fqdn = None;
+
+    manifest.hosts = Some(vec![ManifestHost {
+        ip: "fd00::1".to_string(),
+        names: vec!["bar".to_string()],
+    }]);
+    validate(&manifest).expect("unexpected error");
+
+    manifest.hosts = Some(vec![ManifestHost {
+        ip: "10.0.0.256".to_string(),
+        names: vec!["bar".to_string()],
+    }]);
+    assert!(validate(&manifest).is_err());
+}
@@ -0,0 +1,211 @@
+use std::path::Path;
+
+use crate::ali::ManifestLocale;
+use crate::errors::{
+    codes,
+    AliError,
+};
+
+const MSG: &str = "locale validation failed";
+
+const SUPPORTED: &str = "/usr/share/i18n/SUPPORTED";
+const KEYMAPS_DIR: &str = "/usr/share/kbd/keymaps";
+
+const LC_KEYS: [&str; 13] = [
+    "LC_CTYPE",
+    "LC_NUMERIC",
+    "LC_TIME",
+    "LC_COLLATE",
+    "LC_MONETARY",
+    "LC_MESSAGES",
+    "LC_PAPER",
+    "LC_NAME",
+    "LC_ADDRESS",
+    "LC_TELEPHONE",
+    "LC_MEASUREMENT",
+    "LC_IDENTIFICATION",
+    "LC_ALL",
+];
+
+/// Validates manifest locales against the live system's
+/// list of supported locales and kbd keymaps.
+pub fn validate(locale: &ManifestLocale) -> Result<(), AliError> {
+    let supported = std::fs::read_to_string(SUPPORTED)
+        .map_err(|err| AliError::NoSuchFile(err, SUPPORTED.to_string()))?;
+
+    validate_locales(locale, &supported)?;
+
+    if let Some(ref keymap) = locale.keymap {
+        if !keymap_exists(Path::new(KEYMAPS_DIR), keymap) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_LOCALE,
+                format!("{MSG}: no such keymap {keymap} in {KEYMAPS_DIR}"),
+            ));
+        }
+    }
+
+    for value in [&locale.font, &locale.x11_layout].into_iter().flatten() {
+        if value.is_empty() || value.contains(char::is_whitespace) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_LOCALE,
+                format!("{MSG}: bad console font or x11 layout \"{value}\""),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_locales(
+    locale: &ManifestLocale,
+    supported: &str,
+) -> Result<(), AliError> {
+    let supported: Vec<Vec<&str>> = supported
+        .lines()
+        .map(|line| line.split_whitespace().collect())
+        .filter(|fields: &Vec<&str>| fields.len() == 2)
+        .collect();
+
+    // Validates the same entries that will be generated,
+    // which fall back to defaults if not set
+    let entries = locale.gen_entries();
+
+    let mut names = Vec::new();
+    for entry in entries.iter() {
+        let fields: Vec<&str> = entry.split_whitespace().collect();
+
+        let found = supported.iter().any(|line| {
+            match fields.len() {
+                1 => line[0] == fields[0],
+                _ => *line == fields,
+            }
+        });
+
+        if !found {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_LOCALE,
+                format!("{MSG}: locale \"{entry}\" not in {SUPPORTED}"),
+            ));
+        }
+
+        names.push(fields[0]);
+    }
+
+    // LANG and LC_* must be generated
+    let generated = |name: &str| names.contains(&name);
+
+    if let Some(ref lang) = locale.lang {
+        if !generated(lang) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_LOCALE,
+                format!("{MSG}: LANG {lang} is not in locales to generate"),
+            ));
+        }
+    }
+
+    for (key, value) in locale.lc.iter().flatten() {
+        if !LC_KEYS.contains(&key.as_str()) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_LOCALE,
+                format!("{MSG}: unknown locale category {key}"),
+            ));
+        }
+
+        if !generated(value) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_LOCALE,
+                format!("{MSG}: {key} {value} is not in locales to generate"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Keymaps are stored in subdirectories, e.g. i386/qwerty/us.map.gz
+fn keymap_exists(dir: &Path, keymap: &str) -> bool {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return false,
+    };
+
+    entries.flatten().any(|entry| {
+        let path = entry.path();
+        if path.is_dir() {
+            return keymap_exists(&path, keymap);
+        }
+
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        name == format!("{keymap}.map.gz") || name == format!("{keymap}.map")
+    })
+}
+
+#[test]
+fn test_validate_locales() {
+    use std::collections::BTreeMap;
+
+    let supported = r#"en_US.UTF-8 UTF-8
+en_US ISO-8859-1
+en_GB.UTF-8 UTF-8
+th_TH.UTF-8 UTF-8
+"#;
+
+    let valids = vec![
+        ManifestLocale::default(),
+        ManifestLocale {
+            gen: Some(vec![
+                "en_US.UTF-8 UTF-8".to_string(),
+                "th_TH.UTF-8".to_string(),
+            ]),
+            lang: Some("th_TH.UTF-8".to_string()),
+            lc: Some(BTreeMap::from([(
+                "LC_TIME".to_string(),
+                "en_US.UTF-8".to_string(),
+            )])),
+            ..Default::default()
+        },
+        ManifestLocale {
+            lang: Some("en_US.UTF-8".to_string()),
+            ..Default::default()
+        },
+    ];
+
+    for locale in valids {
+        validate_locales(&locale, supported).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        // en_GB is not generated by default
+        ManifestLocale {
+            lang: Some("en_GB.UTF-8".to_string()),
+            ..Default::default()
+        },
+        ManifestLocale {
+            gen: Some(vec!["en_US.UTF-8 ISO-8859-1".to_string()]),
+            ..Default::default()
+        },
+        ManifestLocale {
+            gen: Some(vec!["foo_BAR.UTF-8".to_string()]),
+            ..Default::default()
+        },
+        ManifestLocale {
+            gen: Some(vec!["en_US.UTF-8".to_string()]),
+            lang: Some("th_TH.UTF-8".to_string()),
+            ..Default::default()
+        },
+        ManifestLocale {
+            gen: Some(vec!["en_US.UTF-8".to_string()]),
+            lc: Some(BTreeMap::from([(
+                "LC_FOO".to_string(),
+                "en_US.UTF-8".to_string(),
+            )])),
+            ..Default::default()
+        },
+    ];
+
+    for locale in invalids {
+        assert!(validate_locales(&locale, supported).is_err());
+    }
+}
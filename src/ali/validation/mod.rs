@@ -1,12 +1,25 @@
 mod blockdev;
+mod bootloader;
+mod files;
 mod hooks;
+mod hostname;
+mod locale;
+mod network;
+mod packages;
+mod pacman;
+mod services;
+mod users;
 
 use crate::ali::Manifest;
 use crate::constants::{
     self,
     defaults,
 };
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::types::blockdev::BlockDevPaths;
 use crate::types::report::ValidationReport;
 use crate::utils::fs::file_exists;
 use crate::utils::shell;
@@ -22,18 +35,20 @@ pub fn validate(
     // Check all commands used by ALI before ch-root
     for cmd in constants::REQUIRED_COMMANDS {
         if !shell::in_path(cmd) {
-            return Err(AliError::Validation(format!(
-                "command {cmd} not in path"
-            )));
+            return Err(AliError::Validation(
+                &codes::E_VAL_COMMAND,
+                format!("command {cmd} not in path"),
+            ));
         }
     }
 
     // Check mkfs for rootfs
     let mkfs_rootfs = &format!("mkfs.{}", manifest.rootfs.fs_type);
     if !shell::in_path(mkfs_rootfs) {
-        return Err(AliError::BadManifest(format!(
-            "no such program to create rootfs: {mkfs_rootfs}"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_MKFS,
+            format!("no such program to create rootfs: {mkfs_rootfs}"),
+        ));
     }
 
     // Check mkfs.{fs} for other FS
@@ -43,13 +58,69 @@ pub fn validate(
             if !shell::in_path(mkfs_cmd) {
                 let device = &fs.device;
 
-                return Err(AliError::BadManifest(format!(
-                    "no such program to create filesystem for device {device}: {mkfs_cmd}"
-                )));
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_MKFS,
+                    format!(
+                        "no such program to create filesystem for device {device}: {mkfs_cmd}"
+                    ),
+                ));
             }
         }
     }
 
+    // Validate pacman.conf options and repositories
+    if let Some(ref manifest_pacman) = manifest.pacman {
+        pacman::validate(manifest_pacman)?;
+    }
+
+    // Validate keys to import in the live system and target keyrings
+    if let Some(ref keyring) = manifest.keyring {
+        pacman::validate_keyring(keyring)?;
+    }
+
+    // Validate package phases. Packages are resolved in sync databases
+    // later by `apply`, see [`packages_sync`]
+    packages::validate(manifest)?;
+
+    // Validate local package files to install with pacman -U
+    if let Some(ref local_packages) = manifest.local_packages {
+        packages::validate_local(local_packages)?;
+    }
+
+    // Validate hostname, fqdn, and hosts entries
+    hostname::validate(manifest)?;
+
+    // Validate locales, keymap, and fonts
+    if let Some(ref manifest_locale) = manifest.locale {
+        locale::validate(manifest_locale)?;
+    }
+
+    // Validate network interfaces
+    if let Some(ref manifest_network) = manifest.network {
+        network::validate(manifest_network)?;
+    }
+
+    // Validate root password, or root lock
+    users::validate_root(manifest)?;
+
+    // Validate users and groups
+    if let Some(ref manifest_users) = manifest.users {
+        users::validate(manifest_users)?;
+    }
+
+    // Validate systemd unit names
+    if let Some(ref manifest_services) = manifest.services {
+        services::validate(manifest_services)?;
+    }
+
+    // Validate files to write into the target
+    if let Some(ref manifest_files) = manifest.files {
+        files::validate(manifest_files)?;
+    }
+
+    // Validate bootloader and ESP
+    bootloader::validate(manifest)?;
+
     // Validate ali-rs hooks
     hooks::validate(manifest, install_location)?;
 
@@ -63,10 +134,33 @@ pub fn validate(
     );
 
     if !file_exists(&zone_info) {
-        return Err(AliError::BadManifest(format!(
-            "no zone info file {zone_info}"
-        )));
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_LOCALE,
+            format!("no zone info file {zone_info}"),
+        ));
     }
 
     Ok(ValidationReport { block_devs })
 }
+
+/// Validates that packages can be resolved in sync databases,
+/// which needs root and network access.
+pub fn packages_sync(manifest: &Manifest) -> Result<(), AliError> {
+    packages::validate_sync(manifest)
+}
+
+/// Collects block device paths from manifest alone,
+/// without checking against existing system devices.
+pub fn block_devs(manifest: &Manifest) -> Result<BlockDevPaths, AliError> {
+    blockdev::validate(manifest, true)
+}
+
+/// Checks if systemd unit file exists in `install_location`,
+/// after the target system was bootstrapped.
+pub fn unit_installed(
+    unit: &str,
+    global: bool,
+    install_location: &str,
+) -> Result<(), AliError> {
+    services::unit_installed(unit, global, install_location)
+}
@@ -0,0 +1,304 @@
+use std::collections::HashSet;
+use std::net::IpAddr;
+
+use crate::ali::{
+    ManifestNetwork,
+    ManifestWifi,
+    NetworkBackend,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+
+const MSG: &str = "network validation failed";
+
+pub fn validate(network: &ManifestNetwork) -> Result<(), AliError> {
+    let mut names = HashSet::new();
+    let mut slaves = HashSet::new();
+
+    for iface in &network.interfaces {
+        let name = &iface.name;
+
+        if !is_valid_ifname(name) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_NETWORK,
+                format!("{MSG}: bad interface name \"{name}\""),
+            ));
+        }
+
+        if !names.insert(name) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_NETWORK,
+                format!("{MSG}: duplicate interface {name}"),
+            ));
+        }
+
+        for addr in &iface.addresses {
+            if !is_valid_cidr(addr) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!(
+                        "{MSG}: bad address {addr} for {name}, expecting CIDR"
+                    ),
+                ));
+            }
+        }
+
+        for ip in iface.gateways.iter().chain(iface.dns.iter()) {
+            if ip.parse::<IpAddr>().is_err() {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!("{MSG}: bad ip address {ip} for {name}"),
+                ));
+            }
+        }
+
+        for route in &iface.routes {
+            if !is_valid_cidr(&route.to) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!(
+                        "{MSG}: bad route destination {} for {name}",
+                        route.to
+                    ),
+                ));
+            }
+
+            if let Some(ref via) = route.via {
+                if via.parse::<IpAddr>().is_err() {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_NETWORK,
+                        format!("{MSG}: bad route gateway {via} for {name}"),
+                    ));
+                }
+            }
+        }
+
+        if iface.bond.is_some() && iface.vlan.is_some() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_NETWORK,
+                format!("{MSG}: {name} cannot be both bond and vlan"),
+            ));
+        }
+
+        if let Some(ref bond) = iface.bond {
+            if bond.slaves.is_empty() {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!("{MSG}: bond {name} has no slaves"),
+                ));
+            }
+
+            for slave in &bond.slaves {
+                if !is_valid_ifname(slave) || slave == name {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_NETWORK,
+                        format!("{MSG}: bad slave {slave} for bond {name}"),
+                    ));
+                }
+
+                if !slaves.insert(slave) {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_NETWORK,
+                        format!(
+                            "{MSG}: slave {slave} is used by more than 1 bond"
+                        ),
+                    ));
+                }
+            }
+        }
+
+        if let Some(ref vlan) = iface.vlan {
+            if !(1..=4094).contains(&vlan.id) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!("{MSG}: bad vlan id {} for {name}", vlan.id),
+                ));
+            }
+
+            if !is_valid_ifname(&vlan.parent) || &vlan.parent == name {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!(
+                        "{MSG}: bad vlan parent {} for {name}",
+                        vlan.parent
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Bond slaves are configured by their bond
+    if let Some(slave) = slaves.iter().find(|slave| names.contains(*slave)) {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_NETWORK,
+            format!(
+                "{MSG}: bond slave {slave} is also configured as an interface"
+            ),
+        ));
+    }
+
+    if let Some(ref wifi) = network.wifi {
+        validate_wifi(wifi, &network.backend)?;
+    }
+
+    Ok(())
+}
+
+fn validate_wifi(
+    wifi: &ManifestWifi,
+    backend: &NetworkBackend,
+) -> Result<(), AliError> {
+    // NetworkManager manages its own wireless connections
+    if *backend == NetworkBackend::NetworkManager {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_NETWORK,
+            format!("{MSG}: wifi cannot be used with networkmanager backend"),
+        ));
+    }
+
+    if let Some(ref inf) = wifi.interface {
+        if !is_valid_ifname(&inf.replace('*', "")) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_NETWORK,
+                format!("{MSG}: bad wireless interface \"{inf}\""),
+            ));
+        }
+    }
+
+    let mut ssids = HashSet::new();
+    for net in &wifi.networks {
+        let ssid = &net.ssid;
+
+        if ssid.is_empty() || ssid.len() > 32 {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_NETWORK,
+                format!("{MSG}: ssid \"{ssid}\" must be 1-32 bytes"),
+            ));
+        }
+
+        if !ssids.insert(ssid) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_NETWORK,
+                format!("{MSG}: duplicate ssid \"{ssid}\""),
+            ));
+        }
+
+        // Messages must not contain secrets
+        match (&net.passphrase, &net.psk) {
+            (Some(_), Some(_)) | (None, None) => {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_NETWORK,
+                    format!(
+                        "{MSG}: ssid \"{ssid}\" needs either passphrase or psk"
+                    ),
+                ));
+            }
+            (Some(passphrase), None) => {
+                if !(8..=63).contains(&passphrase.expose().len()) {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_NETWORK,
+                        format!(
+                            "{MSG}: passphrase for ssid \"{ssid}\" must be 8-63 characters"
+                        ),
+                    ));
+                }
+            }
+            (None, Some(psk)) => {
+                let psk = psk.expose();
+                if psk.len() != 64
+                    || !psk.chars().all(|c| c.is_ascii_hexdigit())
+                {
+                    return Err(AliError::BadManifest(
+                        &codes::E_VAL_NETWORK,
+                        format!(
+                            "{MSG}: psk for ssid \"{ssid}\" must be 64 hex digits"
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Linux interface names are at most 15 bytes (IFNAMSIZ - 1)
+fn is_valid_ifname(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 15
+        && name.chars().all(|c| {
+            c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.'
+        })
+}
+
+fn is_valid_cidr(cidr: &str) -> bool {
+    let (ip, prefix) = match cidr.split_once('/') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let max = match ip.parse::<IpAddr>() {
+        Ok(IpAddr::V4(_)) => 32,
+        Ok(IpAddr::V6(_)) => 128,
+        Err(_) => return false,
+    };
+
+    prefix.parse::<u8>().is_ok_and(|prefix| prefix <= max)
+}
+
+#[test]
+fn test_validate_network() {
+    let parse = |yaml: &str| -> ManifestNetwork {
+        serde_yaml::from_str(yaml).expect("failed to parse network")
+    };
+
+    let valids = vec![
+        "interfaces: [{name: eth0, dhcp: true}]",
+        "backend: netctl\ninterfaces: [{name: eth0, addresses: [10.0.0.2/24, fd00::2/64], gateways: [10.0.0.1]}]",
+        "interfaces: [{name: bond0, bond: {slaves: [eno1, eno2]}}, {name: bond0.5, vlan: {parent: bond0, id: 5}}]",
+    ];
+
+    for yaml in valids {
+        validate(&parse(yaml)).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        "interfaces: [{name: eth0}, {name: eth0}]",
+        "interfaces: [{name: averyverylongname0}]",
+        "interfaces: [{name: eth0, addresses: [10.0.0.2]}]",
+        "interfaces: [{name: eth0, addresses: [10.0.0.2/33]}]",
+        "interfaces: [{name: eth0, gateways: [foo]}]",
+        "interfaces: [{name: eth0, routes: [{to: 10.1.0.0/16, via: bar}]}]",
+        "interfaces: [{name: bond0, bond: {slaves: []}}]",
+        "interfaces: [{name: bond0, bond: {slaves: [eno1]}}, {name: eno1}]",
+        "interfaces: [{name: vlan0, vlan: {parent: eth0, id: 4095}}]",
+    ];
+
+    for yaml in invalids {
+        assert!(validate(&parse(yaml)).is_err(), "{yaml}");
+    }
+
+    let psk = "a".repeat(64);
+    let wifi_valids = vec![
+        "wifi: {networks: [{ssid: home, passphrase: foobarbaz}]}".to_string(),
+        format!("backend: netctl\nwifi: {{networking: networkd, interface: wlan0, networks: [{{ssid: home, psk: {psk}}}]}}"),
+    ];
+
+    for yaml in wifi_valids {
+        validate(&parse(&yaml)).expect("unexpected error");
+    }
+
+    let wifi_invalids = vec![
+        "backend: networkmanager\nwifi: {networks: [{ssid: home, passphrase: foobarbaz}]}",
+        "wifi: {networks: [{ssid: home}]}",
+        "wifi: {networks: [{ssid: home, passphrase: short}]}",
+        "wifi: {networks: [{ssid: home, psk: nothex}]}",
+        "wifi: {networks: [{ssid: home, passphrase: foobarbaz}, {ssid: home, passphrase: foobarbaz}]}",
+    ];
+
+    for yaml in wifi_invalids {
+        assert!(validate(&parse(yaml)).is_err(), "{yaml}");
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{
+    BTreeSet,
+    HashSet,
+};
+
+use crate::ali::{
+    apply,
+    Manifest,
+    ManifestPackagePhase,
+    PhaseAfter,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::linux::pacman;
+use crate::utils::fs::file_exists;
+
+const MSG: &str = "package validation failed";
+
+/// Checks package phases in `pacstrap_opts`
+pub fn validate(manifest: &Manifest) -> Result<(), AliError> {
+    if let Some(ref opts) = manifest.pacstrap_opts {
+        validate_phases(&opts.phases)?;
+    }
+
+    Ok(())
+}
+
+/// Checks that base and all packages in `pacstraps` can be resolved
+/// in the sync databases configured by `manifest.pacman`,
+/// or the live system's pacman.conf.
+///
+/// This syncs databases into [`pacman::DB_PATH`], which needs root
+/// and network access, so it is only done by `apply`.
+pub fn validate_sync(manifest: &Manifest) -> Result<(), AliError> {
+    let conf = apply::host_conf(&manifest.pacman)?;
+
+    let mut packages = BTreeSet::from(["base"]);
+    for pkg in manifest.pacstraps.iter().flatten() {
+        packages.insert(pkg);
+    }
+
+    for phase in manifest.pacstrap_opts.iter().flat_map(|opts| &opts.phases) {
+        packages.extend(phase.packages.iter().map(String::as_str));
+    }
+
+    let packages: Vec<&str> = packages.into_iter().collect();
+
+    pacman::sync_db(conf)?;
+    let missing = pacman::missing_packages(conf, &packages)?;
+
+    if !missing.is_empty() {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_PACKAGES,
+            format!(
+                "{MSG}: packages not found in sync databases: {}",
+                missing.join(", ")
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_phases(phases: &[ManifestPackagePhase]) -> Result<(), AliError> {
+    let mut names = HashSet::new();
+    for (index, phase) in phases.iter().enumerate() {
+        let name = phase.name.as_str();
+
+        if name.is_empty() || !names.insert(name) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACKAGES,
+                format!(
+                    "{MSG}: empty or duplicate package phase name \"{name}\""
+                ),
+            ));
+        }
+
+        if phase.packages.is_empty() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACKAGES,
+                format!("{MSG}: package phase {name} has no packages"),
+            ));
+        }
+
+        // Phases run in the listed order, so files phases come last
+        if phase.after == PhaseAfter::Pacstrap
+            && phases[..index].iter().any(|p| p.after == PhaseAfter::Files)
+        {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACKAGES,
+                format!(
+                    "{MSG}: package phase {name} must come before files phases"
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that local packages are existing pacman package files
+pub fn validate_local(packages: &[String]) -> Result<(), AliError> {
+    for pkg in packages {
+        if !is_package_file(pkg) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACKAGES,
+                format!("{MSG}: local package {pkg} is not a .pkg.tar.* file"),
+            ));
+        }
+
+        if !file_exists(pkg) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACKAGES,
+                format!("{MSG}: no such local package {pkg}"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_package_file(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+
+    match name.split_once(".pkg.tar") {
+        Some((pkg, ext)) => {
+            !pkg.is_empty()
+                && matches!(ext, "" | ".zst" | ".xz" | ".gz" | ".bz2")
+        }
+        None => false,
+    }
+}
+
+#[test]
+fn test_is_package_file() {
+    let valids = vec![
+        "./pkgs/foo-1.0-1-x86_64.pkg.tar.zst",
+        "/root/bar-git-r10.abc-1-any.pkg.tar.xz",
+        "baz-2-1-any.pkg.tar",
+    ];
+
+    let invalids = vec![
+        "./pkgs/foo.tar.zst",
+        "/root/.pkg.tar.zst",
+        "foo-1-1-any.pkg.tar.zst.sig",
+        "packages.txt",
+    ];
+
+    for valid in valids {
+        assert!(is_package_file(valid), "unexpected invalid {valid}");
+    }
+
+    for invalid in invalids {
+        assert!(!is_package_file(invalid), "unexpected valid {invalid}");
+    }
+}
+
+#[test]
+fn test_validate_phases() {
+    let parse = |yaml: &str| -> Vec<ManifestPackagePhase> {
+        serde_yaml::from_str(yaml).expect("failed to parse phases")
+    };
+
+    let valids = vec![
+        "[]",
+        "[{name: desktop, packages: [plasma-meta]}]",
+        "[{name: drivers, packages: [mesa]}, {name: desktop, packages: [plasma-meta, sddm]}]",
+        "[{name: drivers, packages: [mesa]}, {name: desktop, packages: [sddm], after: files}]",
+    ];
+
+    for yaml in valids {
+        validate_phases(&parse(yaml)).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        "[{name: '', packages: [mesa]}]",
+        "[{name: desktop, packages: []}]",
+        "[{name: desktop, packages: [mesa]}, {name: desktop, packages: [sddm]}]",
+        "[{name: desktop, packages: [sddm], after: files}, {name: drivers, packages: [mesa]}]",
+    ];
+
+    for yaml in invalids {
+        assert!(validate_phases(&parse(yaml)).is_err(), "{yaml}");
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::ali::{
+    ManifestKeyring,
+    ManifestPacman,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+
+const MSG: &str = "pacman validation failed";
+const MSG_KEYRING: &str = "keyring validation failed";
+
+const REPOS_DEFAULT: [&str; 2] = ["core", "extra"];
+
+const SIG_LEVELS: [&str; 5] =
+    ["Never", "Optional", "Required", "TrustedOnly", "TrustAll"];
+
+pub fn validate(pacman: &ManifestPacman) -> Result<(), AliError> {
+    for mirror in &pacman.mirrors {
+        if !is_valid_server(mirror) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG}: bad mirror {mirror}"),
+            ));
+        }
+    }
+
+    if pacman.parallel_downloads == Some(0) {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_PACMAN,
+            format!("{MSG}: parallel_downloads must be at least 1"),
+        ));
+    }
+
+    for pkg in &pacman.ignore_pkg {
+        if pkg.is_empty() || pkg.contains(char::is_whitespace) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG}: bad IgnorePkg entry \"{pkg}\""),
+            ));
+        }
+    }
+
+    if pacman.offline && pacman.repos.is_empty() {
+        return Err(AliError::BadManifest(
+            &codes::E_VAL_PACMAN,
+            format!("{MSG}: offline install requires at least 1 repo"),
+        ));
+    }
+
+    if let Some(ref cache_dir) = pacman.cache_dir {
+        if !Path::new(cache_dir).is_dir() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG}: no such cache directory {cache_dir}"),
+            ));
+        }
+    }
+
+    let mut names = HashSet::from(REPOS_DEFAULT);
+    for repo in &pacman.repos {
+        let name = repo.name.as_str();
+
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+        if !valid || name == "options" {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG}: bad repo name \"{name}\""),
+            ));
+        }
+
+        if !names.insert(name) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG}: duplicate repo {name}"),
+            ));
+        }
+
+        for server in &repo.servers {
+            if !is_valid_server(server) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_PACMAN,
+                    format!("{MSG}: bad server {server} for repo {name}"),
+                ));
+            }
+        }
+
+        if let Some(ref sig_level) = repo.sig_level {
+            if !is_valid_sig_level(sig_level) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_PACMAN,
+                    format!(
+                        "{MSG}: bad SigLevel \"{sig_level}\" for repo {name}"
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_keyring(keyring: &ManifestKeyring) -> Result<(), AliError> {
+    for key in &keyring.keys {
+        let id = key.id.as_str();
+        if !is_valid_key_id(id) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG_KEYRING}: bad key id \"{id}\""),
+            ));
+        }
+
+        if key.file.is_some() && key.keyserver.is_some() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_PACMAN,
+                format!("{MSG_KEYRING}: key {id} has both file and keyserver"),
+            ));
+        }
+
+        if let Some(ref keyserver) = key.keyserver {
+            if !is_valid_server(keyserver) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_PACMAN,
+                    format!(
+                        "{MSG_KEYRING}: bad keyserver {keyserver} for key {id}"
+                    ),
+                ));
+            }
+        }
+
+        if let Some(ref file) = key.file {
+            if !Path::new(file).is_file() {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_PACMAN,
+                    format!(
+                        "{MSG_KEYRING}: no such key file {file} for key {id}"
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Key IDs are 8, 16, or 40 (fingerprint) hex digits, optionally 0x-prefixed
+fn is_valid_key_id(id: &str) -> bool {
+    let id = id.strip_prefix("0x").unwrap_or(id);
+
+    matches!(id.len(), 8 | 16 | 40) && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_valid_server(server: &str) -> bool {
+    server.contains("://") && !server.contains(char::is_whitespace)
+}
+
+// e.g. "Optional TrustAll" or "PackageRequired DatabaseNever"
+fn is_valid_sig_level(sig_level: &str) -> bool {
+    let mut words = sig_level.split_whitespace().peekable();
+    if words.peek().is_none() {
+        return false;
+    }
+
+    words.all(|word| {
+        let word = word
+            .strip_prefix("Package")
+            .or_else(|| word.strip_prefix("Database"))
+            .unwrap_or(word);
+
+        SIG_LEVELS.contains(&word)
+    })
+}
+
+#[test]
+fn test_validate_pacman() {
+    let parse = |yaml: &str| -> ManifestPacman {
+        serde_yaml::from_str(yaml).expect("failed to parse pacman")
+    };
+
+    let valids = vec![
+        "color: true",
+        "mirrors: [http://mirror.local/$repo/os/$arch]\nparallel_downloads: 3",
+        "repos: [{name: multilib}, {name: my-repo, servers: [file:///srv/repo], sig_level: PackageRequired DatabaseNever}]",
+    ];
+
+    for yaml in valids {
+        validate(&parse(yaml)).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        "mirrors: [mirror.local]",
+        "parallel_downloads: 0",
+        "repos: [{name: core}]",
+        "repos: [{name: options}]",
+        "repos: [{name: foo}, {name: foo}]",
+        "repos: [{name: 'foo bar'}]",
+        "repos: [{name: foo, sig_level: Maybe}]",
+        "repos: [{name: foo, sig_level: ''}]",
+        "ignore_pkg: ['linux headers']",
+        "offline: true",
+        "cache_dir: /no/such/cache",
+    ];
+
+    for yaml in invalids {
+        assert!(validate(&parse(yaml)).is_err(), "{yaml}");
+    }
+}
+
+#[test]
+fn test_validate_keyring() {
+    let parse = |yaml: &str| -> ManifestKeyring {
+        serde_yaml::from_str(yaml).expect("failed to parse keyring")
+    };
+
+    let valids = vec![
+        "{init: true, upgrade: true}",
+        "keys: [{id: 0xF3B607488DB35A47, keyserver: hkps://keyserver.ubuntu.com}]",
+        "keys: [{id: 3056513887B78AEB8BF4E0A3E5F0D3B4C6F0A3B1}]",
+    ];
+
+    for yaml in valids {
+        validate_keyring(&parse(yaml)).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        "keys: [{id: F3B6074}]",
+        "keys: [{id: not-a-key-id}]",
+        "keys: [{id: F3B607488DB35A47, keyserver: keyserver.ubuntu.com}]",
+        "keys: [{id: F3B607488DB35A47, file: /no/such/key.asc}]",
+    ];
+
+    for yaml in invalids {
+        assert!(validate_keyring(&parse(yaml)).is_err(), "{yaml}");
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashSet;
+
+use crate::ali::{
+    ManifestServices,
+    ManifestUnits,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+use crate::utils::fs::file_exists;
+
+const MSG: &str = "services validation failed";
+
+const UNIT_SUFFIXES: [&str; 11] = [
+    ".service",
+    ".socket",
+    ".timer",
+    ".target",
+    ".path",
+    ".mount",
+    ".automount",
+    ".swap",
+    ".slice",
+    ".device",
+    ".scope",
+];
+
+const DIRS_SYSTEM: [&str; 2] =
+    ["/usr/lib/systemd/system", "/etc/systemd/system"];
+const DIRS_USER: [&str; 2] = ["/usr/lib/systemd/user", "/etc/systemd/user"];
+
+pub fn validate(services: &ManifestServices) -> Result<(), AliError> {
+    validate_units(&services.system, "system")?;
+    validate_units(&services.user, "user")
+}
+
+/// Checks if unit file for `unit` exists in the target system.
+/// This can only be called after bootstrap, when the packages
+/// providing the units have been installed.
+pub fn unit_installed(
+    unit: &str,
+    global: bool,
+    location: &str,
+) -> Result<(), AliError> {
+    let dirs = if global { DIRS_USER } else { DIRS_SYSTEM };
+
+    let found = unit_files(unit).iter().any(|file| {
+        dirs.iter()
+            .any(|dir| file_exists(format!("{location}{dir}/{file}")))
+    });
+
+    if !found {
+        return Err(AliError::Validation(
+            &codes::E_VAL_SERVICES,
+            format!(
+                "no unit file for {unit} in {location}{}",
+                dirs.join(format!(" or {location}").as_str()),
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_units(units: &ManifestUnits, kind: &str) -> Result<(), AliError> {
+    let mut dups = HashSet::new();
+
+    let all = units
+        .enable
+        .iter()
+        .chain(units.disable.iter())
+        .chain(units.mask.iter());
+
+    for unit in all {
+        if !is_valid_unit(unit) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_SERVICES,
+                format!("{MSG}: bad {kind} unit name {unit}"),
+            ));
+        }
+
+        if !dups.insert(unit_name(unit)) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_SERVICES,
+                format!("{MSG}: {kind} unit {unit} appears more than once"),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_valid_unit(unit: &str) -> bool {
+    !unit.is_empty()
+        && !unit.starts_with('-')
+        && unit
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.@\\".contains(c))
+}
+
+// systemctl assumes .service if unit type is omitted
+fn unit_name(unit: &str) -> String {
+    match UNIT_SUFFIXES.iter().any(|suffix| unit.ends_with(suffix)) {
+        true => unit.to_string(),
+        false => format!("{unit}.service"),
+    }
+}
+
+// Unit file candidates for unit, i.e. its own file,
+// and its template file if unit is a template instance.
+fn unit_files(unit: &str) -> Vec<String> {
+    let name = unit_name(unit);
+    let mut files = vec![name.clone()];
+
+    if let Some((prefix, instance)) = name.split_once('@') {
+        if let Some(dot) = instance.rfind('.') {
+            let suffix = &instance[dot..];
+
+            if dot > 0 {
+                files.push(format!("{prefix}@{suffix}"));
+            }
+        }
+    }
+
+    files
+}
+
+#[test]
+fn test_validate_services() {
+    let services = |units: Vec<&str>| {
+        ManifestServices {
+            system: ManifestUnits {
+                enable: units.into_iter().map(String::from).collect(),
+                disable: vec![],
+                mask: vec![],
+            },
+            user: ManifestUnits::default(),
+        }
+    };
+
+    let valids = vec![
+        vec!["sshd", "systemd-networkd.service", "getty@tty1.service"],
+        vec!["fstrim.timer", "systemd-resolved"],
+    ];
+
+    for units in valids {
+        validate(&services(units)).expect("unexpected error");
+    }
+
+    let invalids = vec![
+        vec![""],
+        vec!["--now"],
+        vec!["sshd; rm -rf /"],
+        vec!["sshd", "sshd.service"],
+    ];
+
+    for units in invalids {
+        assert!(validate(&services(units)).is_err());
+    }
+
+    assert_eq!(vec!["sshd.service"], unit_files("sshd"));
+    assert_eq!(
+        vec!["getty@tty1.service", "getty@.service"],
+        unit_files("getty@tty1")
+    );
+    assert_eq!(vec!["getty@.service"], unit_files("getty@.service"));
+}
@@ -0,0 +1,399 @@
+use std::collections::HashSet;
+
+use colored::Colorize;
+
+use crate::ali::{
+    Manifest,
+    ManifestHashScheme,
+    ManifestUser,
+};
+use crate::errors::{
+    codes,
+    AliError,
+};
+
+const MSG: &str = "user validation failed";
+
+// Neither rootpasswd nor root_account sets a password or locks root
+fn uses_default_root_password(manifest: &Manifest) -> bool {
+    manifest.rootpasswd.is_none()
+        && manifest.root_account.as_ref().is_none_or(|root| {
+            !root.lock
+                && root.password.is_none()
+                && root.password_hash.is_none()
+        })
+}
+
+// Known crypt(3) scheme IDs: MD5, SHA-256, SHA-512, bcrypt,
+// yescrypt, and gost-yescrypt
+const CRYPT_IDS: [&str; 8] = ["1", "5", "6", "2a", "2b", "2y", "y", "gy"];
+
+/// Validates root password from `rootpasswd` or `root_account`
+pub fn validate_root(manifest: &Manifest) -> Result<(), AliError> {
+    if let Some(ref hash) = manifest.rootpasswd {
+        if manifest.root_account.is_some() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!(
+                    "{MSG}: rootpasswd and root_account are mutually exclusive"
+                ),
+            ));
+        }
+
+        if !is_crypt_hash(hash.expose()) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: rootpasswd is not in crypt(3) format"),
+            ));
+        }
+    }
+
+    if uses_default_root_password(manifest) {
+        eprintln!(
+            "{}",
+            "WARN: root password defaults to a well-known password, \
+             set rootpasswd or root_account, \
+             or lock root with root_account: {lock: true}"
+                .yellow()
+        );
+    }
+
+    let Some(ref root) = manifest.root_account else {
+        return Ok(());
+    };
+
+    match (&root.password, &root.password_hash) {
+        (Some(_), Some(_)) => {
+            Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: root has both password and password_hash"),
+            ))
+        }
+        (Some(_), None) | (None, Some(_)) if root.lock => {
+            Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: locked root cannot have password"),
+            ))
+        }
+        (Some(password), None) if password.expose().is_empty() => {
+            Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: empty root password"),
+            ))
+        }
+        (Some(_), None) if root.scheme == ManifestHashScheme::Yescrypt => {
+            Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!(
+                    "{MSG}: yescrypt cannot hash plaintext root password, use password_hash instead"
+                ),
+            ))
+        }
+        (None, Some(hash)) if !is_crypt_hash(hash.expose()) => {
+            Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: root password_hash is not in crypt(3) format"),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn validate(users: &[ManifestUser]) -> Result<(), AliError> {
+    let mut dups = HashSet::new();
+
+    for user in users {
+        let username = &user.username;
+
+        if !is_valid_name(username) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: bad username {username}"),
+            ));
+        }
+
+        if username == "root" {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!(
+                    "{MSG}: root is configured via rootpasswd or root_account"
+                ),
+            ));
+        }
+
+        if !dups.insert(username) {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!("{MSG}: duplicate user {username}"),
+            ));
+        }
+
+        for group in user.groups.iter().flatten() {
+            if !is_valid_name(group) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!("{MSG}: bad group {group} for user {username}"),
+                ));
+            }
+        }
+
+        if let Some(ref shell) = user.shell {
+            if !shell.starts_with('/') || shell.contains(char::is_whitespace) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!(
+                        "{MSG}: shell {shell} for user {username} is not an absolute path"
+                    ),
+                ));
+            }
+        }
+
+        match (&user.password, &user.password_hash) {
+            (Some(_), Some(_)) => {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!(
+                        "{MSG}: user {username} has both password and password_hash"
+                    ),
+                ));
+            }
+            (Some(password), None) if password.expose().is_empty() => {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!("{MSG}: empty password for user {username}"),
+                ));
+            }
+            (None, Some(hash)) if !is_crypt_hash(hash.expose()) => {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!(
+                        "{MSG}: password_hash for user {username} is not in crypt(3) format"
+                    ),
+                ));
+            }
+            _ => {}
+        }
+
+        if let Some(ref home) = user.home_dir {
+            if !home.starts_with('/') || home.contains(char::is_whitespace) {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!(
+                        "{MSG}: home {home} for user {username} is not an absolute path"
+                    ),
+                ));
+            }
+        }
+
+        let has_keys = user.ssh_keys.as_ref().is_some_and(|k| !k.is_empty());
+        if has_keys && user.home().is_none() {
+            return Err(AliError::BadManifest(
+                &codes::E_VAL_USER,
+                format!(
+                    "{MSG}: ssh_keys for user {username} requires home directory"
+                ),
+            ));
+        }
+
+        for key in user.ssh_keys.iter().flatten() {
+            let key_type = key.split_whitespace().next().unwrap_or_default();
+            let known = key_type.starts_with("ssh-")
+                || key_type.starts_with("ecdsa-")
+                || key_type.starts_with("sk-");
+
+            if !known {
+                return Err(AliError::BadManifest(
+                    &codes::E_VAL_USER,
+                    format!(
+                        "{MSG}: bad ssh key type {key_type} for user {username}"
+                    ),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Checks $id$[params$]salt$hash format, without verifying the hash itself
+fn is_crypt_hash(hash: &str) -> bool {
+    let mut fields = hash.split('$');
+
+    if fields.next() != Some("") {
+        return false;
+    }
+
+    match fields.next() {
+        Some(id) if CRYPT_IDS.contains(&id) => {}
+        _ => return false,
+    }
+
+    let fields: Vec<&str> = fields.collect();
+
+    fields.len() >= 2
+        && fields.iter().all(|field| {
+            !field.is_empty()
+                && field.chars().all(|c| {
+                    c.is_ascii_alphanumeric()
+                        || matches!(c, '.' | '/' | '=' | ',')
+                })
+        })
+}
+
+// Follows useradd(8) default NAME_REGEX: [a-z_][a-z0-9_-]*[$]?
+pub(super) fn is_valid_name(name: &str) -> bool {
+    let name = name.strip_suffix('$').unwrap_or(name);
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_lowercase() || c == '_' => {}
+        _ => return false,
+    }
+
+    name.len() <= 32
+        && chars.all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-'
+        })
+}
+
+#[test]
+fn test_validate_users() {
+    use crate::types::secret::Secret;
+
+    let user = |name: &str| {
+        ManifestUser {
+            username: name.to_string(),
+            uid: None,
+            groups: None,
+            shell: None,
+            password: None,
+            password_hash: None,
+            create_home: None,
+            home_dir: None,
+            ssh_keys: None,
+            sudo: false,
+        }
+    };
+
+    for name in ["foo", "_foo", "foo-bar_1", "machine$"] {
+        validate(&[user(name)]).expect("unexpected error");
+    }
+
+    for name in ["", "Foo", "1foo", "foo bar", "foo;rm", "root"] {
+        assert!(validate(&[user(name)]).is_err());
+    }
+
+    assert!(validate(&[user("foo"), user("foo")]).is_err());
+
+    let mut bad_hash = user("foo");
+    bad_hash.password_hash = Some(Secret::from("plaintext"));
+    assert!(validate(&[bad_hash]).is_err());
+
+    let mut bad_key = user("foo");
+    bad_key.ssh_keys = Some(vec!["foo AAAA".to_string()]);
+    assert!(validate(&[bad_key]).is_err());
+
+    let mut good = user("foo");
+    good.password_hash = Some(Secret::from("$6$salt$hash"));
+    good.ssh_keys = Some(vec!["ssh-ed25519 AAAA foo@bar".to_string()]);
+    good.shell = Some("/bin/bash".to_string());
+    validate(&[good.clone()]).expect("unexpected error");
+
+    good.home_dir = Some("/srv/foo".to_string());
+    validate(&[good.clone()]).expect("unexpected error");
+
+    let mut bad_home = good.clone();
+    bad_home.home_dir = Some("srv/foo".to_string());
+    assert!(validate(&[bad_home]).is_err());
+
+    let mut no_home = good;
+    no_home.create_home = Some(false);
+    assert!(validate(&[no_home]).is_err());
+}
+
+#[test]
+fn test_is_crypt_hash() {
+    let valids = vec![
+        "$6$salt$hash",
+        "$6$rounds=5000$salt$hash",
+        "$5$salt$hash",
+        "$y$j9T$salt$hash",
+        "$2b$12$R9h/cIPz0gi.URNNX3kh2OPST9/PgBkqquzi.Ss7KIUgO2t0jWMUW",
+        "$6$mysalt$ToMpSyNM/gQrU9/7eiLTJdkU68LAZ4hdQPSMu9h.Etl2XT9mQ2z7W5IVWcezAbXdw5xmXi86.VkNVSrpg9IBv.",
+    ];
+
+    let invalids = vec![
+        "",
+        "plaintext",
+        "$6$",
+        "$6$salt",
+        "$6$salt$",
+        "$9$salt$hash",
+        "6$salt$hash",
+        "$6$salt$hash with space",
+    ];
+
+    for hash in valids {
+        assert!(is_crypt_hash(hash), "{hash} should be valid");
+    }
+
+    for hash in invalids {
+        assert!(!is_crypt_hash(hash), "{hash} should be invalid");
+    }
+}
+
+#[test]
+fn test_validate_root() {
+    let valids = vec![
+        "rootpasswd: $6$salt$hash",
+        "root_account: {password: archalirs}",
+        "root_account: {password: archalirs, scheme: bcrypt}",
+        "root_account: {password_hash: $y$j9T$salt$hash}",
+        "root_account: {password_hash: $y$j9T$salt$hash, scheme: yescrypt}",
+        "root_account: {lock: true}",
+        "hostname: foo",
+    ];
+
+    let invalids = vec![
+        "rootpasswd: archalirs",
+        "rootpasswd: $6$salt$hash\nroot_account: {lock: true}",
+        "root_account: {password: archalirs, password_hash: $6$salt$hash}",
+        "root_account: {password: archalirs, lock: true}",
+        "root_account: {password: ''}",
+        "root_account: {password: archalirs, scheme: yescrypt}",
+        "root_account: {password_hash: archalirs}",
+    ];
+
+    let manifest = |extra: &str| {
+        let yaml =
+            format!("{extra}\nrootfs: {{device: /dev/vda1, fstype: btrfs}}\n");
+
+        Manifest::from_yaml(&yaml).expect("bad manifest")
+    };
+
+    for yaml in valids {
+        validate_root(&manifest(yaml)).expect("unexpected error");
+    }
+
+    for yaml in invalids {
+        assert!(
+            validate_root(&manifest(yaml)).is_err(),
+            "{yaml} should fail"
+        );
+    }
+
+    // Only manifests without any root password or lock are warned
+    let defaults = vec!["hostname: foo", "root_account: {scheme: bcrypt}"];
+    for yaml in defaults {
+        assert!(uses_default_root_password(&manifest(yaml)), "{yaml}");
+    }
+
+    let configured = vec![
+        "rootpasswd: $6$salt$hash",
+        "root_account: {password: archalirs}",
+        "root_account: {lock: true}",
+    ];
+    for yaml in configured {
+        assert!(!uses_default_root_password(&manifest(yaml)), "{yaml}");
+    }
+}
@@ -4,7 +4,9 @@ use clap::{
     Subcommand,
 };
 
+use crate::constants::defaults;
 use crate::errors::AliError;
+use crate::types::event::EventsFormat;
 use crate::types::stage;
 
 #[derive(Debug, Parser)]
@@ -38,6 +40,9 @@ pub enum Commands {
 
     /// Runs ali-rs hooks
     Hooks(ArgsHooks),
+
+    /// Lists or explains ali-rs error codes
+    Errors(ArgsErrors),
 }
 
 #[derive(Debug, Args)]
@@ -46,6 +51,11 @@ pub struct ArgsApply {
     #[arg(long = "no-validate")]
     pub no_validate: bool,
 
+    /// Do not sync package databases to check that packages exist,
+    /// e.g. without network access. Implied by dry-run
+    #[arg(long = "no-sync-check")]
+    pub no_sync_check: bool,
+
     /// Overwrite existing system block devices (not recommended).
     /// All disks to be used must be declared in manifests,
     /// and existing system devices will not be considered
@@ -64,6 +74,18 @@ pub struct ArgsApply {
     /// and will just print steps to be performed
     #[arg(global = true, short = 'n', default_value_t = false)]
     pub dry_run: bool,
+
+    /// File to write full output of commands to
+    #[arg(long = "log-file", default_value_t = defaults::LOG_FILE.to_string())]
+    pub log_file: String,
+
+    /// Stream progress events in this format, in addition to the report
+    #[arg(long = "events", value_enum)]
+    pub events: Option<EventsFormat>,
+
+    /// Where to stream events: `-` for stdout, or a path like /dev/fd/3
+    #[arg(long = "events-to", default_value = "-")]
+    pub events_to: String,
 }
 
 #[derive(Debug, Args)]
@@ -91,6 +113,21 @@ pub struct ArgsHooks {
     pub dry_run: bool,
 }
 
+#[derive(Debug, Args)]
+pub struct ArgsErrors {
+    #[command(subcommand)]
+    pub command: Option<CommandsErrors>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CommandsErrors {
+    /// Lists all error codes with their exit codes
+    List,
+
+    /// Explains error code, e.g. E-BLK-005
+    Explain { code: String },
+}
+
 fn validate_filename(name: &str) -> Result<String, AliError> {
     if name.is_empty() {
         return Err(AliError::BadArgs(String::from("empty filename")));
@@ -5,6 +5,10 @@ pub mod defaults {
     pub const HOSTNAME: &str = "arch-ali";
     pub const LOCALE_GEN: &str = "en_US.UTF-8 UTF-8";
     pub const LOCALE_CONF: &str = "LANG=en_US.UTF-8";
+    pub const LOG_FILE: &str = "/tmp/ali-rs/ali-rs.log";
+
+    // Under install location, where manifest, plan, and report are kept
+    pub const RECORD_DIR: &str = "/var/log/ali-rs";
 
     const ROOT_PASSWD: &str = "archalirs";
 
@@ -29,6 +33,9 @@ pub mod defaults {
 
 pub const ENV_ALI_LOC: &str = "ALI_LOC";
 
+// Exported after stage-mountpoints for user commands and hooks
+pub const ENV_ALI_KERNEL_CMDLINE: &str = "ALI_KERNEL_CMDLINE";
+
 // Use programs instead of bindings to avoid API dependencies
 pub const REQUIRED_COMMANDS: [&str; 15] = [
     "arch-chroot",
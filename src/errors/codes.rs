@@ -0,0 +1,600 @@
+//! Stable error codes for [`AliError`].
+//!
+//! Codes are part of ali-rs interface: once released, a code keeps
+//! its meaning, and is never reused. Automation should match codes
+//! or exit codes instead of error messages.
+//!
+//! Most variants map to a single code. `BadManifest` and `Validation`
+//! carry the code given where they are constructed, so that
+//! sub-causes like LVM VG validation get their own codes.
+//!
+//! Codes are numbered from 001 within each component,
+//! in the order they were added.
+
+use self::ErrorClass::*;
+use super::AliError;
+use crate::utils::shell::CmdError;
+
+/// Class of errors, each with its own process exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    Args,
+    Manifest,
+    Validation,
+    Command,
+    File,
+    Hook,
+    NotImplemented,
+    Bug,
+}
+
+impl ErrorClass {
+    /// Exit code of ali-rs when failing with this class of errors.
+    ///
+    /// Bad CLI arguments share exit code 2 with argument errors from clap,
+    /// and ali-rs bugs use 70 (`EX_SOFTWARE`) from sysexits.h
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Args => 2,
+            Self::Manifest => 3,
+            Self::Validation => 4,
+            Self::Command => 5,
+            Self::File => 6,
+            Self::Hook => 7,
+            Self::NotImplemented => 8,
+            Self::Bug => 70,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Args => write!(f, "args"),
+            Self::Manifest => write!(f, "manifest"),
+            Self::Validation => write!(f, "validation"),
+            Self::Command => write!(f, "command"),
+            Self::File => write!(f, "file"),
+            Self::Hook => write!(f, "hook"),
+            Self::NotImplemented => write!(f, "not-implemented"),
+            Self::Bug => write!(f, "bug"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub class: ErrorClass,
+    pub summary: &'static str,
+    pub explanation: &'static str,
+}
+
+const fn code(
+    code: &'static str,
+    class: ErrorClass,
+    summary: &'static str,
+    explanation: &'static str,
+) -> ErrorCode {
+    ErrorCode {
+        code,
+        class,
+        summary,
+        explanation,
+    }
+}
+
+pub const E_ARG: ErrorCode = code(
+    "E-ARG-001",
+    Args,
+    "bad cli arguments",
+    "Command-line arguments are invalid or ambiguous, \
+     e.g. a stage given to both --stages and --skip.",
+);
+
+pub const E_MAN: ErrorCode = code(
+    "E-MAN-001",
+    Manifest,
+    "bad manifest",
+    "Manifest is not valid YAML, has unknown keys or bad values, \
+     or has conflicting keys.",
+);
+
+pub const E_CMD_SPAWN: ErrorCode = code(
+    "E-CMD-001",
+    Command,
+    "command failed to spawn",
+    "A command could not be started, usually because \
+     the program is not installed on the live system.",
+);
+
+pub const E_CMD_EXIT: ErrorCode = code(
+    "E-CMD-002",
+    Command,
+    "command exited with error",
+    "A command exited with non-zero status. See commands \
+     in the JSON error and the log file for its output.",
+);
+
+pub const E_FILE_MISSING: ErrorCode = code(
+    "E-FIL-001",
+    File,
+    "no such file",
+    "A file could not be read, e.g. the manifest, \
+     a secret file, or a package-list file.",
+);
+
+pub const E_FILE: ErrorCode = code(
+    "E-FIL-002",
+    File,
+    "file error",
+    "A file or directory could not be created, written, or read.",
+);
+
+pub const E_HOOK_CMD: ErrorCode = code(
+    "E-HOK-001",
+    Hook,
+    "bad hook command",
+    "A hook command is malformed or has bad arguments.",
+);
+
+pub const E_HOOK: ErrorCode = code(
+    "E-HOK-002",
+    Hook,
+    "hook error",
+    "A hook failed while being applied.",
+);
+
+pub const E_NOT_IMPLEMENTED: ErrorCode = code(
+    "E-IMP-001",
+    NotImplemented,
+    "not implemented",
+    "The requested feature is not implemented yet.",
+);
+
+pub const E_BUG: ErrorCode = code(
+    "E-BUG-001",
+    Bug,
+    "ali-rs bug",
+    "ali-rs reached a state it should never reach. \
+     Please report it with the JSON error.",
+);
+
+pub const E_MAN_PACKAGES: ErrorCode = code(
+    "E-MAN-002",
+    Manifest,
+    "package expansion failed",
+    "Package-list files or package groups could not be expanded.",
+);
+
+pub const E_MAN_SECRET: ErrorCode = code(
+    "E-MAN-003",
+    Manifest,
+    "secret resolution failed",
+    "A secret reference (env:, file:, or prompt) could not be resolved, \
+     or resolved to an empty secret.",
+);
+
+pub const E_MAN_CMDLINE: ErrorCode = code(
+    "E-MAN-004",
+    Manifest,
+    "kernel command line failed",
+    "Kernel command line could not be computed from the storage stack, \
+     e.g. hibernation without swap.",
+);
+
+pub const E_VAL_COMMAND: ErrorCode = code(
+    "E-VAL-001",
+    Validation,
+    "required command missing",
+    "A program ali-rs needs is not in PATH of the live system.",
+);
+
+pub const E_VAL_HOSTNAME: ErrorCode = code(
+    "E-VAL-002",
+    Validation,
+    "hostname validation failed",
+    "Hostname, domain, FQDN, or hosts entries are invalid.",
+);
+
+pub const E_VAL_LOCALE: ErrorCode = code(
+    "E-VAL-003",
+    Validation,
+    "locale validation failed",
+    "Locales, keymap, font, or timezone are not available \
+     on the live system.",
+);
+
+pub const E_VAL_USER: ErrorCode = code(
+    "E-VAL-004",
+    Validation,
+    "user validation failed",
+    "Users, groups, root account, or password hashes are invalid.",
+);
+
+pub const E_VAL_SERVICES: ErrorCode = code(
+    "E-VAL-005",
+    Validation,
+    "services validation failed",
+    "A systemd unit is invalid, or has no unit file.",
+);
+
+pub const E_VAL_NETWORK: ErrorCode = code(
+    "E-VAL-006",
+    Validation,
+    "network validation failed",
+    "Network or Wi-Fi configuration is invalid.",
+);
+
+pub const E_VAL_BOOTLOADER: ErrorCode = code(
+    "E-VAL-007",
+    Validation,
+    "bootloader validation failed",
+    "Bootloader or UKI configuration is invalid, e.g. no ESP mounted.",
+);
+
+pub const E_VAL_FILES: ErrorCode = code(
+    "E-VAL-008",
+    Validation,
+    "files validation failed",
+    "A file entry has bad mode, owner, template, or no content.",
+);
+
+pub const E_VAL_PACMAN: ErrorCode = code(
+    "E-VAL-009",
+    Validation,
+    "pacman validation failed",
+    "pacman options, mirrors, or repositories are invalid.",
+);
+
+pub const E_VAL_PACKAGES: ErrorCode = code(
+    "E-VAL-010",
+    Validation,
+    "package validation failed",
+    "Packages do not resolve in sync databases or local repos.",
+);
+
+pub const E_VAL_MKFS: ErrorCode = code(
+    "E-VAL-011",
+    Validation,
+    "mkfs program missing",
+    "There is no mkfs program for a filesystem type in manifest.",
+);
+
+pub const E_BLK_DISK: ErrorCode = code(
+    "E-BLK-001",
+    Validation,
+    "disk validation failed",
+    "A disk does not exist.",
+);
+
+pub const E_BLK_PARTITION: ErrorCode = code(
+    "E-BLK-002",
+    Validation,
+    "partition validation failed",
+    "A partition has bad size, or already exists.",
+);
+
+pub const E_BLK_LUKS: ErrorCode = code(
+    "E-BLK-003",
+    Validation,
+    "luks validation failed",
+    "A LUKS device already exists, or its base device \
+     is missing or cannot have its type.",
+);
+
+pub const E_BLK_PV: ErrorCode = code(
+    "E-BLK-004",
+    Validation,
+    "lvm pv validation failed",
+    "An LVM PV is duplicated or missing.",
+);
+
+pub const E_BLK_VG: ErrorCode = code(
+    "E-BLK-005",
+    Validation,
+    "lvm vg validation failed",
+    "An LVM VG already exists, or its PVs are missing.",
+);
+
+pub const E_BLK_LV: ErrorCode = code(
+    "E-BLK-006",
+    Validation,
+    "lvm lv validation failed",
+    "An LVM LV has bad size, or no VG.",
+);
+
+pub const E_BLK_ROOTFS: ErrorCode = code(
+    "E-BLK-007",
+    Validation,
+    "rootfs validation failed",
+    "There is no top-level fs-ready device for rootfs.",
+);
+
+pub const E_BLK_FS: ErrorCode = code(
+    "E-BLK-008",
+    Validation,
+    "fs validation failed",
+    "A filesystem device is not fs-ready, or is used more than once.",
+);
+
+pub const E_BLK_SWAP: ErrorCode = code(
+    "E-BLK-009",
+    Validation,
+    "swap validation failed",
+    "A swap device is not fs-ready.",
+);
+
+pub const E_BLK_MOUNT: ErrorCode = code(
+    "E-BLK-010",
+    Validation,
+    "mountpoint validation failed",
+    "Mountpoints are duplicated, bad, or not on fs-ready devices.",
+);
+
+pub const E_BLK_DEVICE: ErrorCode = code(
+    "E-BLK-011",
+    Validation,
+    "no such device",
+    "A block device referenced by manifest does not exist, \
+     or has no UUID or other tag that ali-rs needs.",
+);
+
+pub const E_BLK_IN_USE: ErrorCode = code(
+    "E-BLK-012",
+    Validation,
+    "device already in use",
+    "A disk, partition, LUKS base, LVM PV or VG base, or LV name \
+     is already in use on the system, e.g. as a filesystem or by another VG.",
+);
+
+pub const E_BLK_PART_OVERLAP: ErrorCode = code(
+    "E-BLK-013",
+    Validation,
+    "partitions overlap",
+    "An unsized partition, which takes the rest of its disk, \
+     is not the last partition, so later partitions would overlap it.",
+);
+
+pub const E_BLK_LV_EXCEEDS_VG: ErrorCode = code(
+    "E-BLK-014",
+    Validation,
+    "lvs exceed vg",
+    "An unsized LV, which takes all free space (100%FREE) of its VG, \
+     is not the last LV on the VG, so later LVs would not fit in the VG.",
+);
+
+/// Returns all error codes
+pub fn all() -> Vec<&'static ErrorCode> {
+    let mut codes = vec![
+        &E_ARG,
+        &E_MAN,
+        &E_BLK_DEVICE,
+        &E_CMD_SPAWN,
+        &E_CMD_EXIT,
+        &E_FILE_MISSING,
+        &E_FILE,
+        &E_HOOK_CMD,
+        &E_HOOK,
+        &E_NOT_IMPLEMENTED,
+        &E_BUG,
+        &E_MAN_PACKAGES,
+        &E_MAN_SECRET,
+        &E_MAN_CMDLINE,
+        &E_VAL_COMMAND,
+        &E_VAL_HOSTNAME,
+        &E_VAL_LOCALE,
+        &E_VAL_USER,
+        &E_VAL_SERVICES,
+        &E_VAL_NETWORK,
+        &E_VAL_BOOTLOADER,
+        &E_VAL_FILES,
+        &E_VAL_PACMAN,
+        &E_VAL_PACKAGES,
+        &E_VAL_MKFS,
+        &E_BLK_DISK,
+        &E_BLK_PARTITION,
+        &E_BLK_LUKS,
+        &E_BLK_PV,
+        &E_BLK_VG,
+        &E_BLK_LV,
+        &E_BLK_ROOTFS,
+        &E_BLK_FS,
+        &E_BLK_SWAP,
+        &E_BLK_MOUNT,
+        &E_BLK_IN_USE,
+        &E_BLK_PART_OVERLAP,
+        &E_BLK_LV_EXCEEDS_VG,
+    ];
+
+    codes.sort_by_key(|code| code.code);
+
+    codes
+}
+
+/// Looks up error code `code`, case-insensitive
+pub fn find(code: &str) -> Option<&'static ErrorCode> {
+    all()
+        .into_iter()
+        .find(|known| known.code.eq_ignore_ascii_case(code))
+}
+
+/// Returns error code of `err`. Wrapping errors
+/// `InstallError` and `ApplyError` have codes of the errors they wrap.
+pub fn of(err: &AliError) -> &'static ErrorCode {
+    match err {
+        AliError::InstallError { error, .. } => of(error),
+        AliError::ApplyError { error, .. } => of(error),
+        AliError::BadArgs(_) => &E_ARG,
+        AliError::BadManifest(code, _) => code,
+        AliError::Validation(code, _) => code,
+        AliError::NoSuchDevice(_) => &E_BLK_DEVICE,
+        AliError::NoSuchFile(..) => &E_FILE_MISSING,
+        AliError::FileError(..) => &E_FILE,
+        AliError::CmdFailed { error, .. } => {
+            match error {
+                CmdError::ErrSpawn { .. } => &E_CMD_SPAWN,
+                CmdError::ErrRun { .. } => &E_CMD_EXIT,
+            }
+        }
+        AliError::BadHookCmd(_) => &E_HOOK_CMD,
+        AliError::HookError(_) => &E_HOOK,
+        AliError::NotImplemented(_) => &E_NOT_IMPLEMENTED,
+        AliError::AliRsBug(_) => &E_BUG,
+    }
+}
+
+#[test]
+fn test_codes_unique() {
+    use std::collections::HashSet;
+
+    let codes = all();
+    let unique: HashSet<_> = codes.iter().map(|code| code.code).collect();
+
+    assert_eq!(codes.len(), unique.len());
+
+    for code in codes {
+        let parts: Vec<&str> = code.code.split('-').collect();
+
+        assert_eq!(3, parts.len(), "bad code {}", code.code);
+        assert_eq!("E", parts[0]);
+        assert_eq!(3, parts[1].len());
+        assert_eq!(3, parts[2].len());
+    }
+}
+
+#[test]
+fn test_of() {
+    use crate::types::action::{
+        Action,
+        ActionBootstrap,
+    };
+
+    let tests = vec![
+        (AliError::BadArgs("empty filename".into()), "E-ARG-001", 2),
+        (
+            AliError::BadManifest(&E_MAN, "invalid type".into()),
+            "E-MAN-001",
+            3,
+        ),
+        (
+            AliError::BadManifest(
+                &E_BLK_VG,
+                "lvm vg validation failed: vg myvg already exists".into(),
+            ),
+            "E-BLK-005",
+            4,
+        ),
+        (
+            AliError::Validation(
+                &E_VAL_COMMAND,
+                "command fdisk not in path".into(),
+            ),
+            "E-VAL-001",
+            4,
+        ),
+        (AliError::NoSuchDevice("/dev/vdz".into()), "E-BLK-011", 4),
+        (AliError::AliRsBug("unreachable".into()), "E-BUG-001", 70),
+    ];
+
+    for (err, expected, exit_code) in tests {
+        let code = of(&err);
+
+        assert_eq!(expected, code.code, "unexpected code for {err}");
+        assert_eq!(exit_code, code.class.exit_code());
+    }
+
+    // Wrapping errors have codes of errors they wrap
+    let err = AliError::InstallError {
+        error: Box::new(AliError::ApplyError {
+            error: Box::new(AliError::CmdFailed {
+                error: CmdError::ErrRun {
+                    code: Some(1),
+                    stdout: None,
+                    stderr: None,
+                },
+                context: "pacstrap".into(),
+            }),
+            action_failed: Box::new(Action::Bootstrap(
+                ActionBootstrap::InstallBase,
+            )),
+            actions_performed: Vec::new(),
+            commands: Vec::new(),
+        }),
+        stages_performed: Box::default(),
+    };
+
+    assert_eq!(&E_CMD_EXIT, of(&err));
+    assert_eq!(5, err.exit_code());
+    assert_eq!(Some(&E_BLK_DEVICE), find("e-blk-011"));
+}
+
+#[test]
+fn test_codes_reachable() {
+    use std::path::Path;
+
+    // Codes of variants without a code field, and how they are constructed
+    let by_variant = [
+        (&E_ARG, "AliError::BadArgs("),
+        (&E_BLK_DEVICE, "AliError::NoSuchDevice("),
+        (&E_CMD_SPAWN, "CmdError::ErrSpawn {"),
+        (&E_CMD_EXIT, "CmdError::ErrRun {"),
+        (&E_FILE_MISSING, "AliError::NoSuchFile("),
+        (&E_FILE, "AliError::FileError("),
+        (&E_HOOK_CMD, "AliError::BadHookCmd("),
+        (&E_HOOK, "AliError::HookError("),
+        (&E_NOT_IMPLEMENTED, "AliError::NotImplemented("),
+        (&E_BUG, "AliError::AliRsBug("),
+    ];
+
+    // Other codes are attached where errors are constructed,
+    // as `codes::<const>`, so const names are read from this file
+    let mut needles: Vec<(String, String)> = by_variant
+        .iter()
+        .map(|(code, needle)| (code.code.to_string(), needle.to_string()))
+        .collect();
+
+    let mut lines = include_str!("codes.rs").lines();
+    while let Some(line) = lines.next() {
+        let Some(decl) = line.strip_prefix("pub const ") else {
+            continue;
+        };
+        let Some((name, _)) = decl.split_once(": ErrorCode") else {
+            continue;
+        };
+
+        let code = lines.next().unwrap().trim().trim_matches(['"', ',']);
+        if by_variant.iter().all(|(known, _)| known.code != code) {
+            needles.push((code.to_string(), format!("codes::{name}")));
+        }
+    }
+
+    fn read_sources(dir: &Path, sources: &mut String) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+
+            if path.is_dir() {
+                read_sources(&path, sources);
+            } else if !path.ends_with("errors/codes.rs") {
+                sources.push_str(&std::fs::read_to_string(&path).unwrap());
+            }
+        }
+    }
+
+    let mut sources = String::new();
+    let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+    read_sources(&src, &mut sources);
+
+    for code in all() {
+        let (_, needle) = needles
+            .iter()
+            .find(|(known, _)| known == code.code)
+            .unwrap_or_else(|| panic!("no const for code {}", code.code));
+
+        assert!(
+            sources.contains(needle.as_str()),
+            "code {} is never constructed",
+            code.code
+        );
+    }
+}
@@ -1,8 +1,11 @@
+pub mod codes;
+
 use serde_json::json;
 use thiserror::Error;
 
 use crate::types::{
     action,
+    log,
     stage,
 };
 use crate::utils::shell;
@@ -26,11 +29,15 @@ pub enum AliError {
     /// Because some actions may have child actions,
     /// we have to keep both failed action and its
     /// relatives that were successfully performed.
+    ///
+    /// `commands` are logs of commands run by the failed action,
+    /// the last of which is usually the one that failed.
     #[error("ali manifest application error: {error}")]
     ApplyError {
         error: Box<AliError>,
         action_failed: Box<action::Action>,
         actions_performed: Vec<action::Action>,
+        commands: Vec<log::CmdLog>,
     },
 
     #[error("no such file {1}: {0}")]
@@ -42,11 +49,11 @@ pub enum AliError {
     #[error("no such device: {0}")]
     NoSuchDevice(String),
 
-    #[error("bad manifest: {0}")]
-    BadManifest(String),
+    #[error("bad manifest: {1}")]
+    BadManifest(&'static codes::ErrorCode, String),
 
-    #[error("validation error: {0}")]
-    Validation(String),
+    #[error("validation error: {1}")]
+    Validation(&'static codes::ErrorCode, String),
 
     #[error(
         "shell command (context: \"{context}\"), embeddedError: {error:?}"
@@ -73,8 +80,18 @@ pub enum AliError {
 }
 
 impl AliError {
+    /// Returns stable error code of this error
+    pub fn code(&self) -> &'static codes::ErrorCode {
+        codes::of(self)
+    }
+
+    /// Returns process exit code for this error
+    pub fn exit_code(&self) -> i32 {
+        self.code().class.exit_code()
+    }
+
     pub fn to_json_string(&self) -> String {
-        let json_value = match self {
+        let mut json_value = match self {
             Self::InstallError {
                 error,
                 stages_performed,
@@ -88,12 +105,23 @@ impl AliError {
                 error,
                 action_failed,
                 actions_performed,
+                commands,
             } => {
-                json!({
+                let mut json_value = json!({
                     "error": error.to_string(),
                     "actionFailed": action_failed,
                     "actionsPerformed": actions_performed,
-                })
+                    "commands": commands,
+                });
+
+                // Surface the failed command, usually the last one
+                if let Some(failed) = commands.last() {
+                    json_value["exitCode"] = json!(failed.exit_code);
+                    json_value["duration"] = json!(failed.duration);
+                    json_value["stderrTail"] = json!(failed.stderr_tail);
+                }
+
+                json_value
             }
             _ => {
                 json!({
@@ -102,6 +130,8 @@ impl AliError {
             }
         };
 
+        json_value["code"] = json!(self.code().code);
+
         json_value.to_string()
     }
 }
@@ -165,6 +195,13 @@ fn test_json_error() {
             },
         )),
         actions_performed: actions_bootstrap,
+        commands: vec![log::CmdLog {
+            cmd: "foobar".to_string(),
+            exit_code: None,
+            duration: std::time::Duration::ZERO,
+            stdout_tail: Vec::new(),
+            stderr_tail: Vec::new(),
+        }],
     };
 
     println!("ApplyError:");
@@ -177,4 +214,8 @@ fn test_json_error() {
 
     println!("InstallError:");
     println!("{}", err_install.to_json_string());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&err_install.to_json_string()).unwrap();
+    assert_eq!("E-CMD-001", json["code"]);
 }
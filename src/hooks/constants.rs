@@ -48,4 +48,26 @@ pub mod mkinitcpio {
     pub const MKINITCPIO_PRESET_LUKS_ROOT: &str = "@TODO-luks";
     pub const MKINITCPIO_PRESET_LVM_ON_LUKS_ROOT: &str = "@TODO-lvm-on-luks";
     pub const MKINITCPIO_PRESET_LUKS_ON_LVM_ROOT: &str = "@TODO-luks-on-lvm";
+
+    pub const TOKEN_KERNEL: &str = "{{ kernel }}";
+
+    pub const TOKEN_ESP: &str = "{{ esp }}";
+
+    pub const PRESET_UKI: &str = r#"# mkinitcpio preset file for the '{{ kernel }}' package
+# Installed by ali-rs
+ALL_kver="/boot/vmlinuz-{{ kernel }}"
+
+PRESETS=('default' 'fallback')
+
+default_uki="{{ esp }}/EFI/Linux/arch-{{ kernel }}.efi"
+
+fallback_uki="{{ esp }}/EFI/Linux/arch-{{ kernel }}-fallback.efi"
+fallback_options="-S autodetect"
+"#;
+
+    #[test]
+    fn test_tokens() {
+        assert!(PRESET_UKI.contains(TOKEN_KERNEL));
+        assert!(PRESET_UKI.contains(TOKEN_ESP));
+    }
 }
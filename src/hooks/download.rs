@@ -1,4 +1,3 @@
-use super::utils::download;
 use super::{
     wrap_bad_hook_cmd,
     ActionHook,
@@ -9,6 +8,7 @@ use super::{
     KEY_DOWNLOAD_PRINT,
 };
 use crate::errors::AliError;
+use crate::utils::fs;
 
 const USAGE: &str = "<url> <outfile>";
 
@@ -88,15 +88,8 @@ impl Hook for HookDownload {
         _caller: &super::Caller,
         _root_location: &str,
     ) -> Result<super::ActionHook, AliError> {
-        let downloader = download::Downloader::new_from_url(&self.url)?;
-        let bytes = downloader.get_bytes()?;
-
-        if let Err(err) = std::fs::write(&self.outfile, bytes) {
-            return Err(AliError::FileError(
-                err,
-                format!("failed to write downloaded file to {}", self.outfile),
-            ));
-        }
+        let bytes = fs::fetch(&self.url)?;
+        fs::write_file(&self.outfile, bytes)?;
 
         Ok(ActionHook::Download(format!(
             "{} -> {}",
@@ -104,3 +97,33 @@ impl Hook for HookDownload {
         )))
     }
 }
+
+#[test]
+fn test_download_executor() {
+    use std::rc::Rc;
+
+    use crate::utils::executor::{
+        self,
+        Recorder,
+    };
+
+    let recorder = Rc::new(Recorder::default());
+    executor::set_executor(recorder.clone());
+
+    let hook = HookDownload::try_from(
+        "@download https://example.com/motd /alitarget/etc/motd",
+    )
+    .expect("bad hook");
+
+    // Nothing is downloaded or written in dry-run
+    hook.run_hook(&super::Caller::Cli, "/alitarget")
+        .expect("failed to run hook");
+
+    assert_eq!(
+        vec![
+            "fetch https://example.com/motd",
+            "write /alitarget/etc/motd",
+        ],
+        recorder.records(),
+    );
+}
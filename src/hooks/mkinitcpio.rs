@@ -199,6 +199,14 @@ fn apply_mkinitcpio(
     ))
 }
 
+/// Formats mkinitcpio preset for `kernel` that builds UKIs
+/// into `{esp}/EFI/Linux` instead of initramfs images.
+pub(crate) fn fmt_uki_preset(kernel: &str, esp: &str) -> String {
+    PRESET_UKI
+        .replace(TOKEN_KERNEL, kernel)
+        .replace(TOKEN_ESP, esp)
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 enum BootHooksRoot {
     Lvm,
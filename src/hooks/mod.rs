@@ -8,6 +8,8 @@ mod utils;
 mod wrappers;
 
 pub use self::constants::hook_keys::*;
+pub(crate) use self::mkinitcpio::fmt_uki_preset;
+pub(crate) use self::utils::download::Downloader;
 
 use colored::Colorize;
 use serde::{
@@ -15,6 +17,7 @@ use serde::{
     Serialize,
 };
 
+use crate::constants::ENV_ALI_KERNEL_CMDLINE;
 use crate::errors::AliError;
 
 /// All hook actions stores JSON string representation of the hook.
@@ -208,11 +211,23 @@ fn parse_hook(k: &str, cmd: &str) -> Result<Box<dyn Hook>, ParseError> {
     }
 }
 
+/// Expands ali-rs variables (e.g. `$ALI_KERNEL_CMDLINE`) in hook cmd,
+/// if the variables were exported by ali-rs or by the user.
+fn expand_vars(cmd: &str) -> String {
+    let var = ENV_ALI_KERNEL_CMDLINE;
+
+    match std::env::var(var) {
+        Ok(value) => cmd.replace(&format!("${var}"), &value),
+        Err(_) => cmd.to_string(),
+    }
+}
+
 fn parse_validate_caller(
     cmd: &str,
     caller: &Caller,
     root_location: &str,
 ) -> Result<Box<dyn Hook>, AliError> {
+    let cmd = &expand_vars(cmd);
     let (key, _) = extract_key_and_parts(cmd)?;
     let result = parse_hook(&key, cmd);
 
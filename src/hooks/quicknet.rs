@@ -13,7 +13,10 @@ use super::{
     KEY_QUICKNET_PRINT,
 };
 use crate::errors::AliError;
-use crate::utils::shell;
+use crate::utils::{
+    fs,
+    shell,
+};
 
 const USAGE: &str = "interface [dns <DNS_STREAM>]";
 
@@ -190,11 +193,10 @@ fn apply_quicknet(
             let root_location = format!("{root_location}/etc/systemd/network");
             shell::exec("mkdir", &["-p", &root_location])?;
 
-            std::fs::write(&filename, conf_str).map_err(|err| {
-                AliError::FileError(
-                    err,
-                    format!("{hook_key}: writing file {filename}"),
-                )
+            fs::write_file(&filename, conf_str).map_err(|err| {
+                AliError::HookError(format!(
+                    "{hook_key}: writing file {filename}: {err}"
+                ))
             })?;
         }
     }
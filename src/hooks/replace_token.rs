@@ -13,6 +13,7 @@ use super::{
     KEY_REPLACE_TOKEN_PRINT,
 };
 use crate::errors::AliError;
+use crate::utils::fs;
 
 const USAGE: &str = "<TOKEN> <VALUE> <TEMPLATE> [OUTPUT]";
 
@@ -151,17 +152,12 @@ fn apply_replace_token(
 ) -> Result<ActionHook, AliError> {
     let template_string =
         // If the template is a valid remote URL, download it
-        if let Ok(downloader) = download::Downloader::new_from_url(template) {
-            downloader.get_string()
+        if download::Downloader::new_from_url(template).is_ok() {
+            fs::fetch_string(template)
 
         // Otherwise read from file
         } else {
-            std::fs::read_to_string(template).map_err(|err| {
-                AliError::HookError(format!(
-                    "{hook_key}: read template {}: {err}",
-                    template
-                ))
-            })
+            fs::read_file(template)
         }?;
 
     let replaced = r.replace(&template_string)?;
@@ -177,7 +173,7 @@ fn apply_replace_token(
                 _ => format!("/{root_location}/{output}"),
             };
 
-            std::fs::write(output_location, replaced).map_err(|err| {
+            fs::write_file(&output_location, replaced).map_err(|err| {
                 AliError::HookError(format!(
                     "{hook_key}: failed to write to output to {output}: {err}",
                 ))
@@ -14,6 +14,7 @@ use super::{
     KEY_UNCOMMENT_PRINT,
 };
 use crate::errors::AliError;
+use crate::utils::fs;
 
 const USAGE: &str = "<PATTERN> [marker <COMMENT_MARKER=\"#\">] FILE";
 
@@ -191,21 +192,12 @@ fn apply_uncomment(
     };
 
     // Get original from remote location if source is remote URL
-    let original = if let Ok(downloader) =
-        download::Downloader::new_from_url(&uc.source)
-    {
-        downloader.get_string()
+    let original = if download::Downloader::new_from_url(&uc.source).is_ok() {
+        fs::fetch_string(&uc.source)
 
     // Else read from file `target`
     } else {
-        std::fs::read_to_string(&target_file).map_err(|err| {
-            AliError::FileError(
-                err,
-                format!(
-                    "{hook_key}: read original file to uncomment: {target_file}"
-                ),
-            )
-        })
+        fs::read_file(&target_file)
     }?;
 
     let uncommented = match mode {
@@ -224,12 +216,7 @@ fn apply_uncomment(
         }
 
         ModeHook::Normal => {
-            std::fs::write(&target_file, uncommented).map_err(|err| {
-                AliError::FileError(
-                    err,
-                    format!("{hook_key} write uncommented to {target_file}"),
-                )
-            })?;
+            fs::write_file(&target_file, uncommented)?;
         }
     }
 
@@ -81,13 +81,6 @@ impl Downloader {
         }
     }
 
-    pub(crate) fn get_string(&self) -> Result<String, AliError> {
-        match self.proto {
-            Protocol::Http => download_http_string(&self.url),
-            ref other_proto => panic!("unexpected protocol: {other_proto}"),
-        }
-    }
-
     pub(crate) fn get_bytes(&self) -> Result<Vec<u8>, AliError> {
         match self.proto {
             Protocol::Http => download_http_bytes(&self.url),
@@ -109,14 +102,6 @@ fn http_get(url: &str) -> Result<ureq::Response, AliError> {
     Ok(resp)
 }
 
-fn download_http_string(url: &str) -> Result<String, AliError> {
-    let resp = http_get(url)?;
-
-    resp.into_string().map_err(|err| {
-        AliError::HookError(format!("body is not string: {err}"))
-    })
-}
-
 fn download_http_bytes(url: &str) -> Result<Vec<u8>, AliError> {
     let resp = http_get(url)?;
 
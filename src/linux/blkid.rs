@@ -0,0 +1,32 @@
+use crate::errors::AliError;
+use crate::utils::shell;
+
+/// Executes:
+/// ```shell
+/// blkid -s UUID -o value {device}
+/// ```
+pub fn uuid(device: &str) -> Result<String, AliError> {
+    tag_value(device, "UUID")
+}
+
+/// Executes:
+/// ```shell
+/// blkid -s TYPE -o value {device}
+/// ```
+pub fn fs_type(device: &str) -> Result<String, AliError> {
+    tag_value(device, "TYPE")
+}
+
+fn tag_value(device: &str, tag: &str) -> Result<String, AliError> {
+    let output =
+        shell::exec_with_output("blkid", &["-s", tag, "-o", "value", device])?;
+
+    let value = String::from_utf8(output).map_err(|err| {
+        AliError::AliRsBug(format!("blkid output not string: {err}"))
+    })?;
+
+    match value.trim() {
+        "" => Err(AliError::NoSuchDevice(format!("no {tag} for {device}"))),
+        value => Ok(value.to_string()),
+    }
+}
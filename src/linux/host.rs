@@ -0,0 +1,23 @@
+// Host (live system) environment, recorded in reports
+// to compare installs across kernels and ISO releases.
+
+/// Returns kernel release of the host, e.g. `6.7.4-arch1-1`
+pub fn kernel_release() -> Option<String> {
+    read_trimmed("/proc/sys/kernel/osrelease")
+}
+
+/// Returns version of the live ISO, e.g. `2024.02.01`,
+/// which archiso writes to `/version`
+pub fn iso_version() -> Option<String> {
+    read_trimmed("/version")
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let content = content.trim();
+
+    match content.is_empty() {
+        true => None,
+        false => Some(content.to_string()),
+    }
+}
@@ -1,46 +1,69 @@
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 use crate::utils::shell;
 
 // libcryptsetup bindings: https://github.com/stratis-storage/libcryptsetup-rs/
 
+/// Executes:
+/// ```shell
+/// cryptsetup -q luksFormat [--key-file=-] ${{ device }}
+/// ```
+///
+/// If `key` is Some, it is written to cryptsetup stdin
 pub fn format(device: &str, key: Option<&str>) -> Result<(), AliError> {
-    let mut format_cmd = format!("cryptsetup luksFormat {device}");
-
-    if let Some(passphrase) = key {
-        check_passphrase(passphrase)?;
-
-        format_cmd = format!("echo '{passphrase}' | {format_cmd}");
+    match key {
+        Some(_) => cryptsetup(&["-q", "luksFormat", KEY_STDIN, device], key),
+        None => cryptsetup(&["luksFormat", device], key),
     }
-
-    shell::sh_c(&format_cmd)
 }
 
+/// Executes:
+/// ```shell
+/// cryptsetup luksOpen [--key-file=-] ${{ device }} ${{ name }}
+/// ```
+///
+/// If `key` is Some, it is written to cryptsetup stdin
 pub fn open(
     device: &str,
     key: Option<&str>,
     name: &str,
 ) -> Result<(), AliError> {
-    let mut open_cmd = format!("cryptsetup luksOpen {device} {name}");
-
-    if let Some(passphrase) = key {
-        check_passphrase(passphrase)?;
-
-        open_cmd = format!("echo '{passphrase}' | {open_cmd}")
+    match key {
+        Some(_) => cryptsetup(&["luksOpen", KEY_STDIN, device, name], key),
+        None => cryptsetup(&["luksOpen", device, name], key),
     }
-
-    shell::sh_c(&open_cmd)
 }
 
 #[allow(unused)]
 pub fn close(name: &str) -> Result<(), AliError> {
-    let close_cmd = format!("cryptsetup luksClose {name}");
+    shell::exec("cryptsetup", &["luksClose", name])
+}
+
+// Key file `-` is read from stdin until EOF, so no newline is appended
+const KEY_STDIN: &str = "--key-file=-";
+
+// Without key, cryptsetup prompts for passphrase
+fn cryptsetup(args: &[&str], key: Option<&str>) -> Result<(), AliError> {
+    match key {
+        Some(passphrase) => {
+            check_passphrase(passphrase)?;
 
-    shell::sh_c(&close_cmd)
+            shell::exec_with_stdin("cryptsetup", args, passphrase.as_bytes())
+        }
+        None => shell::exec("cryptsetup", args),
+    }
 }
 
 fn check_passphrase(pass: &str) -> Result<(), AliError> {
     match pass {
-        "" => Err(AliError::BadManifest("empty luks passphrase".to_string())),
+        "" => {
+            Err(AliError::BadManifest(
+                &codes::E_MAN,
+                "empty luks passphrase".to_string(),
+            ))
+        }
         _ => Ok(()),
     }
 }
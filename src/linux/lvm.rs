@@ -36,5 +36,5 @@ pub fn create_lv(lv: &ali::ManifestLvmLv) -> Result<(), AliError> {
         None => ("-l", "100%FREE"),
     };
 
-    shell::exec("lvcreate", &[size_flag, size, "-n", &lv.name])
+    shell::exec("lvcreate", &[size_flag, size, &lv.vg, "-n", &lv.name])
 }
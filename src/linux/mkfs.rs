@@ -1,16 +1,50 @@
 use crate::ali::ManifestFs;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 use crate::utils::shell;
 
 /// Executes:
 /// ```shell
 /// mkfs.{fs.fs_type} {fs.fs_opts} {fs.device}
 /// ```
+///
+/// `fs.fs_opts` is split into arguments with shell-like syntax,
+/// but is never interpreted by a shell.
 pub fn create_fs(fs: &ManifestFs) -> Result<(), AliError> {
-    let cmd_mkfs = match &fs.fs_opts {
-        Some(opts) => format!("'mkfs.{} {opts} {}'", fs.fs_type, fs.device),
-        None => format!("'mkfs.{} {}'", fs.fs_type, fs.device),
+    let cmd_mkfs = format!("mkfs.{}", fs.fs_type);
+    let args = split_opts(fs.fs_opts.as_deref(), &fs.device)?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    shell::exec(&cmd_mkfs, &args)
+}
+
+fn split_opts(
+    opts: Option<&str>,
+    device: &str,
+) -> Result<Vec<String>, AliError> {
+    let mut args = match opts {
+        Some(opts) => {
+            shlex::split(opts).ok_or(AliError::BadManifest(
+                &codes::E_MAN,
+                format!("bad fsopts for {device}: {opts}"),
+            ))?
+        }
+        None => Vec::new(),
     };
 
-    shell::sh_c(&cmd_mkfs)
+    args.push(device.to_string());
+
+    Ok(args)
+}
+
+#[test]
+fn test_split_opts() {
+    assert_eq!(
+        vec!["-F", "32", "-L", "MY BOOT", "/dev/vda1"],
+        split_opts(Some("-F 32 -L 'MY BOOT'"), "/dev/vda1").unwrap(),
+    );
+    assert_eq!(vec!["/dev/vda2"], split_opts(None, "/dev/vda2").unwrap());
+    assert!(split_opts(Some("-L 'unterminated"), "/dev/vda1").is_err());
 }
@@ -1,8 +1,11 @@
+pub mod blkid;
 pub mod fdisk;
+pub mod host;
 pub mod luks;
 pub mod lvm;
 pub mod mkfs;
 pub mod mount;
+pub mod pacman;
 pub mod user;
 
 // See linux/block/partition-generic.c
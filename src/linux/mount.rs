@@ -8,14 +8,12 @@ use crate::utils::shell;
 /// ```
 pub fn mount(mnt: &ManifestMountpoint, base: &str) -> Result<(), AliError> {
     let mountpoint = prepend_base(base, &mnt.dest);
-    let cmd_mount = match mnt.mnt_opts {
+    match mnt.mnt_opts {
         Some(ref opts) => {
-            format!("mount -o {opts} {} {mountpoint}", mnt.device)
+            shell::exec("mount", &["-o", opts, &mnt.device, &mountpoint])
         }
-        None => format!("mount {} {mountpoint}", mnt.device),
-    };
-
-    shell::sh_c(&cmd_mount)
+        None => shell::exec("mount", &[&mnt.device, &mountpoint]),
+    }
 }
 
 pub fn prepend_base(base: &str, mountpoint: &str) -> String {
@@ -0,0 +1,105 @@
+use crate::errors::AliError;
+use crate::utils::shell::{
+    self,
+    CmdError,
+};
+
+/// Sync databases are downloaded here to not touch the live system's
+pub const DB_PATH: &str = "/tmp/ali-rs/db";
+
+/// Executes:
+/// ```shell
+/// mkdir -p ${{ DB_PATH }}
+/// pacman --config ${{ conf }} --dbpath ${{ DB_PATH }} -Sy
+/// ```
+pub fn sync_db(conf: &str) -> Result<(), AliError> {
+    shell::exec("mkdir", &["-p", DB_PATH])?;
+    shell::exec_with_output(
+        "pacman",
+        &["--config", conf, "--dbpath", DB_PATH, "-Sy"],
+    )?;
+
+    Ok(())
+}
+
+/// Executes:
+/// ```shell
+/// pacman --config ${{ conf }} --dbpath ${{ DB_PATH }} -Sgq ${{ group }}
+/// ```
+///
+/// Assumes [`sync_db`] was called.
+pub fn group_members(conf: &str, group: &str) -> Result<Vec<String>, AliError> {
+    let output = shell::exec_with_output(
+        "pacman",
+        &["--config", conf, "--dbpath", DB_PATH, "-Sgq", group],
+    )?;
+
+    let output = String::from_utf8(output).map_err(|err| {
+        AliError::AliRsBug(format!("pacman output not string: {err}"))
+    })?;
+
+    Ok(output.lines().map(str::trim).map(String::from).collect())
+}
+
+/// Resolves packages with `pacman -Sp` in sync databases,
+/// returning names of packages not found.
+///
+/// Assumes [`sync_db`] was called.
+pub fn missing_packages(
+    conf: &str,
+    packages: &[&str],
+) -> Result<Vec<String>, AliError> {
+    let mut args = vec![
+        "--config",
+        conf,
+        "--dbpath",
+        DB_PATH,
+        "-Sp",
+        "--print-format",
+        "%n",
+    ];
+    args.extend(packages);
+
+    let err = match shell::exec_with_output("pacman", &args) {
+        Ok(_) => return Ok(Vec::new()),
+        Err(err) => err,
+    };
+
+    if let AliError::CmdFailed {
+        error:
+            CmdError::ErrRun {
+                stderr: Some(ref stderr),
+                ..
+            },
+        ..
+    } = err
+    {
+        let stderr = String::from_utf8_lossy(stderr);
+        let missing = targets_not_found(&stderr);
+
+        if !missing.is_empty() {
+            return Ok(missing.into_iter().map(String::from).collect());
+        }
+    }
+
+    Err(err)
+}
+
+// Parses "error: target not found: foo" lines from pacman stderr
+fn targets_not_found(stderr: &str) -> Vec<&str> {
+    stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("error: target not found: "))
+        .map(str::trim)
+        .collect()
+}
+
+#[test]
+fn test_targets_not_found() {
+    let stderr = r#"error: target not found: foo
+error: target not found: bar-git
+"#;
+
+    assert_eq!(vec!["foo", "bar-git"], targets_not_found(stderr));
+    assert!(targets_not_found("error: failed to init transaction").is_empty());
+}
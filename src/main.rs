@@ -10,12 +10,11 @@ mod utils;
 
 use clap::Parser;
 
-fn main() -> Result<(), errors::AliError> {
+fn main() {
     let args = cli::Cli::parse();
 
     if let Err(err) = run::run(args) {
         eprintln!("{}", err.to_json_string());
+        std::process::exit(err.exit_code());
     }
-
-    Ok(())
 }
@@ -1,15 +1,44 @@
 use std::collections::HashSet;
+use std::path::Path;
+use std::rc::Rc;
+
+use colored::Colorize;
 
 use crate::ali::{
     apply,
+    packages,
+    secrets,
     validation,
     Dm,
+    GrubTarget,
     Manifest,
+    ManifestBootloader,
+    ManifestUki,
+    ManifestUkiSign,
 };
 use crate::cli;
+use crate::constants::{
+    self,
+    defaults,
+};
 use crate::errors::AliError;
-use crate::types::report::Report;
+use crate::linux::host;
+use crate::types::event::EventsFormat;
+use crate::types::report::{
+    Host,
+    Plan,
+    Report,
+    Timing,
+};
 use crate::types::stage;
+use crate::utils::executor::{
+    self,
+    Recorder,
+};
+use crate::utils::{
+    fs,
+    journal,
+};
 
 pub(super) fn run(
     manifest_file: &str,
@@ -51,23 +80,144 @@ pub(super) fn run(
     // help add packages such as lvm2 and btrfs-progs
     let mut manifest = Manifest::from_yaml(&manifest_yaml)?;
 
-    if !args.no_validate {
-        validation::validate(&manifest, install_location, args.overwrite)?;
+    // Merge package lists into pacstraps before validation
+    let manifest_dir =
+        Path::new(manifest_file).parent().unwrap_or(Path::new(""));
+    packages::expand_lists(&mut manifest, manifest_dir)?;
+
+    // Replace secret references with actual secrets before validation
+    secrets::resolve(&mut manifest)?;
+
+    // Block device paths are later used to compute kernel cmdline.
+    // If validation is skipped, paths are collected from manifest alone,
+    // and if that fails too, only root= will be computed.
+    let block_devs = match args.no_validate {
+        false => {
+            validation::validate(&manifest, install_location, args.overwrite)?
+                .block_devs
+        }
+        true => validation::block_devs(&manifest).unwrap_or_default(),
+    };
+
+    // Syncing package databases touches the live system and the network,
+    // so package groups are otherwise left for pacstrap to resolve by name
+    match !args.no_validate && !args.dry_run && !args.no_sync_check {
+        true => {
+            packages::expand_groups(&mut manifest)?;
+            validation::packages_sync(&manifest)?;
+        }
+        false => packages::expand_group_names(&mut manifest, args.dry_run)?,
     }
 
     // Update manifest in some cases
     update_manifest(&mut manifest);
 
-    // Apply manifest to location
+    // Record commands and file writes instead of performing them
+    let recorder = Rc::new(Recorder::default());
+    match args.dry_run {
+        true => {
+            executor::set_executor(recorder.clone());
+        }
+        false => journal::set_log_file(&args.log_file)?,
+    }
+
+    if let Some(EventsFormat::Jsonl) = args.events {
+        journal::set_events(&args.events_to)?;
+    }
+
     let location = super::install_location();
+    let (stages, skipped_stages): (Vec<_>, Vec<_>) = stage::STAGES
+        .into_iter()
+        .partition(|stage| !skip_stages.contains(stage));
+
+    let mut plan = Plan {
+        location: location.clone(),
+        stages: stages.iter().map(ToString::to_string).collect(),
+        skipped_stages: skipped_stages
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        block_devs: block_devs.clone(),
+        kernel_cmdline: None,
+    };
+
+    // Apply manifest to location
     let stages_applied =
-        apply::apply_manifest(&manifest, &location, skip_stages)?;
+        apply::apply_manifest(&manifest, &location, &block_devs, skip_stages)?;
+
+    // Kernel command line is only known after applying
+    plan.kernel_cmdline = std::env::var(constants::ENV_ALI_KERNEL_CMDLINE).ok();
+
+    let actions = journal::actions();
+    let host = Host {
+        kernel: host::kernel_release(),
+        iso_version: host::iso_version(),
+    };
 
-    Ok(Report {
+    let report = Report {
         location,
         summary: stages_applied,
         duration: start.elapsed(),
-    })
+        timing: Timing::new(journal::stages(), &actions, host),
+        actions,
+    };
+
+    // The installation is done, so failing to keep records only warns
+    if let Err(err) = write_records(&manifest, &plan, &report) {
+        eprintln!(
+            "{}",
+            format!("WARN: failed to write records: {err}").yellow()
+        );
+    }
+
+    // Keep stdout JSONL-only when events are streamed there
+    let on_stdout = journal::events_on_stdout();
+    for record in recorder.records() {
+        match on_stdout {
+            true => eprintln!("{record}"),
+            false => println!("{record}"),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Writes the effective manifest, with secrets redacted, the plan,
+/// and the report to [`defaults::RECORD_DIR`] under install location,
+/// so that it can later be told how the system was built.
+fn write_records(
+    manifest: &Manifest,
+    plan: &Plan,
+    report: &Report,
+) -> Result<(), AliError> {
+    let dir = format!("{}{}", plan.location, defaults::RECORD_DIR);
+
+    let manifest_yaml = serde_yaml::to_string(manifest).map_err(|err| {
+        AliError::AliRsBug(format!("failed to serialize manifest: {err}"))
+    })?;
+    let plan_json = serde_json::to_string_pretty(plan).map_err(|err| {
+        AliError::AliRsBug(format!("failed to serialize plan: {err}"))
+    })?;
+    let report_json =
+        serde_json::to_string_pretty(&report.to_json()).map_err(|err| {
+            AliError::AliRsBug(format!("failed to serialize report: {err}"))
+        })?;
+
+    // Manifest may contain sensitive data other than secrets,
+    // so records are only readable by root
+    let records = [
+        ("manifest.yaml", manifest_yaml),
+        ("plan.json", plan_json),
+        ("report.json", report_json),
+    ];
+
+    for (name, content) in records {
+        let path = format!("{dir}/{name}");
+        fs::write_file(&path, content)?;
+        fs::set_mode(&path, 0o600)?;
+    }
+
+    fs::set_mode(&dir, 0o700)
 }
 
 // Update manifest to suit the manifest
@@ -107,6 +257,60 @@ fn update_manifest(manifest: &mut Manifest) {
         _ => {}
     }
 
+    // Update manifest.pacstraps with GRUB packages
+    if let Some(ManifestBootloader::Grub(ref grub)) = manifest.bootloader {
+        let mut packages = vec!["grub".to_string()];
+        if grub.target == GrubTarget::Uefi {
+            packages.push("efibootmgr".to_string());
+        }
+
+        match manifest.pacstraps.as_mut() {
+            Some(pacstraps) => pacstraps.extend(packages),
+            None => manifest.pacstraps = Some(HashSet::from_iter(packages)),
+        }
+    }
+
+    // Update manifest.pacstraps with sbctl for signing UKIs in chroot
+    if let Some(ManifestUki {
+        sign: Some(ManifestUkiSign::Sbctl),
+        ..
+    }) = manifest.uki
+    {
+        let sbctl = "sbctl".to_string();
+        match manifest.pacstraps.as_mut() {
+            Some(pacstraps) => {
+                pacstraps.insert(sbctl);
+            }
+            None => manifest.pacstraps = Some(HashSet::from([sbctl])),
+        }
+    }
+
+    // Update manifest.pacstraps with sudo if any user is sudoer
+    let has_sudo = manifest
+        .users
+        .as_ref()
+        .is_some_and(|users| users.iter().any(|user| user.sudo));
+
+    if has_sudo {
+        let sudo = "sudo".to_string();
+        match manifest.pacstraps.as_mut() {
+            Some(pacstraps) => {
+                pacstraps.insert(sudo);
+            }
+            None => manifest.pacstraps = Some(HashSet::from([sudo])),
+        }
+    }
+
+    // Update manifest.pacstraps with network backend packages
+    if let Some(ref network) = manifest.network {
+        let packages = network.packages().into_iter().map(String::from);
+
+        match manifest.pacstraps.as_mut() {
+            Some(pacstraps) => pacstraps.extend(packages),
+            None => manifest.pacstraps = Some(HashSet::from_iter(packages)),
+        }
+    }
+
     // Find a manifest LVM device
     if let Some(ref dms) = manifest.device_mappers {
         for dm in dms {
@@ -133,3 +337,87 @@ fn update_manifest(manifest: &mut Manifest) {
         _ => {}
     }
 }
+
+#[test]
+fn test_write_records() {
+    let location = std::env::temp_dir().join("ali-rs-test-records");
+    let _ = std::fs::remove_dir_all(&location);
+
+    let yaml = r#"
+hostname: foo
+rootpasswd: mysupersecret
+dm:
+  - type: luks
+    device: /dev/vda2
+    name: crypty
+    key: myluksphrase
+rootfs:
+  device: /dev/mapper/crypty
+  fstype: btrfs
+network:
+  wifi:
+    networks:
+      - ssid: home
+        passphrase: mywifiphrase
+"#;
+
+    let mut manifest = Manifest::from_yaml(yaml).expect("bad manifest");
+    update_manifest(&mut manifest);
+
+    let plan = Plan {
+        location: location.to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let report = Report {
+        location: plan.location.clone(),
+        summary: Box::default(),
+        duration: std::time::Duration::ZERO,
+        actions: Vec::new(),
+        timing: Timing::default(),
+    };
+
+    write_records(&manifest, &plan, &report).expect("failed to write records");
+
+    let dir = format!("{}{}", plan.location, defaults::RECORD_DIR);
+    let read = |name: &str| {
+        std::fs::read_to_string(format!("{dir}/{name}"))
+            .unwrap_or_else(|_| panic!("no record {name}"))
+    };
+
+    let (manifest_yaml, plan_json, report_json) = (
+        read("manifest.yaml"),
+        read("plan.json"),
+        read("report.json"),
+    );
+
+    let mode = |path: &str| {
+        use std::os::unix::fs::PermissionsExt;
+
+        let meta = std::fs::metadata(path).expect("no record");
+        meta.permissions().mode() & 0o777
+    };
+    assert_eq!(0o700, mode(&dir));
+    for name in ["manifest.yaml", "plan.json", "report.json"] {
+        assert_eq!(0o600, mode(&format!("{dir}/{name}")), "{name}");
+    }
+
+    std::fs::remove_dir_all(&location).expect("failed to remove records");
+
+    assert!(!manifest_yaml.contains("mysupersecret"));
+    assert!(!manifest_yaml.contains("myluksphrase"));
+    assert!(!manifest_yaml.contains("mywifiphrase"));
+    assert!(manifest_yaml.contains("home"));
+    assert!(manifest_yaml.contains("btrfs-progs"));
+
+    let recorded = Manifest::from_yaml(&manifest_yaml)
+        .expect("recorded manifest is not a manifest");
+    assert_eq!(manifest.hostname, recorded.hostname);
+
+    // Redacted secrets must not be used as actual secrets on replay
+    let mut recorded = recorded;
+    assert!(crate::ali::secrets::resolve(&mut recorded).is_err());
+
+    serde_json::from_str::<serde_json::Value>(&plan_json).expect("bad plan");
+    serde_json::from_str::<serde_json::Value>(&report_json)
+        .expect("bad report");
+}
@@ -0,0 +1,36 @@
+use crate::cli;
+use crate::errors::{
+    codes,
+    AliError,
+};
+
+pub(super) fn run(args: cli::ArgsErrors) -> Result<(), AliError> {
+    match args.command {
+        None | Some(cli::CommandsErrors::List) => {
+            for code in codes::all() {
+                println!(
+                    "{}\texit {}\t{}",
+                    code.code,
+                    code.class.exit_code(),
+                    code.summary
+                );
+            }
+
+            Ok(())
+        }
+        Some(cli::CommandsErrors::Explain { code }) => explain(&code),
+    }
+}
+
+fn explain(code: &str) -> Result<(), AliError> {
+    let known = codes::find(code)
+        .ok_or(AliError::BadArgs(format!("no such error code {code}")))?;
+
+    println!("{}: {}", known.code, known.summary);
+    println!("class: {}", known.class);
+    println!("exit code: {}", known.class.exit_code());
+    println!();
+    println!("{}", known.explanation);
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 pub mod apply;
+pub mod errors;
 pub mod hooks;
 pub mod validate;
 
@@ -8,6 +9,7 @@ use colored::Colorize;
 
 use crate::constants::defaults;
 use crate::errors::AliError;
+use crate::utils::journal;
 use crate::{
     cli,
     constants,
@@ -25,18 +27,24 @@ pub fn run(cli_args: cli::Cli) -> Result<(), AliError> {
         // Apply manifest in full
         Some(cli::Commands::Apply(args_apply)) => {
             if !linux::user::is_root() {
-                println!("{}", "WARN: running as non-root user".yellow())
+                eprintln!("{}", "WARN: running as non-root user".yellow())
             }
 
-            match apply::run(&cli_args.manifest, &new_root_location, args_apply)
-            {
-                Err(err) => Err(err),
-                Ok(report) => Ok(println!("{}", report.to_json_string())),
+            let report =
+                apply::run(&cli_args.manifest, &new_root_location, args_apply)?;
+
+            // Report must not break JSON lines events on stdout
+            journal::report(&report);
+            if !journal::events_on_stdout() {
+                println!("{}", report.to_json_string());
             }
+
+            Ok(())
         }
         Some(cli::Commands::Hooks(args_hooks)) => {
             hooks::run(&cli_args.manifest, args_hooks)
         }
+        Some(cli::Commands::Errors(args_errors)) => errors::run(args_errors),
     }
 }
 
@@ -1,4 +1,8 @@
+use std::path::Path;
+
 use crate::ali::{
+    packages,
+    secrets,
     validation,
     Manifest,
 };
@@ -13,7 +17,11 @@ pub(super) fn run(
     let manifest_yaml = std::fs::read_to_string(manifest_file)
         .map_err(|err| AliError::FileError(err, manifest_file.to_string()))?;
 
-    let manifest = Manifest::from_yaml(&manifest_yaml)?;
+    let mut manifest = Manifest::from_yaml(&manifest_yaml)?;
+    let manifest_dir =
+        Path::new(manifest_file).parent().unwrap_or(Path::new(""));
+    packages::expand_lists(&mut manifest, manifest_dir)?;
+    secrets::resolve(&mut manifest)?;
 
     // @TODO: print validation result
     let _ = validation::validate(&manifest, install_location, true)?;
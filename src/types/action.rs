@@ -17,6 +17,7 @@ pub enum Action {
     Bootstrap(ActionBootstrap),
     Routines(ActionRoutine),
     ChrootAli(ActionChrootAli),
+    Bootloader(ActionBootloader),
     ChrootUser(ActionChrootUser),
     UserPostInstall(ActionPostInstallUser),
 }
@@ -111,6 +112,42 @@ pub enum ActionBootstrap {
 
     #[serde(rename = "installPackages")]
     InstallPackages { packages: HashSet<String> },
+
+    #[serde(rename = "writePacmanConf")]
+    WritePacmanConf(String),
+
+    #[serde(rename = "writeMirrorlist")]
+    WriteMirrorlist(String),
+
+    #[serde(rename = "bindMountCache")]
+    BindMountCache { src: String, dst: String },
+
+    #[serde(rename = "unmountCache")]
+    UnmountCache(String),
+
+    #[serde(rename = "installLocalPackages")]
+    InstallLocalPackages { packages: Vec<String> },
+
+    #[serde(rename = "installPhase")]
+    InstallPhase {
+        phase: String,
+        packages: HashSet<String>,
+    },
+
+    #[serde(rename = "initKeyring")]
+    InitKeyring,
+
+    #[serde(rename = "populateKeyring")]
+    PopulateKeyring,
+
+    #[serde(rename = "upgradeKeyring")]
+    UpgradeKeyring,
+
+    #[serde(rename = "importKey")]
+    ImportKey(String),
+
+    #[serde(rename = "importTargetKey")]
+    ImportTargetKey(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,14 +156,29 @@ pub enum ActionRoutine {
     #[serde(rename = "setHostname")]
     SetHostname,
 
+    #[serde(rename = "writeHosts")]
+    WriteHosts,
+
+    #[serde(rename = "writeNetworkConfig")]
+    WriteNetworkConfig(String),
+
     #[serde(rename = "genfstab")]
     GenFstab,
 
     #[serde(rename = "localeConf")]
     LocaleConf,
 
+    #[serde(rename = "vconsoleConf")]
+    VconsoleConf,
+
+    #[serde(rename = "x11KeyboardConf")]
+    X11KeyboardConf,
+
     #[serde(rename = "rootPasswd")]
     RootPasswd,
+
+    #[serde(rename = "lockRoot")]
+    LockRoot,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +189,79 @@ pub enum ActionChrootAli {
 
     #[serde(rename = "localeGen")]
     LocaleGen,
+
+    #[serde(rename = "createGroup")]
+    CreateGroup(String),
+
+    #[serde(rename = "createUser")]
+    CreateUser(String),
+
+    #[serde(rename = "setUserPassword")]
+    SetUserPassword(String),
+
+    #[serde(rename = "writeAuthorizedKeys")]
+    WriteAuthorizedKeys(String),
+
+    #[serde(rename = "enableSudoWheel")]
+    EnableSudoWheel,
+
+    #[serde(rename = "enableUnit")]
+    EnableUnit { unit: String, global: bool },
+
+    #[serde(rename = "disableUnit")]
+    DisableUnit { unit: String, global: bool },
+
+    #[serde(rename = "maskUnit")]
+    MaskUnit { unit: String, global: bool },
+
+    #[serde(rename = "enableNetctlProfile")]
+    EnableNetctlProfile(String),
+
+    #[serde(rename = "writeFile")]
+    WriteFile(String),
+
+    #[serde(rename = "installPhase")]
+    InstallPhase {
+        phase: String,
+        packages: HashSet<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+
+pub enum ActionBootloader {
+    #[serde(rename = "writeKernelCmdline")]
+    WriteKernelCmdline(String),
+
+    #[serde(rename = "writeUkiPreset")]
+    WriteUkiPreset(String),
+
+    #[serde(rename = "buildUki")]
+    BuildUki { kernel: String },
+
+    #[serde(rename = "createSbctlKeys")]
+    CreateSbctlKeys,
+
+    #[serde(rename = "signUki")]
+    SignUki(String),
+
+    #[serde(rename = "bootctlInstall")]
+    BootctlInstall { esp: String },
+
+    #[serde(rename = "writeLoaderConf")]
+    WriteLoaderConf(String),
+
+    #[serde(rename = "writeLoaderEntry")]
+    WriteLoaderEntry(String),
+
+    #[serde(rename = "grubInstall")]
+    GrubInstall { target: String },
+
+    #[serde(rename = "writeDefaultGrub")]
+    WriteDefaultGrub { cmdline: String },
+
+    #[serde(rename = "grubMkconfig")]
+    GrubMkconfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,6 +284,48 @@ pub enum ActionPostInstallUser {
     Hook(hooks::ActionHook),
 }
 
+impl From<ActionMountpoints> for Action {
+    fn from(action: ActionMountpoints) -> Self {
+        Self::Mountpoints(action)
+    }
+}
+
+impl From<ActionBootstrap> for Action {
+    fn from(action: ActionBootstrap) -> Self {
+        Self::Bootstrap(action)
+    }
+}
+
+impl From<ActionRoutine> for Action {
+    fn from(action: ActionRoutine) -> Self {
+        Self::Routines(action)
+    }
+}
+
+impl From<ActionChrootAli> for Action {
+    fn from(action: ActionChrootAli) -> Self {
+        Self::ChrootAli(action)
+    }
+}
+
+impl From<ActionBootloader> for Action {
+    fn from(action: ActionBootloader) -> Self {
+        Self::Bootloader(action)
+    }
+}
+
+impl From<ActionChrootUser> for Action {
+    fn from(action: ActionChrootUser) -> Self {
+        Self::ChrootUser(action)
+    }
+}
+
+impl From<ActionPostInstallUser> for Action {
+    fn from(action: ActionPostInstallUser) -> Self {
+        Self::UserPostInstall(action)
+    }
+}
+
 #[ignore = "Ignored because just dummy print JSON"]
 #[test]
 // Dummy function to see JSON result
@@ -214,11 +381,21 @@ fn test_json_stages() {
                 .to_string(),
         )];
 
+    let actions_bootloader = vec![
+        ActionBootloader::BootctlInstall {
+            esp: "/boot".to_string(),
+        },
+        ActionBootloader::WriteLoaderEntry(
+            "/boot/loader/entries/arch.conf".to_string(),
+        ),
+    ];
+
     let stages = StageActions {
         mountpoints: actions_mountpoints.clone(),
         bootstrap: actions_bootstrap.clone(),
         routines: actions_routines.clone(),
         chroot_ali: actions_chroot_ali.clone(),
+        bootloader: actions_bootloader.clone(),
         chroot_user: actions_chroot_user.clone(),
         postinstall_user: actions_postinstall_user.clone(),
     };
@@ -227,6 +404,8 @@ fn test_json_stages() {
         summary: Box::new(stages),
         duration: std::time::Duration::from_secs(20),
         location: "dummy".to_string(),
+        actions: Vec::new(),
+        timing: Default::default(),
     };
 
     println!("{}", report.to_json_string());
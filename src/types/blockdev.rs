@@ -7,7 +7,10 @@ use serde::{
 use std::collections::LinkedList;
 
 use crate::ali;
-use crate::errors::AliError;
+use crate::errors::{
+    codes,
+    AliError,
+};
 
 #[derive(
     Debug, PartialEq, Eq, std::hash::Hash, Clone, Serialize, Deserialize,
@@ -87,7 +90,10 @@ pub fn vg_lv_name(lv: &ali::ManifestLvmLv) -> (String, String) {
 
 pub fn parse_human_bytes(s: &str) -> Result<bytes::Bytes, AliError> {
     (s.to_lowercase()).parse::<bytes::Bytes>().map_err(|err| {
-        AliError::BadManifest(format!("bad byte unit string {s}: {err}"))
+        AliError::BadManifest(
+            &codes::E_MAN,
+            format!("bad byte unit string {s}: {err}"),
+        )
     })
 }
 
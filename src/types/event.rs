@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use super::action::Action;
+use super::log;
+
+/// Formats of progress events
+#[derive(Debug, Clone, PartialEq, Eq, ValueEnum)]
+pub enum EventsFormat {
+    /// One JSON object per line
+    Jsonl,
+}
+
+/// Progress event emitted while applying manifest.
+///
+/// Actions within a stage are numbered by `index`, so that
+/// `actionStart` can be matched with `actionEnd` or `actionFailed`.
+/// `actionStart` is emitted when the action runs its first command,
+/// or right before `actionEnd` if it runs none.
+/// `report` is the last event, with the same keys as the JSON report.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event")]
+pub enum Event {
+    #[serde(rename = "stageStart")]
+    StageStart { stage: String },
+
+    #[serde(rename = "stageEnd")]
+    StageEnd { stage: String, duration: Duration },
+
+    #[serde(rename = "stageFailed")]
+    StageFailed { stage: String, error: String },
+
+    #[serde(rename = "actionStart")]
+    ActionStart { stage: String, index: usize },
+
+    #[serde(rename = "command")]
+    Command {
+        stage: String,
+        index: usize,
+        cmd: String,
+    },
+
+    #[serde(rename = "actionEnd")]
+    ActionEnd {
+        stage: String,
+        index: usize,
+        action: Action,
+        duration: Duration,
+    },
+
+    #[serde(rename = "actionFailed")]
+    ActionFailed {
+        stage: String,
+        index: usize,
+        action: Action,
+        error: String,
+    },
+
+    // Final report, emitted after all stages were applied
+    #[serde(rename = "report")]
+    Report {
+        #[serde(flatten)]
+        report: serde_json::Map<String, serde_json::Value>,
+    },
+}
+
+impl Event {
+    /// Formats event as one JSON line, with Unix timestamp in milliseconds
+    pub fn to_json_line(&self) -> String {
+        let timestamp = log::unix_millis_ago(Duration::ZERO);
+
+        let mut json_value =
+            serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+        json_value["timestamp"] = serde_json::json!(timestamp);
+
+        format!("{json_value}\n")
+    }
+}
+
+#[test]
+fn test_event_json() {
+    use super::action::ActionRoutine;
+
+    let event = Event::ActionEnd {
+        stage: "stage-routines".to_string(),
+        index: 0,
+        action: Action::Routines(ActionRoutine::GenFstab),
+        duration: Duration::from_millis(1500),
+    };
+
+    let line = event.to_json_line();
+    let json_value: serde_json::Value =
+        serde_json::from_str(&line).expect("bad json line");
+
+    assert!(line.ends_with('\n'));
+    assert_eq!(1, line.lines().count());
+    assert_eq!("actionEnd", json_value["event"]);
+    assert_eq!("stage-routines", json_value["stage"]);
+    assert_eq!("genfstab", json_value["action"]);
+    assert!(json_value["timestamp"].is_u64());
+}
+
+#[test]
+fn test_report_event_json() {
+    let report = serde_json::json!({
+        "elapsedTime": {"secs": 1, "nanos": 0},
+        "actions": [],
+    });
+
+    let event = Event::Report {
+        report: report.as_object().cloned().unwrap(),
+    };
+
+    let json_value: serde_json::Value =
+        serde_json::from_str(&event.to_json_line()).expect("bad json line");
+
+    assert_eq!("report", json_value["event"]);
+    assert_eq!(report["elapsedTime"], json_value["elapsedTime"]);
+    assert!(json_value["actions"].is_array());
+    assert!(json_value["timestamp"].is_u64());
+}
@@ -0,0 +1,92 @@
+use std::time::{
+    Duration,
+    SystemTime,
+    UNIX_EPOCH,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::action::Action;
+
+/// Number of output lines kept in command logs and error reports
+pub const TAIL_LINES: usize = 20;
+
+/// Log of a command run by ali-rs.
+///
+/// Only the last [`TAIL_LINES`] lines of output are kept,
+/// full output is written to the log file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CmdLog {
+    pub cmd: String,
+
+    #[serde(rename = "exitCode")]
+    pub exit_code: Option<i32>,
+
+    pub duration: Duration,
+
+    #[serde(rename = "stdoutTail")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stdout_tail: Vec<String>,
+
+    #[serde(rename = "stderrTail")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stderr_tail: Vec<String>,
+}
+
+/// Action performed, with logs of commands it ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionLog {
+    pub action: Action,
+
+    // Unix timestamp in milliseconds
+    pub start: u64,
+
+    pub duration: Duration,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<CmdLog>,
+}
+
+/// Stage applied, or failed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageLog {
+    pub stage: String,
+
+    // Unix timestamp in milliseconds
+    pub start: u64,
+
+    pub duration: Duration,
+
+    #[serde(default)]
+    pub failed: bool,
+}
+
+/// Returns Unix timestamp in milliseconds of `duration` ago
+pub fn unix_millis_ago(duration: Duration) -> u64 {
+    let time = SystemTime::now()
+        .checked_sub(duration)
+        .unwrap_or(UNIX_EPOCH);
+
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Returns the last `n` lines of `output` as lossy UTF-8 strings
+pub fn tail(output: &[u8], n: usize) -> Vec<String> {
+    let output = String::from_utf8_lossy(output);
+    let lines: Vec<&str> = output.lines().collect();
+    let skip = lines.len().saturating_sub(n);
+
+    lines.into_iter().skip(skip).map(String::from).collect()
+}
+
+#[test]
+fn test_tail() {
+    assert_eq!(vec!["b", "c"], tail(b"a\nb\nc\n", 2));
+    assert_eq!(vec!["a", "b"], tail(b"a\nb", 5));
+    assert!(tail(b"", 5).is_empty());
+}
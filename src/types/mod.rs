@@ -1,4 +1,7 @@
 pub mod action;
 pub mod blockdev;
+pub mod event;
+pub mod log;
 pub mod report;
+pub mod secret;
 pub mod stage;
@@ -1,19 +1,122 @@
+use std::time::Duration;
+
+use serde::Serialize;
 use serde_json::json;
 
+use super::blockdev::BlockDevPaths;
+use super::log::{
+    ActionLog,
+    StageLog,
+};
 use super::stage::StageActions;
 
+/// Number of slowest actions listed in [`Timing`]
+pub const SLOWEST_ACTIONS: usize = 5;
+
 #[derive(Debug)]
 pub struct Report {
     pub location: String,
     pub summary: Box<StageActions>,
     pub duration: std::time::Duration,
+
+    // Actions in order, with tails of their command output
+    pub actions: Vec<ActionLog>,
+
+    pub timing: Timing,
+}
+
+/// Plan resolved from manifest and CLI arguments before applying
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Plan {
+    pub location: String,
+
+    // Stages to apply, in order
+    pub stages: Vec<String>,
+
+    #[serde(rename = "skippedStages")]
+    pub skipped_stages: Vec<String>,
+
+    #[serde(rename = "blockDevs")]
+    pub block_devs: BlockDevPaths,
+
+    #[serde(rename = "kernelCmdline")]
+    pub kernel_cmdline: Option<String>,
+}
+
+/// Machine-readable timing summary of an apply run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Timing {
+    pub stages: Vec<StageLog>,
+
+    // Slowest actions, without their command logs
+    #[serde(rename = "slowestActions")]
+    pub slowest_actions: Vec<ActionLog>,
+
+    // Total time spent in pacstrap commands
+    pub pacstrap: Duration,
+
+    // Total time spent in arch-chroot commands
+    pub chroot: Duration,
+
+    pub host: Host,
+}
+
+/// Environment ali-rs was run in
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Host {
+    pub kernel: Option<String>,
+
+    #[serde(rename = "isoVersion")]
+    pub iso_version: Option<String>,
+}
+
+impl Timing {
+    pub fn new(
+        stages: Vec<StageLog>,
+        actions: &[ActionLog],
+        host: Host,
+    ) -> Self {
+        let mut slowest_actions: Vec<ActionLog> = actions
+            .iter()
+            .map(|log| {
+                ActionLog {
+                    commands: Vec::new(),
+                    ..log.clone()
+                }
+            })
+            .collect();
+
+        slowest_actions.sort_by_key(|log| std::cmp::Reverse(log.duration));
+        slowest_actions.truncate(SLOWEST_ACTIONS);
+
+        let commands = actions.iter().flat_map(|log| log.commands.iter());
+        let (mut pacstrap, mut chroot) = (Duration::ZERO, Duration::ZERO);
+
+        for cmd in commands {
+            match cmd.cmd.split_whitespace().next() {
+                Some("pacstrap") => pacstrap += cmd.duration,
+                Some("arch-chroot") => chroot += cmd.duration,
+                _ => {}
+            }
+        }
+
+        Self {
+            stages,
+            slowest_actions,
+            pacstrap,
+            chroot,
+            host,
+        }
+    }
 }
 
 impl Report {
     pub fn to_json(&self) -> serde_json::Value {
         json!({
             "summary": self.summary,
-            "elaspedTime": self.duration,
+            "elapsedTime": self.duration,
+            "actions": self.actions,
+            "timing": self.timing,
         })
     }
 
@@ -31,3 +134,62 @@ impl ToString for Report {
 pub struct ValidationReport {
     pub block_devs: super::blockdev::BlockDevPaths,
 }
+
+#[test]
+fn test_timing() {
+    use super::action::{
+        Action,
+        ActionBootstrap,
+        ActionMountpoints,
+        ActionRoutine,
+    };
+    use super::log::CmdLog;
+
+    let cmd = |cmd: &str, secs: u64| {
+        CmdLog {
+            cmd: cmd.to_string(),
+            exit_code: Some(0),
+            duration: Duration::from_secs(secs),
+            stdout_tail: Vec::new(),
+            stderr_tail: Vec::new(),
+        }
+    };
+
+    let action = |action: Action, secs: u64, commands: Vec<CmdLog>| {
+        ActionLog {
+            action,
+            start: 0,
+            duration: Duration::from_secs(secs),
+            commands,
+        }
+    };
+
+    let actions = vec![
+        action(
+            Action::Mountpoints(ActionMountpoints::MkdirRootFs),
+            1,
+            vec![cmd("mkdir -p /alitarget", 1)],
+        ),
+        action(
+            Action::Bootstrap(ActionBootstrap::InstallBase),
+            300,
+            vec![cmd("pacstrap -K /alitarget base", 300)],
+        ),
+        action(
+            Action::Routines(ActionRoutine::GenFstab),
+            12,
+            vec![
+                cmd("arch-chroot /alitarget locale-gen", 7),
+                cmd("arch-chroot /alitarget hwclock --systohc", 5),
+            ],
+        ),
+    ];
+
+    let timing = Timing::new(Vec::new(), &actions, Host::default());
+
+    assert_eq!(Duration::from_secs(300), timing.pacstrap);
+    assert_eq!(Duration::from_secs(12), timing.chroot);
+    assert_eq!(3, timing.slowest_actions.len());
+    assert_eq!(Duration::from_secs(300), timing.slowest_actions[0].duration,);
+    assert!(timing.slowest_actions.iter().all(|a| a.commands.is_empty()));
+}
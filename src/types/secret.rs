@@ -0,0 +1,64 @@
+use serde::{
+    Deserialize,
+    Serialize,
+    Serializer,
+};
+
+/// Placeholder for secrets in serialized manifests, reports, and logs
+pub const REDACTED: &str = "<redacted>";
+
+/// Secret manifest value, e.g. LUKS passphrase or password hash.
+///
+/// Its value is never Debug-printed or serialized,
+/// so that it cannot leak into reports, errors, or logs.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    /// Returns the actual value, which should only be
+    /// written to files or fed to commands via stdin.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret({REDACTED})")
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+#[test]
+fn test_secret() {
+    let secret: Secret =
+        serde_yaml::from_str("mysupersecret").expect("failed to parse secret");
+
+    assert_eq!("mysupersecret", secret.expose());
+    assert_eq!("Secret(<redacted>)", format!("{secret:?}"));
+    assert_eq!(
+        "\"<redacted>\"",
+        serde_json::to_string(&secret).expect("failed to serialize secret"),
+    );
+}
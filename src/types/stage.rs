@@ -26,6 +26,9 @@ pub enum Stage {
     )]
     ChrootAli,
 
+    #[value(alias = "boot", alias = "stage-bootloader")]
+    Bootloader,
+
     #[value(
         alias = "chroot_user",
         alias = "stage-chrootuser",
@@ -43,11 +46,12 @@ pub enum Stage {
     PostInstallUser,
 }
 
-pub const STAGES: [Stage; 6] = [
+pub const STAGES: [Stage; 7] = [
     Stage::Mountpoints,
     Stage::Bootstrap,
     Stage::Routines,
     Stage::ChrootAli,
+    Stage::Bootloader,
     Stage::ChrootUser,
     Stage::PostInstallUser,
 ];
@@ -72,6 +76,10 @@ pub struct StageActions {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub chroot_ali: Vec<ActionChrootAli>,
 
+    #[serde(rename = "stage-bootloader")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bootloader: Vec<ActionBootloader>,
+
     #[serde(rename = "stage-chroot_user")]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub chroot_user: Vec<ActionChrootUser>,
@@ -88,6 +96,7 @@ impl std::fmt::Display for Stage {
             Self::Bootstrap => write!(f, "stage-bootstrap"),
             Self::Routines => write!(f, "stage-routines"),
             Self::ChrootAli => write!(f, "stage-chroot_ali"),
+            Self::Bootloader => write!(f, "stage-bootloader"),
             Self::ChrootUser => write!(f, "stage-chroot_user"),
             Self::PostInstallUser => write!(f, "stage-postinstall_user"),
         }
@@ -105,6 +114,7 @@ impl From<Vec<Action>> for StageActions {
                 Action::Bootstrap(action) => s.bootstrap.push(action),
                 Action::Routines(action) => s.routines.push(action),
                 Action::ChrootAli(action) => s.chroot_ali.push(action),
+                Action::Bootloader(action) => s.bootloader.push(action),
                 Action::ChrootUser(action) => s.chroot_user.push(action),
                 Action::UserPostInstall(action) => s.postinstall_user.push(action),
             }
@@ -0,0 +1,667 @@
+//! Executors perform side effects (commands, file I/O, and downloads) for ali-rs.
+//!
+//! Free functions in [`super::shell`] and [`super::fs`] go through
+//! the current executor of the thread, which is [`Real`] by default.
+//! [`Recorder`] only records what would be done (dry-run),
+//! and `Mock` also replies with scripted output in tests.
+
+use std::cell::RefCell;
+use std::io::{
+    ErrorKind,
+    Read,
+    Write,
+};
+use std::os::unix::fs::PermissionsExt;
+use std::process::{
+    Command,
+    Stdio,
+};
+use std::rc::Rc;
+use std::thread::JoinHandle;
+use std::time::{
+    Duration,
+    Instant,
+};
+
+use super::journal;
+use super::shell::CmdError;
+use crate::errors::AliError;
+use crate::hooks::Downloader;
+use crate::types::log::{
+    self,
+    CmdLog,
+    TAIL_LINES,
+};
+
+pub trait Executor {
+    /// Executes `cmd` with `args`, discarding output
+    fn exec(&self, cmd: &str, args: &[&str]) -> Result<(), AliError>;
+
+    /// Executes `cmd` with `args`, returning stdout
+    fn exec_with_output(
+        &self,
+        cmd: &str,
+        args: &[&str],
+    ) -> Result<Vec<u8>, AliError>;
+
+    /// Executes `cmd` with `args`, writing `stdin` to its stdin.
+    /// Implementations must never log or record `stdin`,
+    /// which usually holds secrets.
+    fn exec_with_stdin(
+        &self,
+        cmd: &str,
+        args: &[&str],
+        stdin: &[u8],
+    ) -> Result<(), AliError>;
+
+    /// Pipes stdout of `producer` to stdin of `consumer`
+    fn pipe(
+        &self,
+        producer: (&str, &[&str]),
+        consumer: (&str, &[&str]),
+    ) -> Result<(), AliError>;
+
+    /// Writes `content` to `path`, creating parent directories if needed
+    fn write_file(&self, path: &str, content: &[u8]) -> Result<(), AliError>;
+
+    /// Sets Unix permission bits of `path` to `mode`
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), AliError>;
+
+    /// Reads `path` to string
+    fn read_file(&self, path: &str) -> Result<String, AliError>;
+
+    /// Downloads remote `url`, returning the body
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, AliError>;
+}
+
+thread_local! {
+    static EXECUTOR: RefCell<Rc<dyn Executor>> = RefCell::new(Rc::new(Real));
+}
+
+/// Replaces the current executor of this thread,
+/// returning the previous one.
+pub fn set_executor(executor: Rc<dyn Executor>) -> Rc<dyn Executor> {
+    EXECUTOR.with(|current| current.replace(executor))
+}
+
+pub(super) fn current() -> Rc<dyn Executor> {
+    EXECUTOR.with(|current| current.borrow().clone())
+}
+
+/// Executes commands and writes files on the live system
+pub struct Real;
+
+/// Records commands and file writes without performing them.
+///
+/// Commands with output return a placeholder `<cmd>`,
+/// so that callers parsing output can proceed.
+#[derive(Default)]
+pub struct Recorder {
+    records: RefCell<Vec<String>>,
+}
+
+impl Recorder {
+    /// Returns recorded commands and file writes, in order
+    pub fn records(&self) -> Vec<String> {
+        self.records.borrow().clone()
+    }
+
+    fn record(&self, record: String) {
+        self.records.borrow_mut().push(record);
+    }
+
+    fn record_cmd(&self, line: String) {
+        journal_cmd(&line, 0);
+        self.record(line);
+    }
+}
+
+/// Records like [`Recorder`], and replies to commands starting with
+/// scripted prefixes with scripted stdout or exit codes.
+#[cfg(test)]
+#[derive(Default)]
+pub struct Mock {
+    recorder: Recorder,
+    replies: Vec<(String, Result<Vec<u8>, i32>)>,
+}
+
+#[cfg(test)]
+impl Mock {
+    /// Replies `stdout` to commands starting with `prefix`
+    pub fn reply(mut self, prefix: &str, stdout: &str) -> Self {
+        self.replies
+            .push((prefix.to_string(), Ok(stdout.as_bytes().to_vec())));
+
+        self
+    }
+
+    /// Fails commands starting with `prefix` with exit `code`
+    pub fn fail(mut self, prefix: &str, code: i32) -> Self {
+        self.replies.push((prefix.to_string(), Err(code)));
+
+        self
+    }
+
+    pub fn records(&self) -> Vec<String> {
+        self.recorder.records()
+    }
+
+    fn run(&self, line: String) -> Result<Vec<u8>, AliError> {
+        let reply = self
+            .replies
+            .iter()
+            .find(|(prefix, _)| line.starts_with(prefix.as_str()))
+            .map(|(_, reply)| reply.clone())
+            .unwrap_or(Ok(Vec::new()));
+
+        self.recorder.record(line.clone());
+        journal_cmd(&line, *reply.as_ref().err().unwrap_or(&0));
+
+        reply.map_err(|code| {
+            AliError::CmdFailed {
+                error: CmdError::ErrRun {
+                    code: Some(code),
+                    stdout: None,
+                    stderr: None,
+                },
+                context: format!("mock command {line} exited with {code}"),
+            }
+        })
+    }
+}
+
+impl Real {
+    // Runs `command`, tee-ing its stdout and stderr to console,
+    // and logs it to the journal. If `stdin` is Some, it is written
+    // to stdin of `command` and then closed.
+    fn run(
+        command: &mut Command,
+        line: String,
+        stdin: Option<&[u8]>,
+    ) -> Result<(), AliError> {
+        let cmd = command.get_program().to_string_lossy().to_string();
+        let start = Instant::now();
+        journal::command_start(&line);
+
+        if stdin.is_some() {
+            command.stdin(Stdio::piped());
+        }
+
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                AliError::CmdFailed {
+                    error: CmdError::ErrSpawn { error },
+                    context: format!("command {cmd} failed to spawn"),
+                }
+            })?;
+
+        // Tee before writing stdin, so that cmd never blocks on full pipes
+        // Keep stdout clean for events streamed to it
+        let stdout = match journal::events_on_stdout() {
+            true => tee(child.stdout.take(), std::io::stderr),
+            false => tee(child.stdout.take(), std::io::stdout),
+        };
+        let stderr = tee(child.stderr.take(), std::io::stderr);
+
+        // Dropping the handle closes stdin, so that cmd sees EOF
+        let written = match (stdin, child.stdin.take()) {
+            (Some(input), Some(mut handle)) => handle.write_all(input),
+            _ => Ok(()),
+        };
+
+        // Spawned but may still fail
+        let status = child.wait();
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        let code = status.as_ref().ok().and_then(|status| status.code());
+        let log = CmdLog {
+            cmd: line,
+            exit_code: code,
+            duration: start.elapsed(),
+            stdout_tail: log::tail(&stdout, TAIL_LINES),
+            stderr_tail: log::tail(&stderr, TAIL_LINES),
+        };
+
+        let (stdout_tail, stderr_tail) =
+            (log.stdout_tail.join("\n"), log.stderr_tail.join("\n"));
+
+        journal::command(log, &stdout, &stderr);
+
+        if let Err(error) = written {
+            return Err(AliError::CmdFailed {
+                error: CmdError::ErrSpawn { error },
+                context: format!("failed to write stdin of command {cmd}"),
+            });
+        }
+
+        let status = status.map_err(|error| {
+            AliError::CmdFailed {
+                error: CmdError::ErrSpawn { error },
+                context: format!("command {cmd} failed to run"),
+            }
+        })?;
+
+        let context = match status.code() {
+            Some(0) => return Ok(()),
+            Some(code) => {
+                format!("command {cmd} exited with non-zero status {code}")
+            }
+            None => format!("command {cmd} terminated by signal"),
+        };
+
+        Err(AliError::CmdFailed {
+            error: CmdError::ErrRun {
+                code: status.code(),
+                stdout: Some(stdout_tail.into_bytes()),
+                stderr: Some(stderr_tail.into_bytes()),
+            },
+            context,
+        })
+    }
+}
+
+impl Executor for Real {
+    fn exec(&self, cmd: &str, args: &[&str]) -> Result<(), AliError> {
+        Self::run(Command::new(cmd).args(args), fmt_cmd(cmd, args), None)
+    }
+
+    fn exec_with_output(
+        &self,
+        cmd: &str,
+        args: &[&str],
+    ) -> Result<Vec<u8>, AliError> {
+        let start = Instant::now();
+        journal::command_start(&fmt_cmd(cmd, args));
+
+        let output = Command::new(cmd).args(args).output().map_err(|err| {
+            AliError::CmdFailed {
+                error: CmdError::ErrSpawn { error: err },
+                context: format!("command {cmd} failed to spawn"),
+            }
+        })?;
+
+        // Output is returned to callers, so it is not tee'd
+        journal::command(
+            CmdLog {
+                cmd: fmt_cmd(cmd, args),
+                exit_code: output.status.code(),
+                duration: start.elapsed(),
+                stdout_tail: log::tail(&output.stdout, TAIL_LINES),
+                stderr_tail: log::tail(&output.stderr, TAIL_LINES),
+            },
+            &output.stdout,
+            &output.stderr,
+        );
+
+        if !output.status.success() {
+            return Err(AliError::CmdFailed {
+                error: CmdError::ErrRun {
+                    code: output.status.code(),
+                    stdout: Some(output.stdout),
+                    stderr: Some(output.stderr),
+                },
+                context: format!(
+                    "command {cmd} {} exited with non-zero status",
+                    args.join(" ")
+                ),
+            });
+        }
+
+        Ok(output.stdout)
+    }
+
+    fn exec_with_stdin(
+        &self,
+        cmd: &str,
+        args: &[&str],
+        stdin: &[u8],
+    ) -> Result<(), AliError> {
+        Self::run(
+            Command::new(cmd).args(args),
+            fmt_stdin(cmd, args),
+            Some(stdin),
+        )
+    }
+
+    fn pipe(
+        &self,
+        producer_cmd: (&str, &[&str]),
+        consumer_cmd: (&str, &[&str]),
+    ) -> Result<(), AliError> {
+        let (producer, consumer) = (producer_cmd.0, consumer_cmd.0);
+        let mut child = Command::new(producer)
+            .args(producer_cmd.1)
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                AliError::CmdFailed {
+                    error: CmdError::ErrSpawn { error },
+                    context: format!("producer {producer} failed to spawn"),
+                }
+            })?;
+
+        let result = match child.stdout.take() {
+            Some(stdout) => {
+                Self::run(
+                    Command::new(consumer).args(consumer_cmd.1).stdin(stdout),
+                    fmt_pipe(producer_cmd, consumer_cmd),
+                    None,
+                )
+            }
+            None => {
+                Err(AliError::AliRsBug(format!(
+                    "no stdout piped from producer {producer}"
+                )))
+            }
+        };
+
+        // Reap producer, which exits once consumer closes the pipe
+        let _ = child.wait();
+
+        result
+    }
+
+    fn write_file(&self, path: &str, content: &[u8]) -> Result<(), AliError> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent).map_err(|err| {
+                AliError::FileError(
+                    err,
+                    format!("failed to create dir for {path}"),
+                )
+            })?;
+        }
+
+        std::fs::write(path, content).map_err(|err| {
+            AliError::FileError(err, format!("failed to write {path}"))
+        })
+    }
+
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), AliError> {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+            .map_err(|err| {
+                AliError::FileError(err, format!("failed to chmod {path}"))
+            })
+    }
+
+    fn read_file(&self, path: &str) -> Result<String, AliError> {
+        std::fs::read_to_string(path)
+            .map_err(|err| AliError::FileError(err, path.to_string()))
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, AliError> {
+        Downloader::new_from_url(url)?.get_bytes()
+    }
+}
+
+impl Executor for Recorder {
+    fn exec(&self, cmd: &str, args: &[&str]) -> Result<(), AliError> {
+        self.record_cmd(fmt_cmd(cmd, args));
+
+        Ok(())
+    }
+
+    fn exec_with_output(
+        &self,
+        cmd: &str,
+        args: &[&str],
+    ) -> Result<Vec<u8>, AliError> {
+        self.record_cmd(fmt_cmd(cmd, args));
+
+        Ok(format!("<{cmd}>").into_bytes())
+    }
+
+    fn exec_with_stdin(
+        &self,
+        cmd: &str,
+        args: &[&str],
+        _stdin: &[u8],
+    ) -> Result<(), AliError> {
+        self.record_cmd(fmt_stdin(cmd, args));
+
+        Ok(())
+    }
+
+    fn pipe(
+        &self,
+        producer: (&str, &[&str]),
+        consumer: (&str, &[&str]),
+    ) -> Result<(), AliError> {
+        self.record_cmd(fmt_pipe(producer, consumer));
+
+        Ok(())
+    }
+
+    fn write_file(&self, path: &str, _content: &[u8]) -> Result<(), AliError> {
+        self.record(format!("write {path}"));
+
+        Ok(())
+    }
+
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), AliError> {
+        self.record(format!("chmod {mode:o} {path}"));
+
+        Ok(())
+    }
+
+    // Files in the target may not exist, because nothing was installed
+    fn read_file(&self, path: &str) -> Result<String, AliError> {
+        Ok(std::fs::read_to_string(path).unwrap_or_default())
+    }
+
+    // Nothing is downloaded, and a placeholder is returned like commands
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, AliError> {
+        self.record(format!("fetch {url}"));
+
+        Ok(format!("<{url}>").into_bytes())
+    }
+}
+
+#[cfg(test)]
+impl Executor for Mock {
+    fn exec(&self, cmd: &str, args: &[&str]) -> Result<(), AliError> {
+        self.run(fmt_cmd(cmd, args)).map(|_| ())
+    }
+
+    fn exec_with_output(
+        &self,
+        cmd: &str,
+        args: &[&str],
+    ) -> Result<Vec<u8>, AliError> {
+        self.run(fmt_cmd(cmd, args))
+    }
+
+    fn exec_with_stdin(
+        &self,
+        cmd: &str,
+        args: &[&str],
+        _stdin: &[u8],
+    ) -> Result<(), AliError> {
+        self.run(fmt_stdin(cmd, args)).map(|_| ())
+    }
+
+    fn pipe(
+        &self,
+        producer: (&str, &[&str]),
+        consumer: (&str, &[&str]),
+    ) -> Result<(), AliError> {
+        self.run(fmt_pipe(producer, consumer)).map(|_| ())
+    }
+
+    fn write_file(&self, path: &str, content: &[u8]) -> Result<(), AliError> {
+        self.recorder.write_file(path, content)
+    }
+
+    fn set_mode(&self, path: &str, mode: u32) -> Result<(), AliError> {
+        self.recorder.set_mode(path, mode)
+    }
+
+    fn read_file(&self, path: &str) -> Result<String, AliError> {
+        self.recorder.read_file(path)
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>, AliError> {
+        let line = format!("fetch {url}");
+        let reply = self
+            .replies
+            .iter()
+            .find(|(prefix, _)| line.starts_with(prefix.as_str()))
+            .map(|(_, reply)| reply.clone());
+
+        self.recorder.record(line);
+
+        match reply {
+            None => Ok(format!("<{url}>").into_bytes()),
+            Some(reply) => {
+                reply.map_err(|code| {
+                    AliError::HookError(format!("mock fetch {url}: {code}"))
+                })
+            }
+        }
+    }
+}
+
+// Copies output of child process to console as it comes,
+// returning all of the output once the child closes it
+fn tee<R, W>(reader: Option<R>, console: fn() -> W) -> JoinHandle<Vec<u8>>
+where
+    R: Read + Send + 'static,
+    W: Write + 'static,
+{
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let Some(mut reader) = reader else {
+            return output;
+        };
+
+        let mut chunk = [0u8; 8192];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => {
+                    let mut console = console();
+                    let _ = console.write_all(&chunk[..n]);
+                    let _ = console.flush();
+
+                    output.extend_from_slice(&chunk[..n]);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+
+        output
+    })
+}
+
+// Logs commands that were not actually run by dry-run and mock executors
+fn journal_cmd(line: &str, exit_code: i32) {
+    journal::command_start(line);
+
+    let log = CmdLog {
+        cmd: line.to_string(),
+        exit_code: Some(exit_code),
+        duration: Duration::ZERO,
+        stdout_tail: Vec::new(),
+        stderr_tail: Vec::new(),
+    };
+
+    journal::command(log, &[], &[]);
+}
+
+// Formats command as shell-quoted line
+fn fmt_cmd(cmd: &str, args: &[&str]) -> String {
+    let argv = std::iter::once(cmd).chain(args.iter().copied());
+
+    shlex::join(argv)
+}
+
+// Stdin content is never recorded
+fn fmt_stdin(cmd: &str, args: &[&str]) -> String {
+    format!("{} < <stdin>", fmt_cmd(cmd, args))
+}
+
+fn fmt_pipe(producer: (&str, &[&str]), consumer: (&str, &[&str])) -> String {
+    format!(
+        "{} | {}",
+        fmt_cmd(producer.0, producer.1),
+        fmt_cmd(consumer.0, consumer.1)
+    )
+}
+
+#[test]
+fn test_mock() {
+    let mock = Mock::default()
+        .reply("blkid -s UUID", "uuid-vda2\n")
+        .fail("false", 1)
+        .reply("fetch https://example.com/motd", "hello");
+
+    assert_eq!(
+        b"uuid-vda2\n".to_vec(),
+        mock.exec_with_output("blkid", &["-s", "UUID", "/dev/vda2"])
+            .expect("unexpected error"),
+    );
+    assert!(mock.exec("false", &[]).is_err());
+    mock.exec("sh", &["-c", "echo 'hello world'"])
+        .expect("unexpected error");
+    mock.write_file("/mnt/etc/hostname", b"arch")
+        .expect("unexpected error");
+    mock.exec_with_stdin("cryptsetup", &["luksOpen", "/dev/vda2"], b"secret")
+        .expect("unexpected error");
+    assert_eq!(
+        b"hello".to_vec(),
+        mock.fetch("https://example.com/motd")
+            .expect("unexpected error"),
+    );
+
+    assert_eq!(
+        vec![
+            "blkid -s UUID /dev/vda2",
+            "false",
+            "sh -c \"echo 'hello world'\"",
+            "write /mnt/etc/hostname",
+            "cryptsetup luksOpen /dev/vda2 < <stdin>",
+            "fetch https://example.com/motd",
+        ],
+        mock.records(),
+    );
+}
+
+#[test]
+fn test_real_journal() {
+    use crate::types::action::ActionRoutine;
+
+    let err = Real
+        .exec("sh", &["-c", "echo out; echo err >&2; exit 3"])
+        .expect_err("unexpected success");
+
+    match err {
+        AliError::CmdFailed {
+            error:
+                CmdError::ErrRun {
+                    code: Some(3),
+                    stderr: Some(ref stderr),
+                    ..
+                },
+            ..
+        } => assert_eq!(b"err".to_vec(), *stderr),
+        _ => panic!("unexpected result"),
+    }
+
+    let failed = journal::failed(&ActionRoutine::GenFstab.into(), &err);
+    assert_eq!(1, failed.len());
+    assert_eq!(Some(3), failed[0].exit_code);
+    assert_eq!(vec!["out"], failed[0].stdout_tail);
+    assert_eq!(vec!["err"], failed[0].stderr_tail);
+
+    Real.exec_with_stdin("cat", &[], b"secret")
+        .expect("unexpected error");
+    journal::done(ActionRoutine::RootPasswd);
+
+    let actions = journal::actions();
+    assert_eq!(1, actions.len());
+    assert_eq!("cat < <stdin>", actions[0].commands[0].cmd);
+}
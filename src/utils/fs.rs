@@ -1,6 +1,39 @@
+use super::executor;
+use crate::errors::AliError;
+
 pub fn file_exists<P>(path: P) -> bool
 where
     P: AsRef<std::path::Path>,
 {
     path.as_ref().exists()
 }
+
+/// Writes `content` to `path`, creating parent directories if needed
+pub fn write_file<C>(path: &str, content: C) -> Result<(), AliError>
+where
+    C: AsRef<[u8]>,
+{
+    executor::current().write_file(path, content.as_ref())
+}
+
+/// Sets Unix permission bits of `path` to `mode`
+pub fn set_mode(path: &str, mode: u32) -> Result<(), AliError> {
+    executor::current().set_mode(path, mode)
+}
+
+/// Reads `path` to string
+pub fn read_file(path: &str) -> Result<String, AliError> {
+    executor::current().read_file(path)
+}
+
+/// Downloads remote `url`, returning the body
+pub fn fetch(url: &str) -> Result<Vec<u8>, AliError> {
+    executor::current().fetch(url)
+}
+
+/// Downloads remote `url`, returning the body as string
+pub fn fetch_string(url: &str) -> Result<String, AliError> {
+    String::from_utf8(fetch(url)?).map_err(|err| {
+        AliError::HookError(format!("body of {url} is not string: {err}"))
+    })
+}
@@ -0,0 +1,382 @@
+//! Journal keeps logs of commands run by executors, and groups them
+//! into actions as actions are done.
+//!
+//! Full command output is also appended to the log file, if set,
+//! and progress events are streamed to the events writer, if set.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Write;
+use std::time::Instant;
+
+use crate::errors::AliError;
+use crate::types::action::Action;
+use crate::types::event::Event;
+use crate::types::log::{
+    self,
+    ActionLog,
+    CmdLog,
+    StageLog,
+};
+use crate::types::report::Report;
+use crate::types::stage::Stage;
+
+#[derive(Default)]
+struct Journal {
+    // Commands run since the last action was done
+    pending: Vec<CmdLog>,
+    actions: Vec<ActionLog>,
+    stages: Vec<StageLog>,
+    log_file: Option<File>,
+
+    events: Option<Box<dyn Write>>,
+    events_on_stdout: bool,
+
+    stage: String,
+    stage_start: Option<Instant>,
+
+    // Index of the current action within stage, whether
+    // actionStart was emitted for it, and when the previous action ended
+    index: usize,
+    action_started: bool,
+    action_start: Option<Instant>,
+}
+
+thread_local! {
+    static JOURNAL: RefCell<Journal> = RefCell::new(Journal::default());
+}
+
+impl Journal {
+    fn emit(&mut self, event: Event) {
+        if let Some(ref mut writer) = self.events {
+            // Events must not fail the installation
+            let _ = writer.write_all(event.to_json_line().as_bytes());
+            let _ = writer.flush();
+        }
+    }
+
+    fn start_action(&mut self) {
+        if self.action_started {
+            return;
+        }
+
+        self.action_started = true;
+        self.emit(Event::ActionStart {
+            stage: self.stage.clone(),
+            index: self.index,
+        });
+    }
+
+    fn end_stage(&mut self, failed: bool) -> std::time::Duration {
+        let duration = self
+            .stage_start
+            .map(|start| start.elapsed())
+            .unwrap_or_default();
+
+        self.stages.push(StageLog {
+            stage: self.stage.clone(),
+            start: log::unix_millis_ago(duration),
+            duration,
+            failed,
+        });
+
+        duration
+    }
+
+    fn next_action(&mut self) {
+        self.index += 1;
+        self.action_started = false;
+        self.action_start = Some(Instant::now());
+    }
+}
+
+/// Truncates or creates `path` as the log file for full command output
+pub fn set_log_file(path: &str) -> Result<(), AliError> {
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(parent).map_err(|err| {
+            AliError::FileError(err, format!("failed to create dir for {path}"))
+        })?;
+    }
+
+    let file = File::create(path).map_err(|err| {
+        AliError::FileError(err, format!("failed to create log file {path}"))
+    })?;
+
+    JOURNAL.with(|journal| journal.borrow_mut().log_file = Some(file));
+
+    Ok(())
+}
+
+/// Streams progress events to `path` as JSON lines.
+///
+/// If `path` is `-`, events are written to stdout,
+/// and command output is instead tee'd to stderr.
+/// File descriptors can be given as `/dev/fd/<fd>`.
+pub fn set_events(path: &str) -> Result<(), AliError> {
+    let (writer, on_stdout): (Box<dyn Write>, bool) = match path {
+        "-" => (Box::new(std::io::stdout()), true),
+        _ => {
+            let file = File::options()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| {
+                    AliError::FileError(
+                        err,
+                        format!("failed to open events file {path}"),
+                    )
+                })?;
+
+            (Box::new(file), false)
+        }
+    };
+
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        journal.events = Some(writer);
+        journal.events_on_stdout = on_stdout;
+    });
+
+    Ok(())
+}
+
+/// Returns whether events are streamed to stdout
+pub fn events_on_stdout() -> bool {
+    JOURNAL.with(|journal| journal.borrow().events_on_stdout)
+}
+
+pub fn stage_start(stage: &Stage) {
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        journal.stage = stage.to_string();
+        journal.stage_start = Some(Instant::now());
+        journal.action_start = journal.stage_start;
+        journal.index = 0;
+        journal.action_started = false;
+
+        let stage = journal.stage.clone();
+        journal.emit(Event::StageStart { stage });
+    })
+}
+
+pub fn stage_end() {
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        let duration = journal.end_stage(false);
+
+        let stage = journal.stage.clone();
+        journal.emit(Event::StageEnd { stage, duration });
+    })
+}
+
+pub fn stage_failed(err: &AliError) {
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        journal.end_stage(true);
+
+        let stage = journal.stage.clone();
+        journal.emit(Event::StageFailed {
+            stage,
+            error: err.to_string(),
+        });
+    })
+}
+
+/// Marks start of command `line`, which also starts
+/// the current action if it has not started yet
+pub(super) fn command_start(line: &str) {
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        journal.start_action();
+
+        let (stage, index) = (journal.stage.clone(), journal.index);
+        journal.emit(Event::Command {
+            stage,
+            index,
+            cmd: line.to_string(),
+        });
+    })
+}
+
+/// Logs command with its full output
+pub(super) fn command(log: CmdLog, stdout: &[u8], stderr: &[u8]) {
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+
+        if let Some(ref mut file) = journal.log_file {
+            // Logging must not fail the installation
+            let _ = write_command(file, &log, stdout, stderr);
+        }
+
+        journal.pending.push(log);
+    })
+}
+
+/// Marks `action` as done, grouping commands run since
+/// the last action was done into its [`ActionLog`].
+///
+/// Returns `action`, so that callers can push it to their actions:
+/// ```ignore
+/// actions.push(journal::done(action));
+/// ```
+pub fn done<A>(action: A) -> A
+where
+    A: Clone + Into<Action>,
+{
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        let duration = journal
+            .action_start
+            .map(|start| start.elapsed())
+            .unwrap_or_default();
+
+        let commands = std::mem::take(&mut journal.pending);
+        let log = ActionLog {
+            action: action.clone().into(),
+            start: log::unix_millis_ago(duration),
+            duration,
+            commands,
+        };
+
+        if let Some(ref mut file) = journal.log_file {
+            let _ = writeln!(file, "==> done {}", json(&log.action));
+        }
+
+        journal.start_action();
+        let (stage, index) = (journal.stage.clone(), journal.index);
+        journal.emit(Event::ActionEnd {
+            stage,
+            index,
+            action: log.action.clone(),
+            duration,
+        });
+
+        journal.actions.push(log);
+        journal.next_action();
+    });
+
+    action
+}
+
+/// Marks `action` as failed with `err`, and takes commands run
+/// by the failed action, i.e. commands run since the last action was done
+pub fn failed(action: &Action, err: &AliError) -> Vec<CmdLog> {
+    JOURNAL.with(|journal| {
+        let mut journal = journal.borrow_mut();
+        let (stage, index) = (journal.stage.clone(), journal.index);
+
+        journal.emit(Event::ActionFailed {
+            stage,
+            index,
+            action: action.clone(),
+            error: err.to_string(),
+        });
+
+        journal.next_action();
+        std::mem::take(&mut journal.pending)
+    })
+}
+
+/// Emits `report` as the final event
+pub fn report(report: &Report) {
+    let report = match report.to_json() {
+        serde_json::Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+
+    JOURNAL.with(|journal| journal.borrow_mut().emit(Event::Report { report }))
+}
+
+/// Returns logs of actions done, in order
+pub fn actions() -> Vec<ActionLog> {
+    JOURNAL.with(|journal| journal.borrow().actions.clone())
+}
+
+/// Returns logs of stages applied or failed, in order
+pub fn stages() -> Vec<StageLog> {
+    JOURNAL.with(|journal| journal.borrow().stages.clone())
+}
+
+fn write_command(
+    file: &mut File,
+    log: &CmdLog,
+    stdout: &[u8],
+    stderr: &[u8],
+) -> std::io::Result<()> {
+    writeln!(file, "$ {}", log.cmd)?;
+
+    if !stdout.is_empty() {
+        writeln!(file, "[stdout]")?;
+        file.write_all(stdout)?;
+    }
+    if !stderr.is_empty() {
+        writeln!(file, "[stderr]")?;
+        file.write_all(stderr)?;
+    }
+
+    let code = match log.exit_code {
+        Some(code) => code.to_string(),
+        None => "none".to_string(),
+    };
+
+    writeln!(file, "[exit code {code}, took {:?}]", log.duration)
+}
+
+fn json(action: &Action) -> String {
+    serde_json::to_string(action).unwrap_or_default()
+}
+
+#[test]
+fn test_events() {
+    use super::executor::{
+        Executor,
+        Mock,
+    };
+    use crate::types::action::ActionMountpoints;
+
+    let path = std::env::temp_dir().join("ali-rs-test-events.jsonl");
+    let _ = std::fs::remove_file(&path);
+    set_events(path.to_str().unwrap()).expect("failed to set events");
+
+    let mock = Mock::default().fail("mount", 32);
+
+    stage_start(&Stage::Mountpoints);
+    mock.exec("mkdir", &["-p", "/alitarget"]).unwrap();
+    done(ActionMountpoints::MkdirRootFs);
+
+    let err = mock
+        .exec("mount", &["/dev/vda2", "/alitarget"])
+        .unwrap_err();
+    let commands =
+        failed(&Action::Mountpoints(ActionMountpoints::MountRootFs), &err);
+    stage_failed(&err);
+
+    assert_eq!(1, commands.len());
+    assert_eq!(Some(32), commands[0].exit_code);
+
+    let content = std::fs::read_to_string(&path).expect("no events file");
+    std::fs::remove_file(&path).expect("failed to remove events file");
+
+    let events: Vec<serde_json::Value> = content
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("bad json line"))
+        .collect();
+
+    let expected = vec![
+        ("stageStart", None),
+        ("actionStart", Some(0)),
+        ("command", Some(0)),
+        ("actionEnd", Some(0)),
+        ("actionStart", Some(1)),
+        ("command", Some(1)),
+        ("actionFailed", Some(1)),
+        ("stageFailed", None),
+    ];
+
+    assert_eq!(expected.len(), events.len());
+    for ((event, index), actual) in expected.into_iter().zip(events) {
+        assert_eq!(event, actual["event"]);
+        assert_eq!("stage-mountpoints", actual["stage"]);
+        assert_eq!(index, actual["index"].as_u64());
+    }
+}
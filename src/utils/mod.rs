@@ -1,2 +1,4 @@
+pub mod executor;
 pub mod fs;
+pub mod journal;
 pub mod shell;
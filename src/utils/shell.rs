@@ -1,12 +1,9 @@
-use std::process::{
-    Command,
-    Stdio,
-};
 use std::{
     env,
     fs,
 };
 
+use super::executor;
 use crate::errors::AliError;
 
 pub enum CmdError {
@@ -25,56 +22,7 @@ pub enum CmdError {
 /// Output is discarded (printed to console) and not used.
 /// Throw an error if `cmd` fails to spawn or exit code != 0
 pub fn exec(cmd: &str, args: &[&str]) -> Result<(), AliError> {
-    match Command::new(cmd).args(args).spawn() {
-        Ok(mut result) => {
-            match result.wait() {
-                // Spawned but may still fail
-                Ok(r) => {
-                    match r.code() {
-                        Some(0) => Ok(()),
-                        Some(code) => {
-                            Err(AliError::CmdFailed {
-                                error: CmdError::ErrRun {
-                                    code: Some(code),
-                                    stdout: None,
-                                    stderr: None,
-                                },
-                                context: format!(
-                            "command {cmd} exited with non-zero status {code}"
-                        ),
-                            })
-                        }
-                        None => {
-                            Err(AliError::CmdFailed {
-                                error: CmdError::ErrRun {
-                                    code: None,
-                                    stdout: None,
-                                    stderr: None,
-                                },
-                                context: format!(
-                                    "command {cmd} terminated by signal"
-                                ),
-                            })
-                        }
-                    }
-                }
-                Err(error) => {
-                    Err(AliError::CmdFailed {
-                        error: CmdError::ErrSpawn { error },
-                        context: format!("command ${cmd} failed to run"),
-                    })
-                }
-            }
-        }
-
-        // Failed to spawn
-        Err(error) => {
-            Err(AliError::CmdFailed {
-                error: CmdError::ErrSpawn { error },
-                context: format!("command {cmd} failed to spawn"),
-            })
-        }
-    }
+    executor::current().exec(cmd, args)
 }
 
 /// Executes command `cmd` with arguments `args`,
@@ -82,33 +30,21 @@ pub fn exec(cmd: &str, args: &[&str]) -> Result<(), AliError> {
 /// or stderr output as lossy UTF-8 strings.
 ///
 /// Throws an error if command fails to spawn
-#[allow(unused)]
 pub fn exec_with_output(cmd: &str, args: &[&str]) -> Result<Vec<u8>, AliError> {
-    let output = Command::new(cmd).args(args).output().map_err(|err| {
-        AliError::CmdFailed {
-            error: CmdError::ErrSpawn { error: err },
-            context: format!("command {cmd} failed to spawn"),
-        }
-    })?;
-
-    if !output.status.success() {
-        let stdout = Some(output.stdout);
-        let stderr = Some(output.stderr);
-
-        return Err(AliError::CmdFailed {
-            error: CmdError::ErrRun {
-                code: output.status.code(),
-                stdout,
-                stderr,
-            },
-            context: format!(
-                "command {cmd} {} exited with non-zero status",
-                args.join(" ")
-            ),
-        });
-    }
+    executor::current().exec_with_output(cmd, args)
+}
 
-    Ok(output.stdout)
+/// Executes command `cmd` with arguments `args`, writing `stdin`
+/// to its stdin without a shell. Used to pass secrets,
+/// which would otherwise show up in the process list.
+///
+/// `stdin` is never logged or included in errors.
+pub fn exec_with_stdin(
+    cmd: &str,
+    args: &[&str],
+    stdin: &[u8],
+) -> Result<(), AliError> {
+    executor::current().exec_with_stdin(cmd, args, stdin)
 }
 
 /// Pipe stdout of `producer_cmd` to stdin of `consumer_cmd`,
@@ -122,68 +58,7 @@ pub fn pipe(
     producer_cmd: (&str, &[&str]),
     consumer_cmd: (&str, &[&str]),
 ) -> Result<(), AliError> {
-    let producer = Command::new(producer_cmd.0)
-        .args(producer_cmd.1)
-        .stdout(Stdio::piped())
-        .spawn()
-        .unwrap_or_else(|_| {
-            panic!(
-                "failed to spawn producer {} {}",
-                consumer_cmd.0,
-                consumer_cmd.1.join(" ")
-            )
-        });
-
-    // Ignore fdisk stderr - it will be inherited from ali-rs
-    let consumer = Command::new(consumer_cmd.0)
-        .args(consumer_cmd.1)
-        .stdin(producer.stdout.unwrap())
-        .spawn()
-        .unwrap_or_else(|_| {
-            panic!(
-                "failed to spawn consumer {} {}",
-                consumer_cmd.0,
-                consumer_cmd.1.join(" ")
-            )
-        });
-
-    match consumer.wait_with_output() {
-        Ok(result) => {
-            match result.status.success() {
-                false => {
-                    Err(AliError::CmdFailed {
-                        error: CmdError::ErrRun {
-                            code: result.status.code(),
-                            stdout: None,
-                            stderr: Some(result.stderr),
-                        },
-                        context: format!(
-                            "consumer {} command exited with bad status: {}",
-                            consumer_cmd.0,
-                            result
-                                .status
-                                .code()
-                                .expect("failed to get exit code"),
-                        ),
-                    })
-                }
-                _ => Ok(()),
-            }
-        }
-        Err(error) => {
-            Err(AliError::CmdFailed {
-                error: CmdError::ErrRun {
-                    code: None,
-                    stdout: None,
-                    stderr: None,
-                },
-                context: format!(
-                    "consumer {} command failed to run: {error}",
-                    consumer_cmd.0
-                ),
-            })
-        }
-    }
+    executor::current().pipe(producer_cmd, consumer_cmd)
 }
 
 // Executes cmd_str with `sh -c`:
@@ -196,13 +71,25 @@ pub fn sh_c(cmd_str: &str) -> Result<(), AliError> {
     exec("sh", &["-c", cmd_str])
 }
 
-/// Executes cmd with `arch-chroot` to `location`
-/// Example: location: `"/mnt"`, cmd: `"cat /etc/fstab"`:
+/// Executes argv `args` with `arch-chroot` to `location`, without a shell
+/// Example: location: `"/mnt"`, args: `&["cat", "/etc/fstab"]`:
+/// ```shell
+/// arch-chroot /mnt cat /etc/fstab
+/// ```
+pub fn arch_chroot(location: &str, args: &[&str]) -> Result<(), AliError> {
+    let mut argv = vec![location];
+    argv.extend(args);
+
+    exec("arch-chroot", &argv)
+}
+
+/// Executes user-supplied shell `cmd` with `sh -c` in `arch-chroot`,
+/// so that pipes and redirections happen inside `location`:
 /// ```shell
-/// sh -c arch-chroot /mnt cat /etc/fstab
+/// arch-chroot /mnt sh -c {cmd}
 /// ```
-pub fn arch_chroot(location: &str, cmd: &str) -> Result<(), AliError> {
-    sh_c(&format!("arch-chroot {location} {cmd}"))
+pub fn arch_chroot_sh(location: &str, cmd: &str) -> Result<(), AliError> {
+    exec("arch-chroot", &[location, "sh", "-c", cmd])
 }
 
 pub fn in_path(program: &str) -> bool {